@@ -0,0 +1,76 @@
+//! 活动日志 (~/.local/share/rhizome/activity.log)
+//!
+//! 每次修改操作追加一行 JSON，仅追加、不修改已有内容
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// 一条活动记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub timestamp: DateTime<Local>,
+    pub action: String,
+    pub node_id: String,
+    pub title: String,
+}
+
+impl AuditRecord {
+    pub fn new(action: &str, node_id: &str, title: &str) -> Self {
+        Self {
+            timestamp: Local::now(),
+            action: action.to_string(),
+            node_id: node_id.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    /// 序列化为单行 JSON
+    pub fn to_line(&self) -> io::Result<String> {
+        serde_json::to_string(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// 将记录追加到日志文件
+pub fn log_action(path: &Path, record: &AuditRecord) -> io::Result<()> {
+    let line = record.to_line()?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_record_serialization() {
+        let record = AuditRecord::new("add", "id-1", "早起");
+        let line = record.to_line().unwrap();
+        assert!(!line.contains('\n'));
+        assert!(line.contains("\"action\":\"add\""));
+        assert!(line.contains("\"title\":\"早起\""));
+
+        let parsed: AuditRecord = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed, record);
+    }
+
+    #[test]
+    fn test_log_appends() {
+        let path = crate::test_util::temp_path("audit.log");
+        log_action(&path, &AuditRecord::new("add", "a", "A")).unwrap();
+        log_action(&path, &AuditRecord::new("delete", "a", "A")).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let actions: Vec<String> = content
+            .lines()
+            .map(|line| serde_json::from_str::<AuditRecord>(line).unwrap().action)
+            .collect();
+        assert_eq!(actions, vec!["add", "delete"]);
+    }
+}
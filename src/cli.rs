@@ -0,0 +1,212 @@
+//! 命令行参数解析
+
+use std::path::PathBuf;
+
+use crate::i18n::Lang;
+use crate::storage::Seed;
+
+/// 命令行选项
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CliOptions {
+    /// 仅校验数据文件并退出，不启动 TUI
+    pub check: bool,
+    /// 打印版本与数据文件路径后退出
+    pub version: bool,
+    /// 界面语言，覆盖配置文件中的 `lang`
+    pub lang: Option<Lang>,
+    /// 只读模式，忽略所有修改操作
+    pub readonly: bool,
+    /// 临时会话：不读取也不写入数据文件
+    pub ephemeral: bool,
+    /// 启动后选中的节点 ID
+    pub goto: Option<String>,
+    /// 数据文件不存在时的初始内容（`--seed-example` / `--template <path>`）
+    pub seed: Seed,
+    /// 启动时导入的缩进大纲文件，节点追加为新的根节点
+    pub import_outline: Option<PathBuf>,
+    /// 将数据文件导出为 Markdown 任务列表后退出
+    pub export_markdown: Option<PathBuf>,
+    /// 导出时只保留前 N 层（`--depth N`，需配合 `--export-markdown`）
+    pub depth: Option<usize>,
+}
+
+/// 命令行参数错误，由 `Strings::cli_error` 按界面语言生成说明
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliError {
+    MissingLang,
+    MissingNodeId(&'static str),          // 参数名
+    MissingPath(&'static str),            // 参数名
+    InvalidDepth,                         // --depth 缺少参数或不是正整数
+    UnsupportedLang(String),              // 无法识别的语言
+    UnknownArg(String),                   // 无法识别的参数
+    Conflict(&'static str, &'static str), // 不能同时使用的两个参数
+    Requires(&'static str, &'static str), // 前一个参数需要配合后一个使用
+}
+
+/// 从参数中找出 `--lang` 指定的语言（多次指定时取最后一个），解析参数之前用于决定报告错误的语言
+pub fn find_lang(args: &[String]) -> Option<Lang> {
+    args.windows(2)
+        .rev()
+        .filter(|pair| pair[0] == "--lang")
+        .find_map(|pair| Lang::parse(&pair[1]))
+}
+
+/// 解析命令行参数（不含程序名）
+pub fn parse_args<I>(args: I) -> Result<CliOptions, CliError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = CliOptions::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => options.check = true,
+            "--version" | "-V" => options.version = true,
+            "--lang" => {
+                let value = args.next().ok_or(CliError::MissingLang)?;
+                let lang = Lang::parse(&value).ok_or(CliError::UnsupportedLang(value))?;
+                options.lang = Some(lang);
+            }
+            "--readonly" => options.readonly = true,
+            "--ephemeral" => options.ephemeral = true,
+            "--goto" => {
+                options.goto = Some(args.next().ok_or(CliError::MissingNodeId("--goto"))?);
+            }
+            "--seed-example" => options.seed = Seed::Example,
+            "--template" => {
+                let path = args.next().ok_or(CliError::MissingPath("--template"))?;
+                options.seed = Seed::Template(PathBuf::from(path));
+            }
+            "--import-outline" => {
+                let path = args
+                    .next()
+                    .ok_or(CliError::MissingPath("--import-outline"))?;
+                options.import_outline = Some(PathBuf::from(path));
+            }
+            "--export-markdown" => {
+                let path = args
+                    .next()
+                    .ok_or(CliError::MissingPath("--export-markdown"))?;
+                options.export_markdown = Some(PathBuf::from(path));
+            }
+            "--depth" => {
+                let depth = args.next().and_then(|value| value.parse().ok());
+                options.depth = Some(
+                    depth
+                        .filter(|&depth| depth > 0)
+                        .ok_or(CliError::InvalidDepth)?,
+                );
+            }
+            other => return Err(CliError::UnknownArg(other.to_string())),
+        }
+    }
+    if options.readonly && options.import_outline.is_some() {
+        return Err(CliError::Conflict("--readonly", "--import-outline"));
+    }
+    if options.ephemeral && options.check {
+        return Err(CliError::Conflict("--ephemeral", "--check"));
+    }
+    if options.ephemeral && options.export_markdown.is_some() {
+        return Err(CliError::Conflict("--ephemeral", "--export-markdown"));
+    }
+    if options.depth.is_some() && options.export_markdown.is_none() {
+        return Err(CliError::Requires("--depth", "--export-markdown"));
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliOptions, CliError> {
+        parse_args(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse(&[]).unwrap(), CliOptions::default());
+        assert!(parse(&["--check"]).unwrap().check);
+        assert_eq!(
+            parse(&["--bogus"]),
+            Err(CliError::UnknownArg("--bogus".to_string()))
+        );
+        assert!(parse(&["--version"]).unwrap().version);
+        assert!(parse(&["-V"]).unwrap().version);
+        assert!(parse(&["--readonly"]).unwrap().readonly);
+        assert!(parse(&["--ephemeral"]).unwrap().ephemeral);
+        assert_eq!(
+            parse(&["--ephemeral", "--check"]),
+            Err(CliError::Conflict("--ephemeral", "--check"))
+        );
+        assert_eq!(parse(&["--lang", "en"]).unwrap().lang, Some(Lang::En));
+        assert_eq!(parse(&["--lang"]), Err(CliError::MissingLang));
+        assert_eq!(
+            parse(&["--lang", "fr"]),
+            Err(CliError::UnsupportedLang("fr".to_string()))
+        );
+        assert_eq!(
+            parse(&["--goto", "abc"]).unwrap().goto.as_deref(),
+            Some("abc")
+        );
+        assert_eq!(parse(&["--goto"]), Err(CliError::MissingNodeId("--goto")));
+        assert_eq!(parse(&["--seed-example"]).unwrap().seed, Seed::Example);
+        assert_eq!(
+            parse(&["--template", "t.toml"]).unwrap().seed,
+            Seed::Template(PathBuf::from("t.toml"))
+        );
+        assert_eq!(
+            parse(&["--import-outline", "plan.md"])
+                .unwrap()
+                .import_outline,
+            Some(PathBuf::from("plan.md"))
+        );
+        assert_eq!(
+            parse(&["--import-outline"]),
+            Err(CliError::MissingPath("--import-outline"))
+        );
+        assert!(parse(&["--readonly", "--import-outline", "plan.md"]).is_err());
+        assert_eq!(
+            parse(&["--export-markdown", "tree.md"])
+                .unwrap()
+                .export_markdown,
+            Some(PathBuf::from("tree.md"))
+        );
+        assert!(parse(&["--export-markdown"]).is_err());
+        assert!(parse(&["--ephemeral", "--export-markdown", "tree.md"]).is_err());
+        assert_eq!(
+            parse(&["--export-markdown", "tree.md", "--depth", "2"])
+                .unwrap()
+                .depth,
+            Some(2)
+        );
+        assert_eq!(
+            parse(&["--export-markdown", "tree.md", "--depth", "0"]),
+            Err(CliError::InvalidDepth)
+        );
+        assert_eq!(
+            parse(&["--export-markdown", "tree.md", "--depth"]),
+            Err(CliError::InvalidDepth)
+        );
+        assert_eq!(
+            parse(&["--depth", "2"]),
+            Err(CliError::Requires("--depth", "--export-markdown"))
+        );
+    }
+
+    #[test]
+    fn test_find_lang() {
+        let args = |args: &[&str]| -> Vec<String> { args.iter().map(|s| s.to_string()).collect() };
+        assert_eq!(find_lang(&args(&[])), None);
+        assert_eq!(
+            find_lang(&args(&["--bogus", "--lang", "en"])),
+            Some(Lang::En)
+        );
+        assert_eq!(
+            find_lang(&args(&["--lang", "en", "--lang", "zh"])),
+            Some(Lang::Zh)
+        );
+        assert_eq!(find_lang(&args(&["--lang", "fr"])), None);
+        assert_eq!(find_lang(&args(&["--lang"])), None);
+    }
+}
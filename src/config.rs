@@ -0,0 +1,328 @@
+//! 用户配置 (~/.config/rhizome/config.toml)
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Datelike, Local, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Lang;
+
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// 每周的第一天
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+impl WeekStart {
+    pub fn weekday(self) -> Weekday {
+        match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Sunday => Weekday::Sun,
+        }
+    }
+
+    /// `date` 所在周的第一天
+    pub fn first_day(self, date: NaiveDate) -> NaiveDate {
+        date.week(self.weekday()).first_day()
+    }
+}
+
+/// 删除有子节点的节点时如何处理子节点
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DeleteMode {
+    /// 连同整棵子树一起删除
+    #[default]
+    Cascade,
+    /// 只删除节点本身，子节点接到其原父节点下
+    Reparent,
+}
+
+/// 根节点按周期分组显示的方式，组标题行不可选中
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RootGrouping {
+    /// 不分组
+    #[default]
+    Off,
+    /// 按月，如 `2024-03`
+    Month,
+    /// 按季度，如 `2024 Q1`
+    Quarter,
+}
+
+impl RootGrouping {
+    /// 某一时间所属周期的标签，不分组时为 None
+    pub fn period_label(self, at: &DateTime<Local>) -> Option<String> {
+        match self {
+            RootGrouping::Off => None,
+            RootGrouping::Month => Some(format!("{}-{:02}", at.year(), at.month())),
+            RootGrouping::Quarter => Some(format!("{} Q{}", at.year(), at.month0() / 3 + 1)),
+        }
+    }
+}
+
+/// 操作被拒绝（如移动到自身下、只读模式下修改）时的提醒方式
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorAlert {
+    /// 只在状态栏显示消息
+    #[default]
+    Off,
+    /// 底部提示栏短暂反色闪烁
+    Flash,
+    /// 终端响铃
+    Bell,
+    /// 闪烁并响铃
+    Both,
+}
+
+impl ErrorAlert {
+    pub fn flash(self) -> bool {
+        matches!(self, ErrorAlert::Flash | ErrorAlert::Both)
+    }
+
+    pub fn bell(self) -> bool {
+        matches!(self, ErrorAlert::Bell | ErrorAlert::Both)
+    }
+}
+
+/// 界面主题（`[theme]` 表）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    /// 有子节点的根节点图标
+    pub root_branch: String,
+    /// 没有子节点的根节点图标
+    pub root_leaf: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            root_branch: "📋".to_string(),
+            root_leaf: "📌".to_string(),
+        }
+    }
+}
+
+/// 详情面板（上下布局时）的最小高度：上下边框加一行内容
+pub const MIN_DETAILS_HEIGHT: u16 = 3;
+
+/// 界面布局（`[layout]` 表）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// 上下布局时详情面板的高度（含边框），终端太矮时自动缩小，保证节点列表可见
+    pub details_height: u16,
+    /// 显示顶部标题栏
+    pub show_title: bool,
+    /// 显示底部快捷键提示栏（状态栏总是显示）
+    pub show_help: bool,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            details_height: 6,
+            show_title: true,
+            show_help: true,
+        }
+    }
+}
+
+/// 用户配置，缺省字段使用默认值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// 日期时间显示格式（chrono strftime 语法）
+    pub date_format: String,
+    pub week_start: WeekStart,
+    /// 最大层级数（根节点为第 1 层），不设置表示不限制
+    pub max_depth: Option<usize>,
+    /// 界面语言：`zh` 或 `en`
+    pub lang: Lang,
+    /// 移动的子树超过该节点数时需要确认
+    pub move_confirm_threshold: usize,
+    /// 过长的标题折行显示，而不是截断
+    pub wrap_titles: bool,
+    /// 启动时将超过该天数未更新的活跃叶子节点标记为失败，不设置表示关闭
+    pub auto_fail_days: Option<u32>,
+    /// 添加节点后停留在添加对话框，继续在同一父节点下添加，按 Esc 结束
+    pub sticky_add: bool,
+    /// 操作被拒绝时的提醒方式：`off` / `flash` / `bell` / `both`
+    pub error_alert: ErrorAlert,
+    /// 删除节点时级联删除子树（`cascade`），或将子节点上移一层（`reparent`）
+    pub delete_mode: DeleteMode,
+    /// 根节点按周期分组显示：`off` / `month` / `quarter`，节点的 `period` 优先于创建时间
+    pub root_grouping: RootGrouping,
+    /// 自动折叠已完成节点的子树，手动展开过的除外
+    pub collapse_completed: bool,
+    /// 启动时子节点按状态排列（活跃、暂停、已完成、失败），可用 `S` 切换
+    pub sort_by_status: bool,
+    /// 标题栏完成度的分母包含失败与暂停的节点（默认只计活跃与已完成）
+    pub completion_counts_all: bool,
+    /// 节点标记完成时在后台运行的命令，`{title}` / `{id}` 替换为节点的值（需启用 `hooks` feature）
+    pub on_complete_command: Option<String>,
+    /// 界面布局：详情面板高度与标题栏、快捷键提示栏的显示
+    pub layout: LayoutConfig,
+    /// 界面主题，目前为根节点图标
+    pub theme: Theme,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            week_start: WeekStart::default(),
+            max_depth: None,
+            lang: Lang::default(),
+            move_confirm_threshold: 10,
+            wrap_titles: false,
+            auto_fail_days: None,
+            sticky_add: false,
+            error_alert: ErrorAlert::default(),
+            delete_mode: DeleteMode::default(),
+            root_grouping: RootGrouping::default(),
+            collapse_completed: false,
+            sort_by_status: false,
+            completion_counts_all: false,
+            on_complete_command: None,
+            layout: LayoutConfig::default(),
+            theme: Theme::default(),
+        }
+    }
+}
+
+impl Config {
+    /// 校验配置项
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_depth == Some(0) {
+            return Err("max_depth 必须大于 0".to_string());
+        }
+        if self.layout.details_height < MIN_DETAILS_HEIGHT {
+            return Err(format!(
+                "layout.details_height 不能小于 {}",
+                MIN_DETAILS_HEIGHT
+            ));
+        }
+        validate_date_format(&self.date_format)
+    }
+}
+
+/// 检查日期格式能否被 chrono 渲染
+pub fn validate_date_format(format: &str) -> Result<(), String> {
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(format!("无效的日期格式 date_format = \"{}\"", format));
+    }
+    Ok(())
+}
+
+/// 从 TOML 文件加载配置，文件不存在时使用默认配置
+pub fn load_config(path: &Path) -> io::Result<Config> {
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    let content = fs::read_to_string(path)?;
+    let config: Config =
+        toml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    config.validate().map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), e),
+        )
+    })?;
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    #[test]
+    fn test_period_label() {
+        let at = Local.with_ymd_and_hms(2024, 3, 31, 23, 0, 0).unwrap();
+        assert_eq!(RootGrouping::Off.period_label(&at), None);
+        assert_eq!(RootGrouping::Month.period_label(&at).unwrap(), "2024-03");
+        assert_eq!(RootGrouping::Quarter.period_label(&at).unwrap(), "2024 Q1");
+
+        let at = Local.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap();
+        assert_eq!(RootGrouping::Quarter.period_label(&at).unwrap(), "2024 Q2");
+        let at = Local.with_ymd_and_hms(2025, 12, 1, 0, 0, 0).unwrap();
+        assert_eq!(RootGrouping::Quarter.period_label(&at).unwrap(), "2025 Q4");
+        assert_eq!(RootGrouping::Month.period_label(&at).unwrap(), "2025-12");
+    }
+
+    #[test]
+    fn test_invalid_date_format_rejected() {
+        let config: Config = toml::from_str("date_format = \"%Y-%Q\"").unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("无效的日期格式"));
+        assert!(err.contains("%Y-%Q"));
+    }
+
+    #[test]
+    fn test_custom_date_format() {
+        let config: Config =
+            toml::from_str("date_format = \"%m/%d %H时\"\nweek_start = \"sunday\"").unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.week_start, WeekStart::Sunday);
+
+        let t = Local.with_ymd_and_hms(2024, 3, 9, 14, 5, 0).unwrap();
+        assert_eq!(t.format(&config.date_format).to_string(), "03/09 14时");
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config, Config::default());
+        assert_eq!(config.week_start.weekday(), Weekday::Mon);
+        // 2024-03-13 为周三
+        let wednesday = NaiveDate::from_ymd_opt(2024, 3, 13).unwrap();
+        assert_eq!(
+            config.week_start.first_day(wednesday),
+            NaiveDate::from_ymd_opt(2024, 3, 11).unwrap()
+        );
+        assert_eq!(
+            WeekStart::Sunday.first_day(wednesday),
+            NaiveDate::from_ymd_opt(2024, 3, 10).unwrap()
+        );
+        assert_eq!(
+            WeekStart::Sunday.first_day(wednesday - chrono::Duration::days(3)),
+            wednesday - chrono::Duration::days(3)
+        );
+        assert_eq!(config.lang, Lang::Zh);
+
+        let config: Config = toml::from_str("lang = \"en\"").unwrap();
+        assert_eq!(config.lang, Lang::En);
+
+        let config: Config = toml::from_str("delete_mode = \"reparent\"").unwrap();
+        assert_eq!(config.delete_mode, DeleteMode::Reparent);
+
+        let config: Config = toml::from_str("[theme]\nroot_leaf = \"•\"").unwrap();
+        assert_eq!(config.theme.root_leaf, "•");
+        assert_eq!(config.theme.root_branch, Theme::default().root_branch);
+
+        let config: Config = toml::from_str("[layout]\nshow_help = false").unwrap();
+        assert!(!config.layout.show_help);
+        assert_eq!(config.layout.details_height, 6);
+    }
+
+    #[test]
+    fn test_details_height_too_small_rejected() {
+        let config: Config = toml::from_str("[layout]\ndetails_height = 2").unwrap();
+        assert!(config.validate().is_err());
+        assert!(Config::default().validate().is_ok());
+    }
+}
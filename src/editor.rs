@@ -0,0 +1,77 @@
+//! 用外部编辑器（`$VISUAL` / `$EDITOR`）编辑节点内容
+//!
+//! 暂停与恢复终端由主循环负责，这里只处理临时文件与编辑器进程。
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// 未设置 `$VISUAL` / `$EDITOR` 时使用的编辑器
+const FALLBACK_EDITOR: &str = "vi";
+
+/// 用外部编辑器编辑文字，返回编辑后的内容（去掉末尾的换行）
+pub fn edit_text(initial: &str) -> io::Result<String> {
+    edit_with(initial, run_editor)
+}
+
+/// 将 `initial` 写入临时文件，调用 `launch` 编辑后读回；无论成功与否都删除临时文件
+fn edit_with(initial: &str, launch: impl FnOnce(&Path) -> io::Result<()>) -> io::Result<String> {
+    let path = std::env::temp_dir().join(format!("rhizome-{}.md", uuid::Uuid::new_v4()));
+    fs::write(&path, initial)?;
+    let result = launch(&path).and_then(|()| fs::read_to_string(&path));
+    let _ = fs::remove_file(&path);
+    Ok(result?.trim_end_matches(['\n', '\r']).to_string())
+}
+
+/// 要使用的编辑器命令，依次取 `$VISUAL`、`$EDITOR`
+fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| FALLBACK_EDITOR.to_string())
+}
+
+/// 运行编辑器并等待其退出，编辑器命令可带参数（如 `code -w`）
+fn run_editor(path: &Path) -> io::Result<()> {
+    let command = editor_command();
+    let mut parts = command.split_whitespace();
+    let program = parts.next().unwrap_or(FALLBACK_EDITOR);
+    let status = Command::new(program).args(parts).arg(path).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{} 退出状态 {}", program, status)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_edit_round_trip() {
+        let mut seen = PathBuf::new();
+        let edited = edit_with("旧内容", |path| {
+            assert_eq!(fs::read_to_string(path)?, "旧内容");
+            seen = path.to_path_buf();
+            fs::write(path, "新内容\n第二行\n")
+        })
+        .unwrap();
+        assert_eq!(edited, "新内容\n第二行");
+        assert!(!seen.exists());
+    }
+
+    #[test]
+    fn test_edit_failure_cleans_up() {
+        let mut seen = PathBuf::new();
+        let result = edit_with("x", |path| {
+            seen = path.to_path_buf();
+            Err(io::Error::other("editor crashed"))
+        });
+        assert_eq!(result.unwrap_err().to_string(), "editor crashed");
+        assert!(!seen.exists());
+    }
+}
@@ -0,0 +1,94 @@
+//! 节点完成时运行的外部命令（`on_complete_command`）
+//!
+//! 命令模板的替换总是可用；实际执行命令需启用 `hooks` feature，且只支持 Unix（通过 `sh -c` 运行）。
+
+/// 将命令模板中的 `{title}`、`{id}` 替换为节点的值
+///
+/// 替换值加上单引号作为一个完整的 shell 参数，模板中无需再加引号
+#[cfg_attr(not(feature = "hooks"), allow(dead_code))]
+pub fn render_command(template: &str, title: &str, id: &str) -> String {
+    // 逐段替换，避免标题中的 `{id}` 被再次替换
+    let mut command = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        command.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        rest = if let Some(after) = tail.strip_prefix("{title}") {
+            command.push_str(&shell_quote(title));
+            after
+        } else if let Some(after) = tail.strip_prefix("{id}") {
+            command.push_str(&shell_quote(id));
+            after
+        } else {
+            command.push('{');
+            &tail[1..]
+        };
+    }
+    command.push_str(rest);
+    command
+}
+
+/// 用单引号包裹，内部的单引号写作 `'\''`
+#[cfg_attr(not(feature = "hooks"), allow(dead_code))]
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(feature = "hooks")]
+pub use runner::spawn;
+
+#[cfg(feature = "hooks")]
+mod runner {
+    use std::io;
+    use std::process::Child;
+
+    /// 在后台通过 `sh -c` 运行命令，不等待其结束，输入输出均丢弃
+    #[cfg(unix)]
+    pub fn spawn(command: &str) -> io::Result<Child> {
+        use std::process::{Command, Stdio};
+
+        Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+    }
+
+    /// 替换值按 POSIX shell 规则加引号，交给 `cmd /C` 并不安全，因此其他平台上不运行
+    #[cfg(not(unix))]
+    pub fn spawn(_command: &str) -> io::Result<Child> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "完成命令仅支持 Unix 平台",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_command() {
+        assert_eq!(
+            render_command("echo {title} >> done.log", "每天早起", "abc"),
+            "echo '每天早起' >> done.log"
+        );
+        assert_eq!(
+            render_command("notify {id} {title} {id}", "x", "1"),
+            "notify '1' 'x' '1'"
+        );
+        // 标题中的引号与 shell 元字符不会被解释
+        assert_eq!(
+            render_command("echo {title}", "it's $(rm -rf ~)", "1"),
+            r"echo 'it'\''s $(rm -rf ~)'"
+        );
+        assert_eq!(
+            render_command("echo {title} {other}", "{id}", "1"),
+            "echo '{id}' {other}"
+        );
+        assert_eq!(render_command("true", "x", "1"), "true");
+    }
+}
@@ -0,0 +1,558 @@
+//! 界面文字本地化
+//!
+//! 所有面向用户的文字集中在 [`strings!`] 表中，每个键同时给出中文与英文，
+//! 因此任一语言都不会缺少条目。文字中的 `{}` 为占位符，由 [`Strings::format`] 依次替换。
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cli::CliError;
+use crate::models::{LogEntry, LogEvent, NodeStatus, Notification, TreeError, TreeIssue};
+
+/// 界面语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    #[default]
+    Zh,
+    En,
+}
+
+impl Lang {
+    /// 从 `zh` / `en` 解析（用于命令行参数）
+    pub fn parse(s: &str) -> Option<Lang> {
+        match s {
+            "zh" => Some(Lang::Zh),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! strings {
+    ($($key:ident => $zh:expr, $en:expr;)*) => {
+        /// 文字条目的键
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Key {
+            $($key,)*
+        }
+
+        impl Key {
+            #[allow(dead_code)]
+            pub const ALL: &'static [Key] = &[$(Key::$key,)*];
+        }
+
+        fn lookup(lang: Lang, key: Key) -> &'static str {
+            match (lang, key) {
+                $(
+                    (Lang::Zh, Key::$key) => $zh,
+                    (Lang::En, Key::$key) => $en,
+                )*
+            }
+        }
+    };
+}
+
+strings! {
+    // 标题与面板
+    AppTitle => "🌳 RSIP 国策树", "🌳 RSIP Focus Tree";
+    CompletionPercent => "  完成度 {}%", "  {}% done";
+    EphemeralBadge => "  [临时会话，不会保存]", "  [ephemeral, nothing is saved]";
+    TreeTitle => "节点列表", "Nodes";
+    TreeTitleWithArchived => "节点列表（含已归档）", "Nodes (incl. archived)";
+    TreeTitleToc => "目录（Ctrl+T 展开选中的根节点）", "Outline (Ctrl+T to zoom into the selected root)";
+    DaysSuffix => " ({} 天)", " ({}d)";
+    EmptyTree => "暂无节点，按 'a' 添加第一个国策", "No nodes yet, press 'a' to add the first focus";
+    TreeEmptyHint => "空空如也，按 a 创建你的第一个国策", "Nothing here yet, press a to create your first focus";
+    TreeHiddenHint => "所有节点均已归档，按 v 显示", "All nodes are archived, press v to show them";
+    DetailsTitle => "详情 ", "Details ";
+    TabContent => "内容", "Content";
+    TabLog => "日志", "Log";
+    TabMeta => "信息", "Info";
+
+    // 详情字段
+    LabelId => "ID: ", "Id: ";
+    LabelTitle => "标题: ", "Title: ";
+    LabelRule => "规则: ", "Rule: ";
+    LabelLinks => "关联: ", "See also: ";
+    LabelCreated => "创建于: ", "Created: ";
+    LabelUpdated => "  最后更新: ", "  Updated: ";
+    LabelStreak => "连续: ", "Streak: ";
+    LabelStatus => "  状态: ", "  Status: ";
+    Days => "{} 天", "{} days";
+    LabelSubtree => "  子树: ", "  Subtree: ";
+    LabelCompleted => "完成于: ", "Completed: ";
+    LabelEstimate => "预估: ", "Estimate: ";
+    LabelEstimateTotal => "  含子节点: ", "  Incl. children: ";
+    SubtreeNodes => "{} 个节点", "{} node(s)";
+    LabelPosition => "位置: ", "Position: ";
+    SiblingPosition => "第 {} / {} 个子节点", "child {} of {}";
+    RootPosition => "第 {} / {} 个根节点", "root {} of {}";
+    LabelDue => "截止: ", "Due: ";
+    DueOverdue => "（已逾期）", " (overdue)";
+    NoValue => "(无)", "(none)";
+    NoLog => "(暂无日志)", "(no log entries)";
+
+    // 节点状态
+    StatusActive => "活跃", "Active";
+    StatusFailed => "失败", "Failed";
+    StatusCompleted => "已完成", "Completed";
+    StatusPaused => "暂停", "Paused";
+
+    // 快捷键提示
+    HelpNormal => "[a] 添加  [A] 添加根节点  [e] 编辑  [r] 重命名  [O] 外部编辑器  [m] 移动  [n] 关联  [d] 删除  [Ctrl+Z] 撤销  [f] 失败/激活  [F] 清除失败  [c] 完成  [x] 勾选  [s] 切换状态  [C] 归档  [v] 显示归档  [t] 颜色  [E] 预估  [u] 截止日期  [w] 折行  [i] ID  [D] 层级  [S] 按状态排列  [R] 替换  [g] 跳转  []o / [o] 逾期节点  [o] 陈旧根节点  [Ctrl+R] 最近修改  [Ctrl+T] 目录  [j/k] 导航  [h/l] 折叠/展开  [+/-] 展开一层/折叠  [H/L] 横向滚动  [z] 居中  [Tab] 详情  [:] 命令  [Ctrl+S] 保存  [?] 全部快捷键  [q] 退出  [Q] 放弃修改并退出",
+        "[a] Add  [A] Add root  [e] Edit  [r] Rename  [O] $EDITOR  [m] Move  [n] Link  [d] Delete  [Ctrl+Z] Undo  [f] Fail/Revive  [F] Purge failed  [c] Done  [x] Check  [s] Cycle status  [C] Archive  [v] Show archived  [t] Color  [E] Estimate  [u] Due  [w] Wrap  [i] Ids  [D] Depth  [S] Sort by status  [R] Replace  [g] Go to  []o / [o] Overdue  [o] Stale roots  [Ctrl+R] Recent  [Ctrl+T] Outline  [j/k] Navigate  [h/l] Collapse/Expand  [+/-] Expand one/Collapse  [H/L] Scroll row  [z] Center  [Tab] Details  [:] Commands  [Ctrl+S] Save  [?] All keys  [q] Quit  [Q] Quit without saving";
+    HelpAddTitle => "输入标题后按 [Enter] 继续  [Shift/Alt+Enter] 直接添加  [Esc] 取消", "Type a title, [Enter] to continue  [Shift/Alt+Enter] Add now  [Esc] Cancel";
+    HelpAddContent => "输入内容后按 [Enter] 完成  [Esc] 取消", "Type the content, [Enter] to finish  [Esc] Cancel";
+    HelpEdit => "[Tab] 切换字段  [Enter] 保存  [Esc] 取消", "[Tab] Switch field  [Enter] Save  [Esc] Cancel";
+    HelpLink => "[j/k] 选择要关联的节点  [h/l] 折叠/展开  [n/Enter] 确认关联  [Esc] 取消", "[j/k] Pick node  [h/l] Collapse/Expand  [n/Enter] Confirm link  [Esc] Cancel";
+    HelpMove => "[j/k] 选择目标位置  [h/l] 折叠/展开  [m] 确认移动  [Esc] 取消", "[j/k] Pick target  [h/l] Collapse/Expand  [m] Confirm move  [Esc] Cancel";
+    HelpPalette => "输入筛选  [↑/↓] 选择  [Enter] 执行  [Esc] 取消", "Type to filter  [↑/↓] Select  [Enter] Run  [Esc] Cancel";
+    HelpReplaceFrom => "输入要查找的文字后按 [Enter] 继续  [Esc] 取消", "Type the text to find, [Enter] to continue  [Esc] Cancel";
+    HelpReplaceTo => "输入替换文字后按 [Enter] 预览  [Esc] 取消", "Type the replacement, [Enter] to preview  [Esc] Cancel";
+    HelpGoto => "输入节点 ID 后按 [Enter] 跳转  [Esc] 取消", "Type a node id, [Enter] to jump  [Esc] Cancel";
+    HelpStaleRoots => "[j/k] 选择  [f] 失败  [c] 完成  [d] 删除  [Enter] 定位  [Esc] 关闭", "[j/k] Select  [f] Fail  [c] Done  [d] Delete  [Enter] Locate  [Esc] Close";
+    HelpRecent => "[j/k] 选择  [Enter] 跳转  [Esc] 关闭", "[j/k] Select  [Enter] Jump  [Esc] Close";
+    HelpKeys => "[Esc/?] 关闭", "[Esc/?] Close";
+    HelpMore => "…更多(?)", "…more (?)";
+    HelpEstimate => "输入预估工作量（如小时数，留空清除）后按 [Enter] 保存  [Esc] 取消", "Type an estimate (e.g. hours, empty to clear), [Enter] to save  [Esc] Cancel";
+    HelpFailReason => "输入失败原因（可留空）后按 [Enter] 标记失败  [Esc] 取消", "Type a reason (optional), [Enter] to mark as failed  [Esc] Cancel";
+    HelpDue => "输入截止日期（YYYY-MM-DD，留空清除）后按 [Enter] 保存  [Esc] 取消", "Type a due date (YYYY-MM-DD, empty to clear), [Enter] to save  [Esc] Cancel";
+    HelpConfirm => "[y/Enter] 确认  [n/Esc] 取消", "[y/Enter] Confirm  [n/Esc] Cancel";
+
+    // 弹窗
+    AddDialogTitle => "添加新国策", "Add Focus";
+    AddRootDialogTitle => "添加新国策（根节点）", "Add Focus (Root)";
+    FieldTitle => "标题", "Title";
+    FieldContent => "内容", "Content";
+    FieldContentOptional => "内容 (可选)", "Content (optional)";
+    AddHintTitle => "输入标题后按 Enter 继续", "Type a title and press Enter to continue";
+    AddHintContent => "输入内容后按 Enter 完成（可留空）", "Type the content and press Enter to finish (may be empty)";
+    EditDialogTitle => "编辑节点", "Edit Node";
+    EditHint => "Tab 切换字段，Enter 保存，Esc 取消", "Tab switches field, Enter saves, Esc cancels";
+    ReplaceDialogTitle => "查找替换", "Find and Replace";
+    FieldFind => "查找", "Find";
+    FieldReplaceWith => "替换为", "Replace with";
+    GotoDialogTitle => "跳转到节点", "Go to Node";
+    FailReasonTitle => "失败原因", "Failure Reason";
+    FieldReason => "原因（可选，记录到节点日志）", "Reason (optional, saved to the node log)";
+    EstimateTitle => "预估工作量", "Estimate";
+    DueTitle => "截止日期", "Due date";
+    FieldDue => "截止日期（YYYY-MM-DD）", "Due date (YYYY-MM-DD)";
+    FieldEstimate => "预估（如小时数）", "Estimate (e.g. hours)";
+    FieldNodeId => "节点 ID", "Node id";
+    StaleRootsTitle => "根节点（按持续天数排序）", "Root Nodes (Stalest First)";
+    StaleRootsDays => "{} 天", "{} d";
+    RecentTitle => "最近修改", "Recently Modified";
+    KeysTitle => "快捷键", "Keys";
+    PaletteTitle => "命令面板", "Command Palette";
+    PaletteFilter => "筛选", "Filter";
+    ConfirmTitle => "⚠️ 确认操作", "⚠️ Confirm";
+    ConfirmDeleteLeaf => "确认删除该节点？", "Delete this node?";
+    ConfirmDeleteTree => "确认删除该节点及其所有子节点？", "Delete this node and all its children?";
+    ConfirmDeleteReparent => "确认删除该节点？其子节点将上移一层", "Delete this node? Its children will move up one level";
+    ConfirmFailLeaf => "确认标记该节点为失败？", "Mark this node as failed?";
+    ConfirmFailTree => "确认标记该节点为失败并删除所有子节点？", "Mark this node as failed and delete all its children?";
+    ConfirmReplace => "将 \"{}\" 替换为 \"{}\"，影响以下 {} 个节点：", "Replace \"{}\" with \"{}\" in these {} node(s):";
+    ConfirmMove => "将移动 {} 个节点到『{}』", "Move {} node(s) under \"{}\"?";
+    RootTarget => "根", "root";
+    ConfirmPurgeFailed => "确认删除全部失败节点？", "Delete all failed nodes?";
+    ConfirmMergeRoots => "确认合并同名根节点？有内容或日志的重复根节点会保留", "Merge roots with the same title? Duplicates with content or a log are kept";
+    ConfirmRepair => "发现 {} 个结构问题，修复？（孤立与成环的节点提升为根节点，悬空引用移除）", "Found {} structural issues. Repair? (orphaned and cyclic nodes become roots, dangling references are removed)";
+    IssueWithTitle => "{}（{}）", "{} ({})";
+    ConfirmQuitDiscard => "放弃本次会话的所有未保存修改并退出？", "Discard all unsaved changes and quit?";
+    ConfirmReloadExternal => "数据文件已被外部修改，放弃未保存的修改并重新加载？", "The data file changed on disk. Discard unsaved changes and reload?";
+
+    // 状态栏消息
+    MsgAdded => "节点已添加", "Node added";
+    MsgAddedSticky => "节点已添加，继续输入下一个（Esc 结束）", "Node added, type the next one (Esc to finish)";
+    MsgTitleEmpty => "标题不能为空", "Title must not be empty";
+    MsgUpdated => "节点已更新", "Node updated";
+    MsgUnchanged => "内容未改变", "Nothing changed";
+    MsgIntegrityOk => "共 {} 个节点，未发现问题", "{} nodes, no issues found";
+    MsgIntegrityIssues => "发现 {} 个结构问题（只读模式，无法修复）", "Found {} structural issues (read-only, cannot repair)";
+    MsgRepaired => "已修复 {} 个问题", "Repaired {} issues";
+    MsgEditorEmpty => "编辑结果为空，内容未修改", "The editor returned nothing, content kept";
+    MsgEditorFailed => "外部编辑器运行失败: {}", "External editor failed: {}";
+    MsgPickParent => "请选择新的父节点（或根节点），按 'm' 确认移动", "Pick the new parent (or a root), press 'm' to move";
+    MsgNoMoveTarget => "没有其他可作为父节点的位置", "There is no other node to move under";
+    MsgMoved => "节点已移动", "Node moved";
+    MsgPickLinkTarget => "请选择要关联的节点，按 'n' 确认", "Pick the node to link to, press 'n' to confirm";
+    MsgNoLinkTarget => "没有其他可关联的节点", "There is no other node to link to";
+    MsgLinked => "已添加关联", "Link added";
+    MsgAlreadyLinked => "已经关联过该节点", "Already linked to that node";
+    MsgPositionUnchanged => "位置未改变", "Position unchanged";
+    MsgRecovered => "节点已恢复为活跃状态", "Node is active again";
+    MsgCompleted => "节点已标记完成", "Node marked as completed";
+    MsgHookFailed => "完成命令运行失败: {}", "Completion command failed: {}";
+    MsgCheckLeafOnly => "只能勾选活跃或已完成的叶子节点，父节点的进度由子节点决定", "Only active or completed leaves can be checked; parents follow their children";
+    MsgRecoverFirst => "失败的节点需先恢复为活跃状态", "Revive a failed node before completing it";
+    MsgArchived => "已归档 {} 个已完成节点", "Archived {} completed node(s)";
+    MsgShowArchived => "显示已归档节点", "Showing archived nodes";
+    MsgHideArchived => "隐藏已归档节点", "Hiding archived nodes";
+    MsgWrapOn => "长标题折行显示", "Wrapping long titles";
+    MsgWrapOff => "长标题截断显示", "Truncating long titles";
+    MsgTocOn => "目录模式：只显示根节点", "Outline view: showing roots only";
+    MsgTocOff => "已展开选中的根节点", "Zoomed into the selected root";
+    MsgIdsShown => "显示节点 ID", "Showing node ids";
+    MsgIdsHidden => "隐藏节点 ID", "Hiding node ids";
+    MsgDepthShown => "显示层级", "Showing depth";
+    MsgDepthHidden => "隐藏层级", "Hiding depth";
+    MsgStatusSortOn => "子节点按状态排列：活跃、暂停、已完成、失败", "Children sorted by status: active, paused, completed, failed";
+    MsgStatusSortOff => "子节点按手动顺序排列", "Children in manual order";
+    MsgStatusSet => "状态: {}", "Status: {}";
+    MsgColorSet => "颜色标签: {}", "Color tag: {}";
+    MsgDueSet => "截止日期已设为 {}", "Due date set to {}";
+    MsgDueCleared => "已清除截止日期", "Due date cleared";
+    MsgInvalidDue => "无效的日期: {}（格式为 YYYY-MM-DD）", "Invalid date: {} (expected YYYY-MM-DD)";
+    MsgEstimateSet => "预估已设为 {}", "Estimate set to {}";
+    MsgInvalidEstimate => "无效的预估: {}（需要非负数）", "Invalid estimate: {} (expected a non-negative number)";
+    MsgColorCleared => "已清除颜色标签", "Color tag cleared";
+    MsgDeleted => "已删除 {} 个节点", "Deleted {} node(s)";
+    MsgDeletedReparented => "已删除节点，{} 个子节点上移一层", "Node deleted, {} child node(s) moved up one level";
+    MsgFailed => "节点已标记失败，删除了 {} 个子节点", "Node marked as failed, {} child node(s) deleted";
+    MsgNoMatch => "没有包含 \"{}\" 的节点", "No node contains \"{}\"";
+    MsgReplaced => "已替换 {} 处", "Replaced {} occurrence(s)";
+    MsgReplacedSkipped => "已替换 {} 处，跳过 {} 个替换后标题为空的节点", "Replaced {} occurrence(s), skipped {} node(s) whose title would become empty";
+    MsgReadonly => "只读模式，不能修改", "Read-only mode: changes are disabled";
+    MsgCompletedWeek => "本周（{} 起）完成了 {} 个节点: {}", "This week (since {}): {} node(s) completed: {}";
+    MsgNoRecent => "还没有最近修改的节点", "No recently modified nodes yet";
+    MsgNoRoots => "没有可显示的根节点", "There are no root nodes to show";
+    MsgAutoFailed => "{} 个节点超过 {} 天未更新，已自动标记为失败", "{} node(s) untouched for over {} days were marked as failed";
+    MsgNodeNotFound => "找不到节点: {}", "No node with id: {}";
+    MsgNoOverdue => "没有逾期的节点", "No overdue nodes";
+    MsgOverduePosition => "逾期节点 {}/{}", "Overdue {}/{}";
+    MsgReloaded => "数据文件已被外部修改，已重新加载", "The data file changed on disk and was reloaded";
+    MsgReloadFailed => "重新加载失败: {}", "Failed to reload: {}";
+    MsgSaved => "已保存", "Saved";
+    MsgEphemeral => "临时会话，不会写入任何文件", "Ephemeral session: nothing is written to disk";
+    MsgNoChanges => "无更改", "No changes";
+    MsgDataDirReadonly => "数据目录不可写，修改将无法保存: {}", "Data directory is not writable, changes will not be saved: {}";
+    StatusNotSaving => "⚠ 无法保存", "⚠ Not saving";
+    MsgSaveFailed => "保存失败: {}", "Failed to save: {}";
+    MsgDeleteUndone => "已恢复 {} 个节点", "Restored {} node(s)";
+    MsgNothingToUndo => "没有可撤销的删除或重命名", "Nothing to undo";
+    MsgRenamed => "标题: {} → {}", "Title: {} → {}";
+    MsgRenameUndone => "已撤销重命名，标题恢复为 {}", "Rename undone, title restored to {}";
+    MsgUndoFailed => "无法恢复：原父节点已不存在", "Cannot restore: the original parent no longer exists";
+    MsgMergedRoots => "合并了 {} 个同名根节点", "Merged {} duplicate root(s)";
+    MsgMergedRootsSkipped => "合并了 {} 个同名根节点，{} 个有内容或日志的已保留", "Merged {} duplicate root(s), kept {} with content or a log";
+    MsgPurged => "已清除 {} 个失败节点", "Purged {} failed node(s)";
+    MsgUnknownVersion => "数据文件版本 {} 比当前程序支持的 {} 更新，已以只读模式打开，避免覆盖无法识别的内容", "Data file version {} is newer than the supported {}, opened read-only so unknown content is not overwritten";
+    MsgImported => "已导入 {} 个节点", "Imported {} nodes";
+    MsgSnapshotFailed => "进度快照写入失败: {}", "Failed to write progress snapshot: {}";
+    MsgAuditFailed => "活动日志写入失败: {}", "Failed to write activity log: {}";
+
+    // 退出后输出到终端
+    ExitDiscarded => "已放弃本次会话的修改", "Discarded this session's changes";
+    ExitSaved => "数据已保存到 {}", "Data saved to {}";
+    ExitNotSaved => "临时会话或只读模式，未保存任何数据", "Ephemeral or read-only session, nothing was saved";
+    ExitUnchanged => "数据没有修改，未写入文件", "No changes, data file left untouched";
+    ExitSaveFailed => "保存失败，本次会话的修改没有写入！\n  数据文件: {}\n  原因: {}\n  {}", "Save failed, this session's changes were NOT written!\n  Data file: {}\n  Reason: {}\n  {}";
+    ExitUnknownPath => "(未知)", "(unknown)";
+    HintPermissionDenied => "请检查数据文件及所在目录的写入权限", "Check write permissions of the data file and its directory";
+    HintStorageFull => "请检查磁盘剩余空间", "Check free disk space";
+    HintSaveFailed => "请检查数据目录是否存在、是否有写入权限以及磁盘剩余空间", "Check that the data directory exists, is writable and has free disk space";
+
+    // 命令行
+    CliMissingLang => "--lang 需要一个参数 (zh|en)", "--lang needs a value (zh|en)";
+    CliMissingNodeId => "{} 需要一个节点 ID", "{} needs a node ID";
+    CliMissingPath => "{} 需要一个文件路径", "{} needs a file path";
+    CliInvalidDepth => "--depth 需要一个正整数", "--depth needs a positive integer";
+    CliUnsupportedLang => "不支持的语言: {}", "Unsupported language: {}";
+    CliUnknownArg => "未知参数: {}", "Unknown argument: {}";
+    CliConflict => "{} 与 {} 不能同时使用", "{} cannot be used together with {}";
+    CliRequires => "{} 需要与 {} 一起使用", "{} can only be used with {}";
+    CliDataFile => "数据文件: {}", "Data file: {}";
+    CliNoDataDir => "无法获取用户数据目录", "Cannot determine the user data directory";
+    CliNoConfigDir => "无法获取用户配置目录", "Cannot determine the user config directory";
+    CheckMissing => "数据文件不存在: {}", "Data file not found: {}";
+    CheckClean => "{}: 共 {} 个节点，未发现问题", "{}: {} node(s), no issues found";
+    CheckIssues => "{}: 发现 {} 个问题", "{}: found {} issue(s)";
+    ExportedMarkdown => "已导出 {} 个节点到 {}", "Exported {} node(s) to {}";
+    ErrNewerDataFile => "数据文件版本 {} 比当前程序更新，拒绝覆盖", "Data file version {} is newer than this program, refusing to overwrite it";
+
+    // 树操作错误
+    ErrNotFound => "节点不存在", "Node not found";
+    ErrMoveIntoSelf => "不能将节点移动到自己下面", "Cannot move a node under itself";
+    ErrMoveIntoDescendant => "不能将节点移动到其子节点下", "Cannot move a node under its own descendant";
+    ErrDepthExceeded => "超出最大层级限制（{} 层）", "Maximum depth exceeded ({} levels)";
+    ErrLinkToSelf => "不能将节点关联到自身", "Cannot link a node to itself";
+    ErrIdExists => "节点 ID 已存在: {}", "Node id already exists: {}";
+
+    // 树结构校验问题
+    IssueOrphan => "孤立节点 {}：父节点 {} 不存在", "Orphaned node {}: parent {} does not exist";
+    IssueCycle => "循环引用：节点 {} 的祖先链成环", "Cycle: the ancestors of node {} form a loop";
+    IssueDanglingRoot => "悬空引用：根列表引用了不存在的节点 {}", "Dangling reference: the root list refers to missing node {}";
+    IssueDanglingChild => "悬空引用：{} 的子节点列表引用了不存在的节点 {}", "Dangling reference: the children of {} refer to missing node {}";
+    IssueEmptyChildren => "空列表：{} 的子节点列表为空", "Empty list: the children list of {} is empty";
+
+    // 节点日志
+    LogFailed => "标记为失败", "Marked as failed";
+    LogAutoFailed => "超过 {} 天未更新，自动标记为失败", "Not updated for over {} days, marked as failed automatically";
+    LogFailReason => "失败原因: {}", "Reason for failure: {}";
+    LogRecovered => "恢复为活跃", "Recovered to active";
+    LogCompleted => "标记为完成", "Marked as completed";
+    LogUncompleted => "取消完成", "Unmarked as completed";
+    LogStatusChanged => "状态: {} → {}", "Status: {} → {}";
+
+    // 活动日志的详情
+    AuditNodes => "{} 个节点", "{} node(s)";
+    AuditIssues => "{} 个问题", "{} issue(s)";
+
+    // 桌面通知
+    NotifyMilestone => "「{}」已坚持 {} 天", "\"{}\" has been going for {} days";
+    NotifyOverdue => "「{}」已过截止日期 {}", "\"{}\" is past its due date {}";
+
+    // 命令名称
+    ActionQuit => "退出", "Quit";
+    ActionQuitDiscard => "放弃修改并退出", "Quit without saving";
+    ActionMoveUp => "上移选择", "Select previous";
+    ActionMoveDown => "下移选择", "Select next";
+    ActionCollapse => "折叠 / 跳到父节点", "Collapse / go to parent";
+    ActionExpand => "展开 / 跳到子节点", "Expand / go to child";
+    ActionExpandOne => "展开一层", "Expand one level";
+    ActionCollapseOne => "折叠选中节点", "Collapse selected";
+    ActionScrollLeft => "选中行标题向右滚动（看开头）", "Scroll selected title back";
+    ActionScrollRight => "选中行标题向左滚动（看结尾）", "Scroll selected title forward";
+    ActionCenterSelection => "选中行居中", "Center selection";
+    ActionAdd => "添加节点", "Add node";
+    ActionAddRoot => "添加根节点", "Add root node";
+    ActionEditContent => "编辑内容", "Edit content";
+    ActionOpenInEditor => "在外部编辑器中编辑内容", "Edit content in $EDITOR";
+    ActionRename => "重命名", "Rename";
+    ActionMove => "移动节点", "Move node";
+    ActionLink => "关联节点", "Link node";
+    ActionDelete => "删除节点", "Delete node";
+    ActionFail => "标记失败/恢复", "Fail / revive";
+    ActionUndo => "撤销", "Undo";
+    ActionPurgeFailed => "清除失败节点", "Purge failed nodes";
+    ActionComplete => "标记完成/取消完成", "Complete / uncomplete";
+    ActionToggleLeafDone => "勾选叶子节点", "Check off leaf";
+    ActionCycleStatus => "切换状态（活跃/完成/暂停）", "Cycle status (active / done / paused)";
+    ActionArchive => "归档已完成节点", "Archive completed nodes";
+    ActionShowArchived => "显示/隐藏已归档", "Show / hide archived";
+    ActionCycleColor => "切换颜色标签", "Cycle color tag";
+    ActionEstimate => "设置预估工作量", "Set estimate";
+    ActionDue => "设置截止日期", "Set due date";
+    ActionWrapTitles => "切换标题折行", "Toggle title wrapping";
+    ActionToc => "目录模式（只看根节点）", "Outline view (roots only)";
+    ActionShowIds => "显示/隐藏节点 ID", "Show / hide node ids";
+    ActionShowDepth => "显示/隐藏层级", "Show / hide depth";
+    ActionStatusSort => "子节点按状态排列", "Sort children by status";
+    ActionReplace => "查找替换", "Find and replace";
+    ActionGoto => "跳转到节点", "Go to node";
+    ActionNextOverdue => "下一个逾期节点", "Next overdue node";
+    ActionPrevOverdue => "上一个逾期节点", "Previous overdue node";
+    ActionStaleRoots => "按持续天数查看根节点", "Review roots by staleness";
+    ActionRecent => "最近修改的节点", "Recently modified nodes";
+    ActionShowKeys => "显示全部快捷键", "Show all keys";
+    ActionMergeRoots => "合并同名根节点", "Merge roots with the same title";
+    ActionCheckIntegrity => "检查数据完整性", "Check data integrity";
+    ActionReviewCompleted => "回顾本周完成的节点", "Review nodes completed this week";
+    ActionSave => "立即保存", "Save now";
+    ActionPalette => "命令面板", "Command palette";
+    ActionNextTab => "切换详情标签", "Next details tab";
+    ActionCancel => "取消", "Cancel";
+    ActionSubmit => "确认", "Confirm";
+    ActionSubmitQuick => "直接添加（内容留空）", "Add without content";
+    ActionInput => "输入", "Input";
+    ActionDeleteChar => "删除字符", "Delete character";
+    ActionDeleteWord => "删除前一个词", "Delete previous word";
+    ActionDeleteToLineStart => "删除到行首", "Delete to line start";
+    ActionSwitchField => "切换输入字段", "Switch input field";
+}
+
+/// 某一语言的文字集合
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Strings {
+    pub lang: Lang,
+}
+
+impl Strings {
+    pub fn new(lang: Lang) -> Self {
+        Self { lang }
+    }
+
+    /// 取得键对应的文字
+    pub fn get(&self, key: Key) -> &'static str {
+        lookup(self.lang, key)
+    }
+
+    /// 依次用参数替换文字中的 `{}` 占位符
+    pub fn format(&self, key: Key, args: &[&dyn fmt::Display]) -> String {
+        let mut parts = self.get(key).split("{}");
+        let mut result = parts.next().unwrap_or_default().to_string();
+        for (i, part) in parts.enumerate() {
+            if let Some(arg) = args.get(i) {
+                result.push_str(&arg.to_string());
+            }
+            result.push_str(part);
+        }
+        result
+    }
+
+    /// 节点状态名称
+    pub fn status(&self, status: &NodeStatus) -> &'static str {
+        self.get(match status {
+            NodeStatus::Active => Key::StatusActive,
+            NodeStatus::Failed => Key::StatusFailed,
+            NodeStatus::Completed => Key::StatusCompleted,
+            NodeStatus::Paused => Key::StatusPaused,
+        })
+    }
+
+    /// 节点日志条目的文字：事件按当前语言生成，旧版本记录的文字原样显示
+    pub fn log_entry(&self, entry: &LogEntry) -> String {
+        let Some(event) = &entry.event else {
+            return entry.text.clone();
+        };
+        match event {
+            LogEvent::Failed => self.get(Key::LogFailed).to_string(),
+            LogEvent::AutoFailed { days } => self.format(Key::LogAutoFailed, &[days]),
+            LogEvent::FailReason { reason } => self.format(Key::LogFailReason, &[reason]),
+            LogEvent::Recovered => self.get(Key::LogRecovered).to_string(),
+            LogEvent::Completed => self.get(Key::LogCompleted).to_string(),
+            LogEvent::Uncompleted => self.get(Key::LogUncompleted).to_string(),
+            LogEvent::StatusChanged { from, to } => self.format(
+                Key::LogStatusChanged,
+                &[&self.status(from), &self.status(to)],
+            ),
+        }
+    }
+
+    /// 桌面通知的正文
+    #[cfg_attr(not(feature = "notifications"), allow(dead_code))]
+    pub fn notification(&self, notification: &Notification) -> String {
+        match notification {
+            Notification::Milestone { title, days } => {
+                self.format(Key::NotifyMilestone, &[title, days])
+            }
+            Notification::Overdue { title, due } => self.format(Key::NotifyOverdue, &[title, due]),
+        }
+    }
+
+    /// 命令行参数错误的说明
+    pub fn cli_error(&self, error: &CliError) -> String {
+        match error {
+            CliError::MissingLang => self.get(Key::CliMissingLang).to_string(),
+            CliError::MissingNodeId(flag) => self.format(Key::CliMissingNodeId, &[flag]),
+            CliError::MissingPath(flag) => self.format(Key::CliMissingPath, &[flag]),
+            CliError::InvalidDepth => self.get(Key::CliInvalidDepth).to_string(),
+            CliError::UnsupportedLang(lang) => self.format(Key::CliUnsupportedLang, &[lang]),
+            CliError::UnknownArg(arg) => self.format(Key::CliUnknownArg, &[arg]),
+            CliError::Conflict(a, b) => self.format(Key::CliConflict, &[a, b]),
+            CliError::Requires(a, b) => self.format(Key::CliRequires, &[a, b]),
+        }
+    }
+
+    /// 树操作错误的说明
+    pub fn tree_error(&self, error: &TreeError) -> String {
+        match error {
+            TreeError::NotFound => self.get(Key::ErrNotFound).to_string(),
+            TreeError::MoveIntoSelf => self.get(Key::ErrMoveIntoSelf).to_string(),
+            TreeError::MoveIntoDescendant => self.get(Key::ErrMoveIntoDescendant).to_string(),
+            TreeError::DepthExceeded(max) => self.format(Key::ErrDepthExceeded, &[max]),
+            TreeError::LinkToSelf => self.get(Key::ErrLinkToSelf).to_string(),
+            TreeError::IdExists(id) => self.format(Key::ErrIdExists, &[id]),
+        }
+    }
+
+    /// 树结构校验问题的说明
+    pub fn tree_issue(&self, issue: &TreeIssue) -> String {
+        match issue {
+            TreeIssue::Orphan { id, parent_id } => self.format(Key::IssueOrphan, &[id, parent_id]),
+            TreeIssue::Cycle { id } => self.format(Key::IssueCycle, &[id]),
+            TreeIssue::DanglingChild {
+                parent_id,
+                child_id,
+            } if parent_id.is_empty() => self.format(Key::IssueDanglingRoot, &[child_id]),
+            TreeIssue::DanglingChild {
+                parent_id,
+                child_id,
+            } => self.format(Key::IssueDanglingChild, &[parent_id, child_id]),
+            TreeIssue::EmptyChildren { parent_id } => {
+                self.format(Key::IssueEmptyChildren, &[parent_id])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_switch_to_english() {
+        let zh = Strings::new(Lang::Zh);
+        let en = Strings::new(Lang::En);
+        assert_eq!(zh.get(Key::TreeTitle), "节点列表");
+        assert_eq!(en.get(Key::TreeTitle), "Nodes");
+        assert_eq!(en.get(Key::MsgAdded), "Node added");
+        assert_eq!(en.status(&NodeStatus::Completed), "Completed");
+        assert_eq!(en.format(Key::MsgDeleted, &[&3]), "Deleted 3 node(s)");
+        assert_eq!(zh.format(Key::MsgDeleted, &[&3]), "已删除 3 个节点");
+        assert_eq!(
+            en.tree_error(&TreeError::DepthExceeded(2)),
+            "Maximum depth exceeded (2 levels)"
+        );
+        assert_eq!(
+            en.tree_issue(&TreeIssue::Cycle {
+                id: "a".to_string()
+            }),
+            "Cycle: the ancestors of node a form a loop"
+        );
+        let orphan = TreeIssue::Orphan {
+            id: "a".to_string(),
+            parent_id: "b".to_string(),
+        };
+        assert_eq!(zh.tree_issue(&orphan), "孤立节点 a：父节点 b 不存在");
+        let milestone = Notification::Milestone {
+            title: "早起".to_string(),
+            days: 30,
+        };
+        assert_eq!(zh.notification(&milestone), "「早起」已坚持 30 天");
+        assert_eq!(
+            en.cli_error(&CliError::Conflict("--ephemeral", "--check")),
+            "--ephemeral cannot be used together with --check"
+        );
+        assert_eq!(
+            zh.cli_error(&CliError::UnknownArg("--bogus".to_string())),
+            "未知参数: --bogus"
+        );
+        let entry = LogEntry {
+            at: chrono::Local::now(),
+            text: String::new(),
+            event: Some(LogEvent::StatusChanged {
+                from: NodeStatus::Completed,
+                to: NodeStatus::Paused,
+            }),
+        };
+        assert_eq!(en.log_entry(&entry), "Status: Completed → Paused");
+        assert_eq!(zh.log_entry(&entry), "状态: 已完成 → 暂停");
+    }
+
+    #[test]
+    fn test_no_missing_strings() {
+        for &key in Key::ALL {
+            let zh = lookup(Lang::Zh, key);
+            let en = lookup(Lang::En, key);
+            assert!(!zh.is_empty() && !en.is_empty(), "{:?} 缺少文字", key);
+            // 两种语言的占位符数量必须一致
+            assert_eq!(
+                zh.matches("{}").count(),
+                en.matches("{}").count(),
+                "{:?}",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_lang() {
+        assert_eq!(Lang::parse("en"), Some(Lang::En));
+        assert_eq!(Lang::parse("zh"), Some(Lang::Zh));
+        assert_eq!(Lang::parse("fr"), None);
+    }
+}
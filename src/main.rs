@@ -1,10 +1,21 @@
+mod audit;
+mod cli;
+mod config;
+mod editor;
+mod hooks;
+mod i18n;
 mod models;
 mod storage;
+#[cfg(test)]
+mod test_util;
 mod ui;
+mod watch;
 
 use std::fs;
-use std::io;
-use std::path::PathBuf;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, Instant};
 
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
@@ -13,29 +24,165 @@ use crossterm::{
 };
 use ratatui::prelude::*;
 
-use crate::storage::{load_tree, save_tree};
+use crate::i18n::{Key, Strings};
+use crate::storage::{
+    append_snapshot_csv, check_writable, export_markdown, import_outline, load_tree,
+    load_tree_or_seed,
+};
+use crate::ui::actions::Action;
+use crate::ui::logic::DispatchResult;
 use crate::ui::{App, render};
 
-/// 获取数据目录路径 (~/.local/share/rhizome/)
-fn get_data_dir() -> io::Result<PathBuf> {
-    let data_dir = dirs::data_dir()
-        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "无法获取用户数据目录"))?
-        .join("rhizome");
+/// 获取数据目录路径 (~/.local/share/rhizome/)，不创建目录，需要写入时再创建
+fn get_data_dir(strings: &Strings) -> io::Result<PathBuf> {
+    Ok(dirs::data_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, strings.get(Key::CliNoDataDir)))?
+        .join("rhizome"))
+}
+
+/// 获取配置文件路径 (~/.config/rhizome/config.toml)
+fn get_config_path(strings: &Strings) -> io::Result<PathBuf> {
+    let config_dir = dirs::config_dir()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, strings.get(Key::CliNoConfigDir)))?;
+
+    Ok(config_dir.join("rhizome").join("config.toml"))
+}
+
+/// 校验数据文件，向 stderr 输出报告，返回是否发现问题
+fn check_data_file(data_path: &Path, strings: &Strings) -> io::Result<bool> {
+    if !data_path.exists() {
+        eprintln!(
+            "{}",
+            strings.format(Key::CheckMissing, &[&data_path.display()])
+        );
+        return Ok(false);
+    }
 
-    fs::create_dir_all(&data_dir)?;
+    let tree = load_tree(data_path)?;
+    let issues = tree.validate();
+    if issues.is_empty() {
+        eprintln!(
+            "{}",
+            strings.format(Key::CheckClean, &[&data_path.display(), &tree.nodes.len()])
+        );
+        return Ok(false);
+    }
 
-    Ok(data_dir)
+    eprintln!(
+        "{}",
+        strings.format(Key::CheckIssues, &[&data_path.display(), &issues.len()])
+    );
+    for issue in &issues {
+        eprintln!("  - {}", strings.tree_issue(issue));
+    }
+    Ok(true)
 }
 
 fn main() -> io::Result<()> {
-    // 数据文件路径 (~/.local/share/rhizome/data.toml)
-    let data_path = get_data_dir()?.join("data.toml");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // 先确定界面语言（命令行参数优先于配置文件），之后的所有输出都使用它；
+    // 配置文件有误时先用默认语言，错误在启动 TUI 前再报告
+    let cli_lang = cli::find_lang(&args);
+    let config = config::load_config(&get_config_path(&Strings::new(
+        cli_lang.unwrap_or_default(),
+    ))?);
+    let config_lang = config.as_ref().ok().map(|config| config.lang);
+
+    let options = cli::parse_args(args).unwrap_or_else(|e| {
+        let strings = Strings::new(cli_lang.or(config_lang).unwrap_or_default());
+        eprintln!("{}", strings.cli_error(&e));
+        process::exit(2);
+    });
+    let strings = Strings::new(options.lang.or(config_lang).unwrap_or_default());
+
+    if options.version {
+        println!("rhizome {}", env!("CARGO_PKG_VERSION"));
+        let data_path = get_data_dir(&strings)?.join("data.toml");
+        println!(
+            "{}",
+            strings.format(Key::CliDataFile, &[&data_path.display()])
+        );
+        return Ok(());
+    }
+
+    if options.check {
+        let has_issues = check_data_file(&get_data_dir(&strings)?.join("data.toml"), &strings)?;
+        process::exit(if has_issues { 1 } else { 0 });
+    }
+
+    if let Some(path) = &options.export_markdown {
+        let mut tree = load_tree(&get_data_dir(&strings)?.join("data.toml"))?;
+        if let Some(depth) = options.depth {
+            tree = models::FocusTree::from_data(tree.prune_to_depth(depth));
+        }
+        fs::write(path, export_markdown(&tree))?;
+        eprintln!(
+            "{}",
+            strings.format(Key::ExportedMarkdown, &[&tree.nodes.len(), &path.display()])
+        );
+        return Ok(());
+    }
+
+    // 加载配置
+    let mut config = config?;
+    config.lang = strings.lang;
+
+    // 临时会话不使用数据目录，也不读写其中的任何文件
+    let data_dir = if options.ephemeral {
+        None
+    } else {
+        Some(get_data_dir(&strings)?)
+    };
+    // 加载树（数据文件为 ~/.local/share/rhizome/data.toml），临时会话只使用初始内容
+    let mut tree = match &data_dir {
+        Some(dir) => load_tree_or_seed(&dir.join("data.toml"), &options.seed)?,
+        None => storage::seed_tree(&options.seed)?,
+    };
+    let imported = match &options.import_outline {
+        Some(path) => tree.append_data(import_outline(&fs::read_to_string(path)?)),
+        None => 0,
+    };
 
-    // 加载树
-    let tree = load_tree(&data_path)?;
+    #[cfg(feature = "notifications")]
+    send_notifications(&mut tree, &strings);
 
     // 创建应用状态
-    let mut app = App::new(tree);
+    let mut app = App::new(tree, config);
+    app.audit_path = data_dir.as_ref().map(|dir| dir.join("activity.log"));
+    app.data_path = data_dir.as_ref().map(|dir| dir.join("data.toml"));
+    app.readonly = options.readonly;
+    app.ephemeral = options.ephemeral;
+    if let Some(id) = options.goto {
+        app.dispatch(Action::GotoId(id));
+    }
+    // 数据文件来自更新的版本时只读打开，避免保存时丢掉不认识的字段
+    if let Some(version) = &app.tree.unknown_version {
+        app.readonly = true;
+        app.message = Some(
+            app.strings
+                .format(Key::MsgUnknownVersion, &[version, &models::DATA_VERSION]),
+        );
+    }
+    if imported > 0 {
+        app.message = Some(app.strings.format(Key::MsgImported, &[&imported]));
+    }
+    app.auto_fail_stale();
+    // 只读模式下不创建数据目录、不写快照，也不探测数据目录是否可写
+    if let Some(dir) = data_dir.as_ref().filter(|_| !app.readonly) {
+        // 尽早提示数据目录不可写，避免到退出时才发现修改丢失
+        match fs::create_dir_all(dir).and_then(|()| check_writable(dir)) {
+            Ok(()) => {
+                if let Err(e) = append_snapshot_csv(&app.tree, &dir.join("progress.csv")) {
+                    app.message = Some(app.strings.format(Key::MsgSnapshotFailed, &[&e]));
+                }
+            }
+            Err(e) => {
+                app.save_failed = true;
+                app.message = Some(app.strings.format(Key::MsgDataDirReadonly, &[&e]));
+            }
+        }
+    }
 
     // 设置终端
     enable_raw_mode()?;
@@ -56,27 +203,219 @@ fn main() -> io::Result<()> {
     )?;
     terminal.show_cursor()?;
 
-    // 保存数据
-    save_tree(&mut app.tree, &data_path)?;
-    println!("数据已保存到 {}", data_path.display());
+    // 放弃修改时不写入任何文件
+    if let Ok(DispatchResult::QuitDiscard) = result {
+        println!("{}", app.strings.get(Key::ExitDiscarded));
+        return Ok(());
+    }
+
+    // 保存数据，只有真正写入成功才提示已保存
+    let saved = app.save_on_exit();
+    match exit_report(saved, &app) {
+        Ok(line) => println!("{}", line),
+        Err(report) => {
+            eprintln!("{}", report);
+            process::exit(1);
+        }
+    }
+
+    result.map(|_| ())
+}
+
+/// 退出时保存结果的说明：成功时为输出到 stdout 的一行，失败时为输出到 stderr 的错误与处理建议
+///
+/// 只有真正写入了数据文件才提示已保存
+fn exit_report(saved: io::Result<Option<PathBuf>>, app: &App) -> Result<String, String> {
+    let strings = &app.strings;
+    match saved {
+        Ok(Some(path)) => Ok(strings.format(Key::ExitSaved, &[&path.display()])),
+        Ok(None) if app.readonly || app.ephemeral => Ok(strings.get(Key::ExitNotSaved).to_string()),
+        Ok(None) => Ok(strings.get(Key::ExitUnchanged).to_string()),
+        Err(e) => {
+            let hint = match e.kind() {
+                io::ErrorKind::PermissionDenied => Key::HintPermissionDenied,
+                io::ErrorKind::StorageFull => Key::HintStorageFull,
+                _ => Key::HintSaveFailed,
+            };
+            let path = app.data_path.as_deref().map_or_else(
+                || strings.get(Key::ExitUnknownPath).to_string(),
+                |p| p.display().to_string(),
+            );
+            Err(strings.format(Key::ExitSaveFailed, &[&path, &e, &strings.get(hint)]))
+        }
+    }
+}
+
+/// 暂停 TUI，用外部编辑器编辑节点内容，结束后恢复终端并写回结果
+fn edit_in_external_editor(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    id: String,
+) -> io::Result<()> {
+    let Some(content) = app.tree.nodes.get(&id).map(|n| n.content.clone()) else {
+        return Ok(());
+    };
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+
+    let result = editor::edit_text(&content);
+
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.clear()?;
+    app.apply_external_edit(id, result);
+    Ok(())
+}
 
-    result
+/// 发送启动时的桌面通知并记录已提醒的里程碑，发送失败（如没有通知服务）时忽略
+#[cfg(feature = "notifications")]
+fn send_notifications(tree: &mut models::FocusTree, strings: &Strings) {
+    let today = chrono::Local::now().date_naive();
+    for notification in tree.notifications(today) {
+        let _ = notify_rust::Notification::new()
+            .summary("Rhizome")
+            .body(&strings.notification(&notification))
+            .show();
+    }
+    tree.mark_milestones_notified(today);
 }
 
-fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> io::Result<()> {
+/// 等待按键的最长时间，超时后检查数据文件是否被外部修改
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 运行主循环直到退出，返回退出方式（保存 / 放弃修改）
+fn run_app(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+) -> io::Result<DispatchResult> {
+    #[cfg(feature = "watch")]
+    let mut watcher = app
+        .data_path
+        .as_deref()
+        .and_then(|path| watch::FileWatcher::new(path).ok());
+
     loop {
+        app.poll_hooks();
         terminal.draw(|f| render(f, app))?;
+        if std::mem::take(&mut app.bell_pending) {
+            terminal.backend_mut().write_all(b"\x07")?;
+            Write::flush(terminal.backend_mut())?;
+        }
+        // 闪烁结束后尽快重绘
+        let now = Instant::now();
+        let timeout = match app.flash_until {
+            Some(until) if app.is_flashing(now) => POLL_INTERVAL.min(until - now),
+            _ => {
+                app.flash_until = None;
+                POLL_INTERVAL
+            }
+        };
 
-        match crossterm::event::read()? {
-            crossterm::event::Event::Key(key)
-                if key.kind == crossterm::event::KeyEventKind::Press =>
+        // 只在普通模式下处理外部修改，避免打断正在进行的输入
+        #[cfg(feature = "watch")]
+        if let Some(watcher) = watcher.as_mut()
+            && app.mode == ui::state::AppMode::Normal
+            && watcher.poll_changed()
+        {
+            app.on_external_change();
+            continue;
+        }
+
+        if !crossterm::event::poll(timeout)? {
+            continue;
+        }
+        // 重绘前取完所有已到达的事件，按住按键时不会每帧只前进一步
+        let mut keys = Vec::new();
+        loop {
+            if let crossterm::event::Event::Key(key) = crossterm::event::read()?
+                && matches!(
+                    key.kind,
+                    crossterm::event::KeyEventKind::Press | crossterm::event::KeyEventKind::Repeat
+                )
             {
-                if ui::handle_key_event(app, key.code)? {
-                    break;
-                }
+                keys.push(key);
+            }
+            if !crossterm::event::poll(Duration::ZERO)? {
+                break;
             }
-            _ => {}
+        }
+        let result = ui::handle_key_events(app, keys)?;
+        if result.is_quit() {
+            return Ok(result);
+        }
+        if let Some(id) = app.editor_request.take() {
+            edit_in_external_editor(terminal, app, id)?;
         }
     }
-    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::models::TreeBuilder;
+
+    #[test]
+    fn test_exit_report_on_save_failure() {
+        let dir = crate::test_util::temp_path("missing");
+        let mut app = App::new(TreeBuilder::new().leaf("a").build(), Config::default());
+        app.data_path = Some(dir.join("data.toml"));
+
+        let saved = app.save_on_exit();
+        assert!(saved.is_err());
+        let report = exit_report(saved, &app).unwrap_err();
+        assert!(!report.contains("数据已保存"));
+        assert!(report.contains(&dir.join("data.toml").display().to_string()));
+        assert!(report.contains("请检查"));
+
+        let path = dir.join("data.toml");
+        assert_eq!(
+            exit_report(Ok(Some(path.clone())), &app).unwrap(),
+            format!("数据已保存到 {}", path.display())
+        );
+    }
+
+    #[test]
+    fn test_exit_report_only_claims_saved_after_writing() {
+        let path = crate::test_util::temp_path("unchanged.toml");
+        let mut app = App::new(TreeBuilder::new().leaf("a").build(), Config::default());
+        app.data_path = Some(path.clone());
+        app.tree.dirty = false;
+        app.remember_selection();
+        app.tree.dirty = false;
+
+        // 没有修改时不写文件，也不提示已保存
+        let saved = app.save_on_exit();
+        assert_eq!(saved.as_ref().unwrap(), &None);
+        assert_eq!(
+            exit_report(saved, &app).unwrap(),
+            app.strings.get(Key::ExitUnchanged)
+        );
+        assert!(!path.exists());
+
+        app.readonly = true;
+        assert_eq!(
+            exit_report(Ok(None), &app).unwrap(),
+            app.strings.get(Key::ExitNotSaved)
+        );
+
+        app.strings = Strings::new(i18n::Lang::En);
+        app.readonly = false;
+        app.tree.dirty = true;
+        let saved = app.save_on_exit();
+        assert_eq!(
+            exit_report(saved, &app).unwrap(),
+            format!("Data saved to {}", path.display())
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
 }
@@ -1,6 +1,6 @@
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDate};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 /// 节点状态
@@ -9,11 +9,48 @@ use uuid::Uuid;
 pub enum NodeStatus {
     #[default]
     Active, // 活跃状态
-    Failed, // 失败状态
+    Failed,    // 失败状态
+    Completed, // 已完成
+    Paused,    // 暂停
+}
+
+impl NodeStatus {
+    /// 按状态排列兄弟节点时的次序：活跃、暂停、已完成、失败
+    pub fn sort_rank(&self) -> u8 {
+        match self {
+            NodeStatus::Active => 0,
+            NodeStatus::Paused => 1,
+            NodeStatus::Completed => 2,
+            NodeStatus::Failed => 3,
+        }
+    }
+}
+
+/// 节点日志条目
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub at: DateTime<Local>,
+    #[serde(default)]
+    pub text: String, // 旧版本记录的文字，新记录的事件为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<LogEvent>, // 与语言无关的事件，显示时由 `Strings::log_entry` 生成文字
+}
+
+/// 状态变化等自动记录到节点日志的事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LogEvent {
+    Failed,
+    AutoFailed { days: u32 }, // 超过天数未更新
+    FailReason { reason: String },
+    Recovered,
+    Completed,
+    Uncompleted,
+    StatusChanged { from: NodeStatus, to: NodeStatus },
 }
 
 /// 国策节点
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FocusNode {
     pub id: String,
     #[serde(default)]
@@ -26,6 +63,26 @@ pub struct FocusNode {
     pub status: NodeStatus,
     #[serde(default)]
     pub streak_days: u32,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Local>>, // 最后一次修改时间，None 表示创建后未修改
+    #[serde(default)]
+    pub log: Vec<LogEntry>, // 按时间顺序追加
+    #[serde(default)]
+    pub archived: bool, // 已归档的节点（连同子树）默认不显示
+    #[serde(default)]
+    pub color: Option<String>, // 标题颜色标签（颜色名或 #rrggbb），优先于状态颜色
+    #[serde(default)]
+    pub due: Option<NaiveDate>, // 截止日期
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Local>>, // 标记完成的时间，不是已完成状态时为 None
+    #[serde(default)]
+    pub estimate: f32, // 预估工作量（如小时），0 表示未设置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub period: Option<String>, // 根节点分组显示时的周期标签（如 "2024 Q1"），为空时按创建时间推算
+    #[serde(default)]
+    pub links: Vec<String>, // 关联（参见）节点的 ID，按添加顺序
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notified_milestone: Option<i64>, // 已发送过提醒的最高连续天数里程碑
 }
 
 impl FocusNode {
@@ -38,6 +95,16 @@ impl FocusNode {
             created_at: Local::now(),
             status: NodeStatus::Active,
             streak_days: 0,
+            updated_at: None,
+            log: Vec::new(),
+            archived: false,
+            color: None,
+            due: None,
+            completed_at: None,
+            estimate: 0.0,
+            period: None,
+            links: Vec::new(),
+            notified_milestone: None,
         }
     }
 
@@ -45,13 +112,89 @@ impl FocusNode {
         self.parent_id.is_empty()
     }
 
+    /// 记录一次修改
+    pub fn touch(&mut self) {
+        self.updated_at = Some(Local::now());
+    }
+
+    /// 追加一条日志
+    pub fn append_log(&mut self, event: LogEvent) {
+        self.log.push(LogEntry {
+            at: Local::now(),
+            text: String::new(),
+            event: Some(event),
+        });
+    }
+
     pub fn days_active(&self) -> i64 {
         let duration = Local::now() - self.created_at;
         duration.num_days().max(0)
     }
+
+    /// 修改状态并维护完成时间：进入已完成时记录，离开时清除
+    pub fn set_status(&mut self, status: NodeStatus) {
+        self.completed_at = (status == NodeStatus::Completed).then(Local::now);
+        self.status = status;
+    }
+
+    /// 快速切换的下一个状态：活跃 → 已完成 → 暂停 → 活跃；失败节点不参与切换
+    pub fn next_status(&self) -> Option<NodeStatus> {
+        match self.status {
+            NodeStatus::Active => Some(NodeStatus::Completed),
+            NodeStatus::Completed => Some(NodeStatus::Paused),
+            NodeStatus::Paused => Some(NodeStatus::Active),
+            NodeStatus::Failed => None,
+        }
+    }
+
+    /// 截止日期已过且仍处于活跃状态
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        self.status == NodeStatus::Active && self.due.is_some_and(|due| due < today)
+    }
+
+    /// `today` 已达到但尚未提醒过的最高里程碑，只有活跃节点参与
+    pub fn pending_milestone(&self, today: NaiveDate) -> Option<i64> {
+        if self.status != NodeStatus::Active {
+            return None;
+        }
+        let days = (today - self.created_at.date_naive()).num_days();
+        STREAK_MILESTONES
+            .iter()
+            .rev()
+            .copied()
+            .find(|&milestone| days >= milestone)
+            .filter(|&milestone| self.notified_milestone.is_none_or(|sent| sent < milestone))
+    }
+
+    /// 是否在今天（本地日历日）创建
+    pub fn created_today(&self) -> bool {
+        self.created_at.date_naive() == Local::now().date_naive()
+    }
+
+    /// 除标题和子节点外是否还带有用户数据（内容、日志、非活跃状态、截止日期等）
+    pub fn has_own_data(&self) -> bool {
+        !self.content.trim().is_empty()
+            || !self.log.is_empty()
+            || self.status != NodeStatus::Active
+            || self.archived
+            || self.color.is_some()
+            || self.due.is_some()
+            || self.estimate > 0.0
+            || !self.links.is_empty()
+    }
 }
 
-pub const DATA_VERSION: &str = "1.0";
+/// 当前数据文件格式版本（`主.次`），读取旧版本时先迁移，保存时总是写入当前版本
+///
+/// - 1.0：初始格式
+/// - 1.1：已完成节点记录 `completed_at`
+pub const DATA_VERSION: &str = "1.1";
+
+/// 解析 `主.次` 格式的版本号
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let (major, minor) = version.trim().split_once('.')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
 
 /// TOML文件结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,6 +207,39 @@ pub struct FocusTreeData {
 pub struct TreeMeta {
     pub version: String,
     pub last_modified: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected_id: Option<String>, // 上次退出时选中的节点
+}
+
+impl FocusTreeData {
+    /// 版本号比当前程序支持的更新或无法识别，此时不做迁移，按原样读取
+    pub fn is_unknown_version(&self) -> bool {
+        match (
+            parse_version(&self.meta.version),
+            parse_version(DATA_VERSION),
+        ) {
+            (Some(version), Some(current)) => version > current,
+            _ => true,
+        }
+    }
+
+    /// 将旧版本的数据迁移到当前版本，返回是否做了迁移
+    pub fn migrate(&mut self) -> bool {
+        if self.is_unknown_version() || self.meta.version == DATA_VERSION {
+            return false;
+        }
+        let version = parse_version(&self.meta.version).unwrap_or_default();
+        if version < (1, 1) {
+            // 旧数据没有完成时间，以最后修改时间（未修改时为创建时间）补齐
+            for node in &mut self.nodes {
+                if node.status == NodeStatus::Completed && node.completed_at.is_none() {
+                    node.completed_at = Some(node.updated_at.unwrap_or(node.created_at));
+                }
+            }
+        }
+        self.meta.version = DATA_VERSION.to_string();
+        true
+    }
 }
 
 impl Default for FocusTreeData {
@@ -73,19 +249,130 @@ impl Default for FocusTreeData {
             meta: TreeMeta {
                 version: DATA_VERSION.to_string(),
                 last_modified: now,
+                selected_id: None,
             },
             nodes: Vec::new(),
         }
     }
 }
 
+/// 树结构校验发现的问题
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeIssue {
+    /// 节点的父节点不存在
+    Orphan { id: String, parent_id: String },
+    /// 节点的祖先链形成环
+    Cycle { id: String },
+    /// children_map（或根列表，此时 parent_id 为空）引用了不存在的节点
+    DanglingChild { parent_id: String, child_id: String },
+    /// children_map 中存在空的子节点列表
+    EmptyChildren { parent_id: String },
+}
+
+impl TreeIssue {
+    /// 出问题的节点 ID（悬空引用与空列表为其父节点，根列表的悬空引用为 None）
+    pub fn node_id(&self) -> Option<&str> {
+        match self {
+            TreeIssue::Orphan { id, .. } | TreeIssue::Cycle { id } => Some(id),
+            TreeIssue::DanglingChild { parent_id, .. } | TreeIssue::EmptyChildren { parent_id } => {
+                Some(parent_id.as_str()).filter(|id| !id.is_empty())
+            }
+        }
+    }
+}
+
+/// 树操作被拒绝的原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeError {
+    NotFound,
+    MoveIntoSelf,
+    MoveIntoDescendant,
+    DepthExceeded(usize), // 超出最大层级限制
+    LinkToSelf,
+    IdExists(String), // 要插入的节点 ID 已在树中
+}
+
+/// 连续天数达到这些值时提醒
+pub const STREAK_MILESTONES: [i64; 3] = [7, 30, 100];
+
+/// 启动时需要提醒用户的事件
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+    /// 活跃节点的连续天数达到新的里程碑（`days` 为里程碑天数）
+    Milestone { title: String, days: i64 },
+    /// 活跃节点已过截止日期
+    Overdue { title: String, due: NaiveDate },
+}
+
+/// 被删除的子树，保存恢复所需的全部数据
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemovedSubtree {
+    pub nodes: Vec<FocusNode>, // 子树根在最前，父节点总在其子节点之前
+    pub position: usize,       // 子树根在兄弟节点（或根列表）中的位置
+}
+
+/// 各状态的节点数量
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StatusCounts {
+    pub active: usize,
+    pub failed: usize,
+    pub completed: usize,
+    pub paused: usize,
+}
+
+impl StatusCounts {
+    fn tally<'a>(nodes: impl Iterator<Item = &'a FocusNode>) -> Self {
+        let mut counts = Self::default();
+        for node in nodes {
+            match node.status {
+                NodeStatus::Active => counts.active += 1,
+                NodeStatus::Failed => counts.failed += 1,
+                NodeStatus::Completed => counts.completed += 1,
+                NodeStatus::Paused => counts.paused += 1,
+            }
+        }
+        counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.active + self.failed + self.completed + self.paused
+    }
+
+    /// 完成度百分比（向下取整）：已完成 / (活跃 + 已完成)，`include_inactive` 时分母为全部节点；
+    /// 分母为 0 时为 0
+    pub fn completion_percent(&self, include_inactive: bool) -> usize {
+        let denominator = if include_inactive {
+            self.total()
+        } else {
+            self.active + self.completed
+        };
+        (self.completed * 100).checked_div(denominator).unwrap_or(0)
+    }
+}
+
+/// 生成显示列表时的过滤选项
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplayFilter {
+    pub show_archived: bool,
+    pub collapsed: HashSet<String>, // 已折叠的节点，其子树不显示
+    pub roots_only: bool,           // 目录模式：只显示根节点
+    pub sort_by_status: bool,       // 子节点按状态排列（同状态保持原有顺序），根节点不参与
+}
+
 /// 运行时树结构（用于高效操作）
+///
+/// 兄弟节点的顺序由 `root_ids` / `children_map` 中的顺序决定，也是显示与保存的顺序：
+/// - 新增、移动的节点总是追加到新父节点子列表的末尾
+/// - 删除只移除对应 id，其余兄弟节点保持原有相对顺序
 #[derive(Debug, Clone)]
 pub struct FocusTree {
     pub nodes: HashMap<String, FocusNode>,
     pub root_ids: Vec<String>,
     pub children_map: HashMap<String, Vec<String>>, // parent_id -> child_ids
     pub dirty: bool,
+    pub max_depth: Option<usize>, // 最大层级数（根节点为第 1 层），None 表示不限制
+    pub selected_id: Option<String>, // 界面中选中的节点，随数据文件保存
+    pub unknown_version: Option<String>, // 数据文件版本比当前程序新时记录该版本，用于提示
 }
 
 impl FocusTree {
@@ -95,31 +382,88 @@ impl FocusTree {
             root_ids: Vec::new(),
             children_map: HashMap::new(),
             dirty: false,
+            max_depth: None,
+            selected_id: None,
+            unknown_version: None,
         }
     }
 
-    pub fn from_data(data: FocusTreeData) -> Self {
+    /// 从数据文件结构构建，旧版本数据先迁移到当前版本（并标记为待保存）
+    pub fn from_data(mut data: FocusTreeData) -> Self {
+        let migrated = data.migrate();
         let mut tree = Self::new();
+        if data.is_unknown_version() {
+            tree.unknown_version = Some(data.meta.version.clone());
+        }
         for node in data.nodes {
             tree.insert_node(node);
         }
-        // 从文件加载的不视为脏数据
-        tree.dirty = false;
+        let pruned = tree.prune_links();
+        tree.selected_id = data.meta.selected_id;
+        // 从文件加载的不视为脏数据，迁移过或清理过关联的需要写回
+        tree.dirty = migrated || pruned > 0;
         tree
     }
 
+    /// 去掉指向不存在节点或自身的关联，返回去掉的数量
+    fn prune_links(&mut self) -> usize {
+        let ids: HashSet<String> = self.nodes.keys().cloned().collect();
+        let mut pruned = 0;
+        for node in self.nodes.values_mut() {
+            let before = node.links.len();
+            let own = node.id.clone();
+            node.links.retain(|id| *id != own && ids.contains(id));
+            pruned += before - node.links.len();
+        }
+        pruned
+    }
+
+    /// 将另一份数据中的节点追加到本树（根节点追加在现有根节点之后），返回追加的节点数
+    pub fn append_data(&mut self, data: FocusTreeData) -> usize {
+        let count = data.nodes.len();
+        for node in data.nodes {
+            self.insert_node(node);
+        }
+        if count > 0 {
+            self.dirty = true;
+        }
+        count
+    }
+
+    /// 导出为可序列化结构
+    ///
+    /// 节点按深度优先顺序输出（与界面显示一致），无法从根到达的节点按 id 排序追加在末尾，
+    /// 保证同一棵树多次保存得到相同的内容
     pub fn to_data(&self) -> FocusTreeData {
-        let nodes: Vec<FocusNode> = self.nodes.values().cloned().collect();
+        let mut nodes: Vec<FocusNode> = self
+            .flatten_for_display()
+            .into_iter()
+            .map(|(_, node)| node.clone())
+            .collect();
+
+        if nodes.len() < self.nodes.len() {
+            let reachable: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+            let mut rest: Vec<&FocusNode> = self
+                .nodes
+                .values()
+                .filter(|n| !reachable.contains(n.id.as_str()))
+                .collect();
+            rest.sort_by(|a, b| a.id.cmp(&b.id));
+            nodes.extend(rest.into_iter().cloned());
+        }
+
         let now = Local::now();
         FocusTreeData {
             meta: TreeMeta {
                 version: DATA_VERSION.to_string(),
                 last_modified: now,
+                selected_id: self.selected_id.clone(),
             },
             nodes,
         }
     }
 
+    /// 插入节点，追加到父节点（或根列表）的末尾
     fn insert_node(&mut self, node: FocusNode) {
         let id = node.id.clone();
         let parent_id = node.parent_id.clone();
@@ -136,18 +480,342 @@ impl FocusTree {
         self.nodes.insert(id, node);
     }
 
-    /// 添加新节点
+    /// 添加新节点，超出最大层级时拒绝
     pub fn add_node(
         &mut self,
         title: String,
         content: String,
         parent_id: Option<String>,
-    ) -> String {
+    ) -> Result<String, TreeError> {
+        let parent_level = match parent_id.as_deref() {
+            Some(id) if self.nodes.contains_key(id) => self.level(id),
+            Some(_) => return Err(TreeError::NotFound),
+            None => 0,
+        };
+        self.check_depth(parent_level + 1)?;
+
         let node = FocusNode::new(title, content, parent_id);
         let id = node.id.clone();
         self.insert_node(node);
         self.dirty = true;
-        id
+        Ok(id)
+    }
+
+    /// 只保留前 `max` 层（根节点为第 1 层）的副本，用于导出摘要
+    ///
+    /// 按显示顺序输出可从根到达的节点，被裁掉的子节点不会出现在任何父节点之下
+    pub fn prune_to_depth(&self, max: usize) -> FocusTreeData {
+        let nodes = self
+            .flatten_for_display()
+            .into_iter()
+            .filter(|(depth, _)| *depth < max)
+            .map(|(_, node)| node.clone())
+            .collect();
+        FocusTreeData {
+            meta: TreeMeta {
+                version: DATA_VERSION.to_string(),
+                last_modified: Local::now(),
+                selected_id: None,
+            },
+            nodes,
+        }
+    }
+
+    /// 节点所在层级（根节点为 1），节点不存在时为 0
+    pub fn level(&self, node_id: &str) -> usize {
+        let mut level = 0;
+        let mut current = node_id;
+        while let Some(node) = self.nodes.get(current) {
+            level += 1;
+            // 防御数据中的环
+            if node.is_root() || level > self.nodes.len() {
+                break;
+            }
+            current = &node.parent_id;
+        }
+        level
+    }
+
+    /// 从根节点到该节点（含）的 ID 路径，节点不存在时为空
+    ///
+    /// 父节点缺失时路径从最上层仍存在的祖先开始
+    pub fn path_ids(&self, node_id: &str) -> Vec<String> {
+        let mut path = Vec::new();
+        let mut current = self.nodes.get(node_id);
+        while let Some(node) = current {
+            path.push(node.id.clone());
+            // 防御数据中的环
+            if node.is_root() || path.len() > self.nodes.len() {
+                break;
+            }
+            current = self.nodes.get(&node.parent_id);
+        }
+        path.reverse();
+        path
+    }
+
+    /// 节点所在子树的根节点 ID，节点不存在时为 None
+    pub fn root_of(&self, node_id: &str) -> Option<String> {
+        self.path_ids(node_id).into_iter().next()
+    }
+
+    /// 节点在兄弟节点中的位置：(从 1 开始的序号, 兄弟节点总数)，根节点在 `root_ids` 中计数
+    pub fn sibling_position(&self, node_id: &str) -> Option<(usize, usize)> {
+        let node = self.nodes.get(node_id)?;
+        let siblings = if node.is_root() {
+            &self.root_ids
+        } else {
+            self.children_map.get(&node.parent_id)?
+        };
+        let index = siblings.iter().position(|id| id == node_id)?;
+        Some((index + 1, siblings.len()))
+    }
+
+    /// 以该节点为根的子树高度（叶子为 1）
+    fn subtree_height(&self, node_id: &str) -> usize {
+        1 + self
+            .children_map
+            .get(node_id)
+            .map(|children| {
+                children
+                    .iter()
+                    .map(|id| self.subtree_height(id))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .unwrap_or(0)
+    }
+
+    /// 检查层级是否超出限制
+    fn check_depth(&self, level: usize) -> Result<(), TreeError> {
+        match self.max_depth {
+            Some(max) if level > max => Err(TreeError::DepthExceeded(max)),
+            _ => Ok(()),
+        }
+    }
+
+    /// 修改节点标题
+    pub fn update_title(&mut self, node_id: &str, title: String) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.title = title;
+            node.touch();
+            self.dirty = true;
+        }
+    }
+
+    /// 修改节点内容
+    pub fn update_content(&mut self, node_id: &str, content: String) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.content = content;
+            node.touch();
+            self.dirty = true;
+        }
+    }
+
+    /// 设置节点的颜色标签，None 表示清除
+    pub fn set_color(&mut self, node_id: &str, color: Option<String>) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.color = color;
+            node.touch();
+            self.dirty = true;
+        }
+    }
+
+    /// 设置节点的预估工作量
+    pub fn set_estimate(&mut self, node_id: &str, estimate: f32) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.estimate = estimate;
+            node.touch();
+            self.dirty = true;
+        }
+    }
+
+    /// 设置或清除节点的截止日期
+    pub fn set_due(&mut self, node_id: &str, due: Option<NaiveDate>) {
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.due = due;
+            node.touch();
+            self.dirty = true;
+        }
+    }
+
+    /// 为节点添加一个关联节点，返回是否新增（已关联时为 false）
+    pub fn add_link(&mut self, node_id: &str, target_id: &str) -> Result<bool, TreeError> {
+        if !self.nodes.contains_key(target_id) {
+            return Err(TreeError::NotFound);
+        }
+        if node_id == target_id {
+            return Err(TreeError::LinkToSelf);
+        }
+        let node = self.nodes.get_mut(node_id).ok_or(TreeError::NotFound)?;
+        if node.links.iter().any(|id| id == target_id) {
+            return Ok(false);
+        }
+        node.links.push(target_id.to_string());
+        node.touch();
+        self.dirty = true;
+        Ok(true)
+    }
+
+    /// 节点关联的仍然存在的节点，按添加顺序
+    pub fn linked_nodes(&self, node_id: &str) -> Vec<&FocusNode> {
+        self.nodes
+            .get(node_id)
+            .map(|node| {
+                node.links
+                    .iter()
+                    .filter_map(|id| self.nodes.get(id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 节点自身与所有子孙节点的预估工作量之和，节点不存在时为 0
+    pub fn rolled_up_estimate(&self, node_id: &str) -> f32 {
+        let Some(node) = self.nodes.get(node_id) else {
+            return 0.0;
+        };
+        node.estimate
+            + self
+                .children_map
+                .get(node_id)
+                .map(|children| {
+                    children
+                        .iter()
+                        .map(|child| self.rolled_up_estimate(child))
+                        .sum()
+                })
+                .unwrap_or(0.0)
+    }
+
+    /// 标题或内容中包含 `pattern` 的节点 id（按显示顺序）
+    pub fn find_text(&self, pattern: &str) -> Vec<String> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+        self.flatten_for_display()
+            .into_iter()
+            .filter(|(_, node)| node.title.contains(pattern) || node.content.contains(pattern))
+            .map(|(_, node)| node.id.clone())
+            .collect()
+    }
+
+    /// 将所有标题中的 `from` 替换为 `to`，返回（替换的次数，跳过的节点数）
+    ///
+    /// 替换后标题为空或只剩空白的节点保持不变
+    pub fn replace_in_titles(&mut self, from: &str, to: &str) -> (usize, usize) {
+        self.replace_text(
+            from,
+            to,
+            |node| &mut node.title,
+            |title| !title.trim().is_empty(),
+        )
+    }
+
+    /// 将所有内容中的 `from` 替换为 `to`，返回替换的次数
+    pub fn replace_in_content(&mut self, from: &str, to: &str) -> usize {
+        self.replace_text(from, to, |node| &mut node.content, |_| true)
+            .0
+    }
+
+    /// 替换结果不满足 `accept` 的节点跳过，返回（替换的次数，跳过的节点数）
+    fn replace_text(
+        &mut self,
+        from: &str,
+        to: &str,
+        field: impl Fn(&mut FocusNode) -> &mut String,
+        accept: impl Fn(&str) -> bool,
+    ) -> (usize, usize) {
+        if from.is_empty() {
+            return (0, 0);
+        }
+        let (mut count, mut skipped) = (0, 0);
+        for node in self.nodes.values_mut() {
+            let text = field(node);
+            let n = text.matches(from).count();
+            if n == 0 {
+                continue;
+            }
+            let replaced = text.replace(from, to);
+            if !accept(&replaced) {
+                skipped += 1;
+                continue;
+            }
+            *text = replaced;
+            node.touch();
+            count += n;
+        }
+        if count > 0 {
+            self.dirty = true;
+        }
+        (count, skipped)
+    }
+
+    /// 将节点移动到新的父节点下（None 表示移动为根节点）
+    ///
+    /// 返回 `Ok(false)` 表示目标即当前位置，未做任何修改
+    pub fn move_node(
+        &mut self,
+        node_id: &str,
+        new_parent_id: Option<&str>,
+    ) -> Result<bool, TreeError> {
+        let new_parent_id = new_parent_id.unwrap_or_default();
+        let old_parent_id = match self.nodes.get(node_id) {
+            Some(node) => node.parent_id.clone(),
+            None => return Err(TreeError::NotFound),
+        };
+
+        if old_parent_id == new_parent_id {
+            return Ok(false);
+        }
+        if !new_parent_id.is_empty() {
+            if new_parent_id == node_id {
+                return Err(TreeError::MoveIntoSelf);
+            }
+            if !self.nodes.contains_key(new_parent_id) {
+                return Err(TreeError::NotFound);
+            }
+            if self.is_descendant(node_id, new_parent_id) {
+                return Err(TreeError::MoveIntoDescendant);
+            }
+        }
+        let parent_level = if new_parent_id.is_empty() {
+            0
+        } else {
+            self.level(new_parent_id)
+        };
+        self.check_depth(parent_level + self.subtree_height(node_id))?;
+
+        self.detach_from_parent(node_id, &old_parent_id);
+
+        // 添加到新父节点
+        if new_parent_id.is_empty() {
+            self.root_ids.push(node_id.to_string());
+        } else {
+            self.children_map
+                .entry(new_parent_id.to_string())
+                .or_default()
+                .push(node_id.to_string());
+        }
+
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.parent_id = new_parent_id.to_string();
+            node.touch();
+        }
+        self.dirty = true;
+        Ok(true)
+    }
+
+    /// 从父节点的子列表（或根列表）中移除节点，子列表为空时一并移除该条目
+    fn detach_from_parent(&mut self, node_id: &str, parent_id: &str) {
+        if parent_id.is_empty() {
+            self.root_ids.retain(|id| id != node_id);
+        } else if let Some(siblings) = self.children_map.get_mut(parent_id) {
+            siblings.retain(|id| id != node_id);
+            if siblings.is_empty() {
+                self.children_map.remove(parent_id);
+            }
+        }
     }
 
     /// 获取节点的所有子节点ID（递归）
@@ -167,6 +835,27 @@ impl FocusTree {
         descendants
     }
 
+    /// `maybe_descendant` 是否位于 `ancestor` 的子树中（不含自身），沿父节点向上查找，找到即返回
+    pub fn is_descendant(&self, ancestor: &str, maybe_descendant: &str) -> bool {
+        let mut current = self.nodes.get(maybe_descendant);
+        // 防御数据中的环
+        for _ in 0..self.nodes.len() {
+            let Some(node) = current.filter(|n| !n.is_root()) else {
+                return false;
+            };
+            if node.parent_id == ancestor {
+                return true;
+            }
+            current = self.nodes.get(&node.parent_id);
+        }
+        false
+    }
+
+    /// 子树大小（节点自身加上全部后代）
+    pub fn subtree_size(&self, node_id: &str) -> usize {
+        1 + self.get_all_descendants(node_id).len()
+    }
+
     /// 删除节点及其所有子节点（堆栈式删除）
     pub fn delete_node(&mut self, node_id: &str) -> Vec<String> {
         self.dirty = true;
@@ -175,12 +864,7 @@ impl FocusTree {
 
         for id in &deleted {
             if let Some(node) = self.nodes.remove(id) {
-                // 从父节点的children_map中移除
-                if node.is_root() {
-                    self.root_ids.retain(|x| x != id);
-                } else if let Some(siblings) = self.children_map.get_mut(&node.parent_id) {
-                    siblings.retain(|x| x != id);
-                }
+                self.detach_from_parent(id, &node.parent_id);
                 // 移除自己的children_map条目
                 self.children_map.remove(id);
             }
@@ -189,105 +873,595 @@ impl FocusTree {
         deleted
     }
 
-    /// 标记节点失败并级联删除所有子节点
-    pub fn fail_node(&mut self, node_id: &str) -> Vec<String> {
+    /// 只删除节点本身，子节点按原顺序接替它在原父节点（或根列表）中的位置，返回被上移的子节点
+    pub fn delete_node_reparent(&mut self, node_id: &str) -> Vec<String> {
+        let Some(node) = self.nodes.remove(node_id) else {
+            return Vec::new();
+        };
         self.dirty = true;
-        // 标记为失败
-        if let Some(node) = self.nodes.get_mut(node_id) {
-            node.status = NodeStatus::Failed;
+        let children = self.children_map.remove(node_id).unwrap_or_default();
+        for child_id in &children {
+            if let Some(child) = self.nodes.get_mut(child_id) {
+                child.parent_id = node.parent_id.clone();
+                child.touch();
+            }
         }
-        // 删除所有子节点
-        let descendants = self.get_all_descendants(node_id);
-        
-        descendants.iter().for_each(|id| {
-            self.nodes.remove(id);
-        });
 
-        // 返回被删除的子节点
-        let deleted = self.get_all_descendants(node_id);
-        for id in &deleted {
-            self.children_map.remove(id);
+        let siblings = if node.is_root() {
+            &mut self.root_ids
+        } else {
+            self.children_map.entry(node.parent_id.clone()).or_default()
+        };
+        match siblings.iter().position(|id| id == node_id) {
+            Some(at) => {
+                siblings.splice(at..=at, children.iter().cloned());
+            }
+            None => siblings.extend(children.iter().cloned()),
         }
-        
-        deleted
+        if siblings.is_empty() && !node.is_root() {
+            self.children_map.remove(&node.parent_id);
+        }
+        children
     }
 
-    pub fn recover_node(&mut self, node_id: &str) {
-        if let Some(node) = self
-            .nodes
-            .get_mut(node_id)
-            .filter(|n| n.status == NodeStatus::Failed)
-        {
-            node.status = NodeStatus::Active;
+    /// 合并标题相同的根节点：保留最前面的一个，其余根节点的子节点按顺序追加到它下面后删除，
+    /// 返回 (合并掉的根节点数, 跳过的根节点数)
+    ///
+    /// 带有自身数据（内容、日志、非活跃状态等）的重复根节点删除后会丢失这些数据，因此跳过，保留为单独的根节点
+    pub fn merge_roots_by_title(&mut self) -> (usize, usize) {
+        let mut kept: HashMap<&str, &str> = HashMap::new();
+        let mut duplicates: Vec<(String, String)> = Vec::new(); // (重复的根, 保留的根)
+        let mut skipped = 0;
+        for id in &self.root_ids {
+            let Some(node) = self.nodes.get(id) else {
+                continue;
+            };
+            match kept.get(node.title.as_str()) {
+                Some(_) if node.has_own_data() => skipped += 1,
+                Some(keep) => duplicates.push((id.clone(), keep.to_string())),
+                None => {
+                    kept.insert(&node.title, id);
+                }
+            }
+        }
+
+        for (duplicate, keep) in &duplicates {
+            let children = self.children_map.remove(duplicate).unwrap_or_default();
+            for child_id in &children {
+                if let Some(child) = self.nodes.get_mut(child_id) {
+                    child.parent_id = keep.clone();
+                    child.touch();
+                }
+            }
+            if !children.is_empty() {
+                self.children_map
+                    .entry(keep.clone())
+                    .or_default()
+                    .extend(children);
+            }
+            self.nodes.remove(duplicate);
+            self.root_ids.retain(|id| id != duplicate);
+        }
+        if !duplicates.is_empty() {
             self.dirty = true;
         }
+        (duplicates.len(), skipped)
     }
 
-    /// 获取直接子节点
-    #[allow(dead_code)]
-    pub fn get_children(&self, node_id: &str) -> Vec<&FocusNode> {
-        self.children_map
-            .get(node_id)
-            .map(|ids| ids.iter().filter_map(|id| self.nodes.get(id)).collect())
-            .unwrap_or_default()
+    /// 复制即将删除的子树，供 `reinsert_nodes` 撤销删除
+    pub fn subtree_snapshot(&self, node_id: &str) -> Option<RemovedSubtree> {
+        let root = self.nodes.get(node_id)?;
+        let siblings = if root.is_root() {
+            &self.root_ids
+        } else {
+            self.children_map.get(&root.parent_id)?
+        };
+        let position = siblings.iter().position(|id| id == node_id)?;
+        let nodes = std::iter::once(node_id.to_string())
+            .chain(self.get_all_descendants(node_id))
+            .filter_map(|id| self.nodes.get(&id).cloned())
+            .collect();
+        Some(RemovedSubtree { nodes, position })
     }
 
-    /// 获取根节点
-    #[allow(dead_code)]
-    pub fn get_roots(&self) -> Vec<&FocusNode> {
-        self.root_ids
+    /// 将删除的子树插回原来的位置；原父节点已不存在或节点 ID 已在树中时返回错误
+    pub fn reinsert_nodes(&mut self, removed: RemovedSubtree) -> Result<String, TreeError> {
+        if let Some(existing) = removed
+            .nodes
             .iter()
-            .filter_map(|id| self.nodes.get(id))
-            .collect()
+            .find(|n| self.nodes.contains_key(&n.id))
+        {
+            return Err(TreeError::IdExists(existing.id.clone()));
+        }
+        let mut nodes = removed.nodes.into_iter();
+        let root = nodes.next().ok_or(TreeError::NotFound)?;
+        let root_id = root.id.clone();
+        let siblings = if root.is_root() {
+            &mut self.root_ids
+        } else if self.nodes.contains_key(&root.parent_id) {
+            self.children_map.entry(root.parent_id.clone()).or_default()
+        } else {
+            return Err(TreeError::NotFound);
+        };
+        siblings.insert(removed.position.min(siblings.len()), root_id.clone());
+        self.nodes.insert(root_id.clone(), root);
+        // 后代按原顺序追加，兄弟之间的相对顺序保持不变
+        for node in nodes {
+            self.insert_node(node);
+        }
+        self.dirty = true;
+        Ok(root_id)
     }
 
-    /// 生成展开的节点列表（用于TUI显示）
-    pub fn flatten_for_display(&self) -> Vec<(usize, &FocusNode)> {
+    /// 标记节点失败并级联删除所有子节点
+    pub fn fail_node(&mut self, node_id: &str) -> Vec<String> {
+        self.dirty = true;
+        // 标记为失败
+        if let Some(node) = self.nodes.get_mut(node_id) {
+            node.set_status(NodeStatus::Failed);
+            node.touch();
+            node.append_log(LogEvent::Failed);
+        }
+        // 删除所有子节点，失败节点本身成为叶子
+        let deleted = self.get_all_descendants(node_id);
+        for id in &deleted {
+            self.nodes.remove(id);
+            self.children_map.remove(id);
+        }
+        self.children_map.remove(node_id);
+
+        // 返回被删除的子节点
+        deleted
+    }
+
+    /// 将超过 `days` 天未更新的活跃叶子节点标记为失败，返回受影响的节点 ID（按显示顺序）
+    pub fn auto_fail_stale(&mut self, days: u32) -> Vec<String> {
+        self.auto_fail_stale_at(days, Local::now())
+    }
+
+    fn auto_fail_stale_at(&mut self, days: u32, now: DateTime<Local>) -> Vec<String> {
+        let limit = chrono::Duration::days(days.into());
+        let stale: Vec<String> = self
+            .flatten_for_display()
+            .into_iter()
+            .map(|(_, node)| node)
+            .filter(|node| node.status == NodeStatus::Active && self.is_leaf(&node.id))
+            .filter(|node| now - node.updated_at.unwrap_or(node.created_at) > limit)
+            .map(|node| node.id.clone())
+            .collect();
+        for id in &stale {
+            if let Some(node) = self.nodes.get_mut(id) {
+                node.set_status(NodeStatus::Failed);
+                node.touch();
+                node.append_log(LogEvent::AutoFailed { days });
+            }
+        }
+        if !stale.is_empty() {
+            self.dirty = true;
+        }
+        stale
+    }
+
+    /// 先在日志中记录失败原因（为空时不记录），再标记失败
+    pub fn fail_node_with_reason(&mut self, node_id: &str, reason: &str) -> Vec<String> {
+        let reason = reason.trim();
+        if !reason.is_empty()
+            && let Some(node) = self.nodes.get_mut(node_id)
+        {
+            node.append_log(LogEvent::FailReason {
+                reason: reason.to_string(),
+            });
+        }
+        self.fail_node(node_id)
+    }
+
+    pub fn recover_node(&mut self, node_id: &str) {
+        if let Some(node) = self
+            .nodes
+            .get_mut(node_id)
+            .filter(|n| n.status == NodeStatus::Failed)
+        {
+            node.set_status(NodeStatus::Active);
+            node.touch();
+            node.append_log(LogEvent::Recovered);
+            self.dirty = true;
+        }
+    }
+
+    /// 将节点切换到 `next_status`，返回切换后的状态（失败节点返回 None）
+    pub fn cycle_status(&mut self, node_id: &str) -> Option<NodeStatus> {
+        let node = self.nodes.get_mut(node_id)?;
+        let next = node.next_status()?;
+        if node.status == NodeStatus::Completed {
+            node.archived = false;
+        }
+        node.append_log(LogEvent::StatusChanged {
+            from: node.status.clone(),
+            to: next.clone(),
+        });
+        node.set_status(next.clone());
+        node.touch();
+        self.dirty = true;
+        Some(next)
+    }
+
+    /// 切换节点的完成状态（活跃/暂停 -> 已完成 -> 活跃），返回切换后的状态
+    pub fn toggle_complete(&mut self, node_id: &str) -> Option<NodeStatus> {
+        let node = self.nodes.get_mut(node_id)?;
+        match node.status {
+            NodeStatus::Active | NodeStatus::Paused => {
+                node.set_status(NodeStatus::Completed);
+                node.append_log(LogEvent::Completed);
+            }
+            NodeStatus::Completed => {
+                node.set_status(NodeStatus::Active);
+                node.archived = false;
+                node.append_log(LogEvent::Uncompleted);
+            }
+            NodeStatus::Failed => return None,
+        }
+        node.touch();
+        self.dirty = true;
+        Some(node.status.clone())
+    }
+
+    /// 像复选框一样在活跃与已完成之间切换叶子节点，返回切换后的状态
+    ///
+    /// 有子节点或处于其他状态的节点不变，返回 None
+    pub fn toggle_leaf_done(&mut self, node_id: &str) -> Option<NodeStatus> {
+        let status = &self.nodes.get(node_id)?.status;
+        if !self.is_leaf(node_id) || !matches!(status, NodeStatus::Active | NodeStatus::Completed) {
+            return None;
+        }
+        self.toggle_complete(node_id)
+    }
+
+    /// 归档所有未归档的已完成节点（其子树随之隐藏），返回归档数量
+    pub fn archive_completed(&mut self) -> usize {
+        let mut count = 0;
+        for node in self.nodes.values_mut() {
+            if node.status == NodeStatus::Completed && !node.archived {
+                node.archived = true;
+                count += 1;
+            }
+        }
+        if count > 0 {
+            self.dirty = true;
+        }
+        count
+    }
+
+    /// `since` 当天及之后完成的节点（按显示顺序）
+    pub fn completed_since(&self, since: NaiveDate) -> Vec<&FocusNode> {
+        self.flatten_for_display()
+            .into_iter()
+            .map(|(_, node)| node)
+            .filter(|node| node.status == NodeStatus::Completed)
+            .filter(|node| node.completed_at.is_some_and(|at| at.date_naive() >= since))
+            .collect()
+    }
+
+    /// 删除所有失败节点及其子树，返回删除的节点数
+    pub fn purge_failed(&mut self) -> usize {
+        let failed: Vec<String> = self
+            .nodes
+            .values()
+            .filter(|node| node.status == NodeStatus::Failed)
+            .map(|node| node.id.clone())
+            .collect();
+        let mut count = 0;
+        for id in failed {
+            // 可能已随其他失败祖先一起删除
+            if self.nodes.contains_key(&id) {
+                count += self.delete_node(&id).len();
+            }
+        }
+        count
+    }
+
+    /// 计算 `today` 需要提醒的事件（按显示顺序，不含已归档节点）
+    ///
+    /// 里程碑在达到后首次计算时提醒（即使当天没有启动），提醒过的由 `mark_milestones_notified` 记录
+    #[cfg_attr(not(feature = "notifications"), allow(dead_code))]
+    pub fn notifications(&self, today: NaiveDate) -> Vec<Notification> {
+        let mut result = Vec::new();
+        for (_, node) in self.flatten_filtered(&DisplayFilter::default()) {
+            if let Some(days) = node.pending_milestone(today) {
+                result.push(Notification::Milestone {
+                    title: node.title.clone(),
+                    days,
+                });
+            }
+            if let Some(due) = node.due.filter(|_| node.is_overdue(today)) {
+                result.push(Notification::Overdue {
+                    title: node.title.clone(),
+                    due,
+                });
+            }
+        }
+        result
+    }
+
+    /// 记录 `notifications(today)` 中的里程碑已提醒，之后不再重复提醒，返回记录的节点数
+    #[cfg_attr(not(feature = "notifications"), allow(dead_code))]
+    pub fn mark_milestones_notified(&mut self, today: NaiveDate) -> usize {
+        let pending: Vec<(String, i64)> = self
+            .flatten_filtered(&DisplayFilter::default())
+            .into_iter()
+            .filter_map(|(_, node)| Some((node.id.clone(), node.pending_milestone(today)?)))
+            .collect();
+        for (id, milestone) in &pending {
+            if let Some(node) = self.nodes.get_mut(id) {
+                node.notified_milestone = Some(*milestone);
+            }
+        }
+        if !pending.is_empty() {
+            self.dirty = true;
+        }
+        pending.len()
+    }
+
+    /// 一次遍历统计各状态的节点数量
+    pub fn count_by_status(&self) -> StatusCounts {
+        StatusCounts::tally(self.nodes.values())
+    }
+
+    /// 节点及其所有子孙节点中各状态的数量，节点不存在时全为 0
+    pub fn count_subtree_by_status(&self, node_id: &str) -> StatusCounts {
+        if !self.nodes.contains_key(node_id) {
+            return StatusCounts::default();
+        }
+        let ids = std::iter::once(node_id.to_string()).chain(self.get_all_descendants(node_id));
+        StatusCounts::tally(ids.filter_map(|id| self.nodes.get(&id)))
+    }
+
+    /// 是否为叶子节点（没有子节点）
+    pub fn is_leaf(&self, node_id: &str) -> bool {
+        self.children_map
+            .get(node_id)
+            .is_none_or(|children| children.is_empty())
+    }
+
+    /// 获取直接子节点
+    #[allow(dead_code)]
+    pub fn get_children(&self, node_id: &str) -> Vec<&FocusNode> {
+        self.children_map
+            .get(node_id)
+            .map(|ids| ids.iter().filter_map(|id| self.nodes.get(id)).collect())
+            .unwrap_or_default()
+    }
+
+    /// 获取根节点
+    pub fn get_roots(&self) -> Vec<&FocusNode> {
+        self.root_ids
+            .iter()
+            .filter_map(|id| self.nodes.get(id))
+            .collect()
+    }
+
+    /// 按持续天数从多到少排列的根节点，天数相同时保持原有顺序
+    pub fn roots_by_staleness(&self) -> Vec<&FocusNode> {
+        let mut roots = self.get_roots();
+        roots.sort_by_key(|node| std::cmp::Reverse(node.days_active()));
+        roots
+    }
+
+    /// 生成展开的节点列表（包含全部可达节点）
+    pub fn flatten_for_display(&self) -> Vec<(usize, &FocusNode)> {
+        self.flatten_filtered(&DisplayFilter {
+            show_archived: true,
+            ..DisplayFilter::default()
+        })
+    }
+
+    /// 按过滤选项生成展开的节点列表（用于TUI显示），被隐藏节点的子树一并隐藏
+    pub fn flatten_filtered(&self, filter: &DisplayFilter) -> Vec<(usize, &FocusNode)> {
         let mut result = Vec::new();
 
         fn traverse<'a>(
             tree: &'a FocusTree,
+            filter: &DisplayFilter,
             node_id: &str,
             depth: usize,
             result: &mut Vec<(usize, &'a FocusNode)>,
         ) {
             if let Some(node) = tree.nodes.get(node_id) {
+                if node.archived && !filter.show_archived {
+                    return;
+                }
                 result.push((depth, node));
+                if filter.roots_only || filter.collapsed.contains(node_id) {
+                    return;
+                }
                 if let Some(children) = tree.children_map.get(node_id) {
+                    let mut children: Vec<&String> = children.iter().collect();
+                    if filter.sort_by_status {
+                        // 稳定排序，同状态的节点保持手动排列的顺序
+                        children
+                            .sort_by_key(|id| tree.nodes.get(*id).map(|n| n.status.sort_rank()));
+                    }
                     for child_id in children {
-                        traverse(tree, child_id, depth + 1, result);
+                        traverse(tree, filter, child_id, depth + 1, result);
                     }
                 }
             }
         }
 
         for root_id in &self.root_ids {
-            traverse(self, root_id, 0, &mut result);
+            traverse(self, filter, root_id, 0, &mut result);
         }
 
         result
     }
 }
 
+impl FocusTree {
+    /// 校验树结构（只读，不做任何修复）
+    pub fn validate(&self) -> Vec<TreeIssue> {
+        let mut issues = Vec::new();
+
+        let mut ids: Vec<&String> = self.nodes.keys().collect();
+        ids.sort();
+
+        // 孤立节点与循环引用
+        for id in &ids {
+            let node = &self.nodes[*id];
+            if !node.is_root() && !self.nodes.contains_key(&node.parent_id) {
+                issues.push(TreeIssue::Orphan {
+                    id: node.id.clone(),
+                    parent_id: node.parent_id.clone(),
+                });
+            } else if self.in_cycle(id) {
+                issues.push(TreeIssue::Cycle {
+                    id: node.id.clone(),
+                });
+            }
+        }
+
+        // 悬空引用
+        for child_id in &self.root_ids {
+            if !self.nodes.contains_key(child_id) {
+                issues.push(TreeIssue::DanglingChild {
+                    parent_id: String::new(),
+                    child_id: child_id.clone(),
+                });
+            }
+        }
+        let mut parents: Vec<&String> = self.children_map.keys().collect();
+        parents.sort();
+        for parent_id in parents {
+            for child_id in &self.children_map[parent_id] {
+                if !self.nodes.contains_key(child_id) {
+                    issues.push(TreeIssue::DanglingChild {
+                        parent_id: parent_id.clone(),
+                        child_id: child_id.clone(),
+                    });
+                }
+            }
+            if self.children_map[parent_id].is_empty() {
+                issues.push(TreeIssue::EmptyChildren {
+                    parent_id: parent_id.clone(),
+                });
+            }
+        }
+
+        issues
+    }
+
+    /// 校验并修复树结构，返回修复前发现的问题
+    ///
+    /// 悬空引用与空列表直接移除；孤立节点与环中的节点提升为根节点（每个环只断开一处）
+    pub fn validate_and_repair(&mut self) -> Vec<TreeIssue> {
+        let issues = self.validate();
+        if issues.is_empty() {
+            return issues;
+        }
+
+        let nodes = &self.nodes;
+        self.root_ids.retain(|id| nodes.contains_key(id));
+        for children in self.children_map.values_mut() {
+            children.retain(|id| nodes.contains_key(id));
+        }
+
+        for issue in &issues {
+            let id = match issue {
+                TreeIssue::Orphan { id, .. } => id,
+                // 同一个环中先处理的节点已断开环，其余节点不再需要提升
+                TreeIssue::Cycle { id } if self.in_cycle(id) => id,
+                _ => continue,
+            };
+            let parent_id = std::mem::take(&mut self.nodes.get_mut(id).unwrap().parent_id);
+            self.detach_from_parent(id, &parent_id);
+            self.root_ids.push(id.clone());
+        }
+
+        self.children_map.retain(|_, children| !children.is_empty());
+        self.dirty = true;
+        issues
+    }
+
+    /// 沿 parent_id 向上遍历，判断节点是否处于环中
+    fn in_cycle(&self, node_id: &str) -> bool {
+        let mut visited = HashSet::new();
+        let mut current = node_id;
+        while let Some(node) = self.nodes.get(current) {
+            if node.is_root() || !visited.insert(current) {
+                return false;
+            }
+            if node.parent_id == node_id {
+                return true;
+            }
+            current = &node.parent_id;
+        }
+        false
+    }
+}
+
 impl Default for FocusTree {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// 以嵌套闭包的方式构建树，便于编写测试数据或脚本
+///
+/// ```ignore
+/// let tree = TreeBuilder::new()
+///     .node("健康", |b| b.leaf("早起").leaf("运动"))
+///     .leaf("阅读")
+///     .build();
+/// ```
+#[derive(Debug, Default)]
+pub struct TreeBuilder {
+    tree: FocusTree,
+    parent: Option<String>,
+}
+
+impl TreeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 在当前层级添加一个没有子节点的节点
+    pub fn leaf(self, title: &str) -> Self {
+        self.node(title, |b| b)
+    }
+
+    /// 在当前层级添加节点，并在闭包中为其添加子节点
+    pub fn node(mut self, title: &str, children: impl FnOnce(Self) -> Self) -> Self {
+        let id = self
+            .tree
+            .add_node(title.to_string(), String::new(), self.parent.clone())
+            .expect("构建时不限制层级");
+        let outer = self.parent.replace(id);
+        let mut builder = children(self);
+        builder.parent = outer;
+        builder
+    }
+
+    pub fn build(self) -> FocusTree {
+        self.tree
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::i18n::Strings;
 
     #[test]
     fn test_add_node() {
         let mut tree = FocusTree::new();
-        let root_id = tree.add_node("Root".to_string(), "Root content".to_string(), None);
-        let _child_id = tree.add_node(
-            "Child".to_string(),
-            "Child content".to_string(),
-            Some(root_id.clone()),
-        );
+        let root_id = tree
+            .add_node("Root".to_string(), "Root content".to_string(), None)
+            .unwrap();
+        let _child_id = tree
+            .add_node(
+                "Child".to_string(),
+                "Child content".to_string(),
+                Some(root_id.clone()),
+            )
+            .unwrap();
 
         assert_eq!(tree.nodes.len(), 2);
         assert_eq!(tree.root_ids.len(), 1);
@@ -298,13 +1472,19 @@ mod tests {
     #[test]
     fn test_delete_cascade() {
         let mut tree = FocusTree::new();
-        let root_id = tree.add_node("Root".to_string(), "".to_string(), None);
-        let child_id = tree.add_node("Child".to_string(), "".to_string(), Some(root_id.clone()));
-        let _grandchild_id = tree.add_node(
-            "Grandchild".to_string(),
-            "".to_string(),
-            Some(child_id.clone()),
-        );
+        let root_id = tree
+            .add_node("Root".to_string(), "".to_string(), None)
+            .unwrap();
+        let child_id = tree
+            .add_node("Child".to_string(), "".to_string(), Some(root_id.clone()))
+            .unwrap();
+        let _grandchild_id = tree
+            .add_node(
+                "Grandchild".to_string(),
+                "".to_string(),
+                Some(child_id.clone()),
+            )
+            .unwrap();
 
         assert_eq!(tree.nodes.len(), 3);
 
@@ -314,6 +1494,677 @@ mod tests {
         assert!(tree.nodes.contains_key(&root_id));
     }
 
+    #[test]
+    fn test_delete_node_reparent() {
+        let mut tree = TreeBuilder::new()
+            .node("root", |b| {
+                b.leaf("before")
+                    .node("middle", |b| b.leaf("x").node("y", |b| b.leaf("deep")))
+                    .leaf("after")
+            })
+            .build();
+        let root = tree.root_ids[0].clone();
+        let middle = tree.get_children(&root)[1].id.clone();
+        let y = tree.get_children(&middle)[1].id.clone();
+
+        let moved = tree.delete_node_reparent(&middle);
+        assert_eq!(moved.len(), 2);
+        assert!(!tree.nodes.contains_key(&middle));
+        let titles: Vec<&str> = tree
+            .get_children(&root)
+            .iter()
+            .map(|n| n.title.as_str())
+            .collect();
+        assert_eq!(titles, ["before", "x", "y", "after"]);
+        assert_eq!(tree.nodes[&y].parent_id, root);
+        assert_eq!(tree.get_children(&y)[0].title, "deep");
+        assert!(tree.validate().is_empty());
+
+        // 删除根节点时子节点成为根节点
+        let moved = tree.delete_node_reparent(&root);
+        assert_eq!(moved.len(), 4);
+        assert_eq!(tree.root_ids, moved);
+        assert!(tree.nodes.values().filter(|n| n.is_root()).count() == 4);
+        assert!(tree.validate().is_empty());
+        assert!(tree.delete_node_reparent("missing").is_empty());
+    }
+
+    #[test]
+    fn test_is_descendant() {
+        let tree = TreeBuilder::new()
+            .node("a", |b| b.node("b", |b| b.leaf("c")))
+            .leaf("other")
+            .build();
+        let a = tree.root_ids[0].clone();
+        let other = tree.root_ids[1].clone();
+        let b = tree.get_children(&a)[0].id.clone();
+        let c = tree.get_children(&b)[0].id.clone();
+
+        assert!(tree.is_descendant(&a, &b));
+        assert!(tree.is_descendant(&a, &c));
+        assert!(!tree.is_descendant(&c, &a));
+        assert!(!tree.is_descendant(&a, &other));
+        assert!(!tree.is_descendant(&other, &c));
+        assert!(!tree.is_descendant(&a, &a));
+        assert!(!tree.is_descendant(&a, "missing"));
+    }
+
+    #[test]
+    fn test_path_ids() {
+        let tree = TreeBuilder::new()
+            .node("a", |b| b.node("b", |b| b.leaf("c")))
+            .build();
+        let a = tree.root_ids[0].clone();
+        let b = tree.get_children(&a)[0].id.clone();
+        let c = tree.get_children(&b)[0].id.clone();
+
+        assert_eq!(tree.path_ids(&c), [a.clone(), b.clone(), c.clone()]);
+        assert_eq!(tree.path_ids(&a), [a.as_str()]);
+        assert!(tree.path_ids("missing").is_empty());
+        assert_eq!(tree.root_of(&c), Some(a));
+    }
+
+    #[test]
+    fn test_add_link() {
+        let mut tree = TreeBuilder::new()
+            .node("a", |b| b.leaf("x"))
+            .leaf("b")
+            .build();
+        let a = tree.root_ids[0].clone();
+        let b = tree.root_ids[1].clone();
+        let x = tree.get_children(&a)[0].id.clone();
+        tree.dirty = false;
+
+        assert_eq!(tree.add_link(&x, &b), Ok(true));
+        assert_eq!(tree.add_link(&x, &a), Ok(true));
+        assert_eq!(tree.add_link(&x, &b), Ok(false));
+        assert!(tree.dirty);
+        let titles: Vec<&str> = tree
+            .linked_nodes(&x)
+            .iter()
+            .map(|n| n.title.as_str())
+            .collect();
+        assert_eq!(titles, ["b", "a"]);
+        // 关联是单向的
+        assert!(tree.linked_nodes(&b).is_empty());
+
+        assert_eq!(tree.add_link(&x, "missing"), Err(TreeError::NotFound));
+        assert_eq!(tree.add_link("missing", &x), Err(TreeError::NotFound));
+    }
+
+    #[test]
+    fn test_add_link_to_self_rejected() {
+        let mut tree = TreeBuilder::new().leaf("a").build();
+        let a = tree.root_ids[0].clone();
+        assert_eq!(tree.add_link(&a, &a), Err(TreeError::LinkToSelf));
+        assert!(tree.nodes[&a].links.is_empty());
+    }
+
+    #[test]
+    fn test_dangling_links_pruned_on_load() {
+        let mut tree = TreeBuilder::new().leaf("a").leaf("b").build();
+        let a = tree.root_ids[0].clone();
+        let b = tree.root_ids[1].clone();
+        tree.add_link(&a, &b).unwrap();
+        let mut data = tree.to_data();
+        let node = data.nodes.iter_mut().find(|n| n.id == a).unwrap();
+        node.links.push("gone".to_string());
+        node.links.push(a.clone());
+
+        let loaded = FocusTree::from_data(data);
+        assert_eq!(loaded.nodes[&a].links, [b.as_str()]);
+        assert!(loaded.dirty);
+
+        let clean = FocusTree::from_data(loaded.to_data());
+        assert!(!clean.dirty);
+    }
+
+    #[test]
+    fn test_sibling_position() {
+        let tree = TreeBuilder::new()
+            .node("a", |b| b.leaf("x").leaf("y").leaf("z"))
+            .node("b", |b| b.leaf("only"))
+            .build();
+        let a = tree.root_ids[0].clone();
+        let b = tree.root_ids[1].clone();
+        let children: Vec<String> = tree.get_children(&a).iter().map(|n| n.id.clone()).collect();
+        let only = tree.get_children(&b)[0].id.clone();
+
+        assert_eq!(tree.sibling_position(&children[1]), Some((2, 3)));
+        assert_eq!(tree.sibling_position(&children[0]), Some((1, 3)));
+        assert_eq!(tree.sibling_position(&children[2]), Some((3, 3)));
+        assert_eq!(tree.sibling_position(&only), Some((1, 1)));
+        assert_eq!(tree.sibling_position(&b), Some((2, 2)));
+        assert_eq!(tree.sibling_position("missing"), None);
+    }
+
+    #[test]
+    fn test_count_subtree_by_status() {
+        let mut tree = TreeBuilder::new()
+            .node("a", |b| b.leaf("x").node("y", |b| b.leaf("z")))
+            .leaf("b")
+            .build();
+        let a = tree.root_ids[0].clone();
+        let x = tree.get_children(&a)[0].id.clone();
+        tree.toggle_complete(&x);
+
+        let counts = tree.count_subtree_by_status(&a);
+        assert_eq!(counts.active, 3);
+        assert_eq!(counts.completed, 1);
+        assert_eq!(counts.total(), tree.subtree_size(&a));
+        assert_eq!(tree.count_subtree_by_status("missing").total(), 0);
+    }
+
+    #[test]
+    fn test_merge_roots_by_title() {
+        let mut tree = TreeBuilder::new()
+            .node("健康", |b| b.leaf("跑步"))
+            .node("学习", |b| b.leaf("读书"))
+            .node("健康", |b| {
+                b.leaf("早睡").node("饮食", |b| b.leaf("少糖"))
+            })
+            .leaf("健康")
+            .build();
+        tree.dirty = false;
+
+        assert_eq!(tree.merge_roots_by_title(), (2, 0));
+        assert!(tree.dirty);
+        let roots: Vec<&str> = tree.get_roots().iter().map(|n| n.title.as_str()).collect();
+        assert_eq!(roots, ["健康", "学习"]);
+        let health = tree.root_ids[0].clone();
+        let children: Vec<&str> = tree
+            .get_children(&health)
+            .iter()
+            .map(|n| n.title.as_str())
+            .collect();
+        assert_eq!(children, ["跑步", "早睡", "饮食"]);
+        assert_eq!(tree.nodes.len(), 7);
+        assert!(tree.validate().is_empty());
+
+        assert_eq!(tree.merge_roots_by_title(), (0, 0));
+    }
+
+    #[test]
+    fn test_merge_roots_keeps_duplicates_with_data() {
+        let mut tree = TreeBuilder::new()
+            .node("健康", |b| b.leaf("跑步"))
+            .node("健康", |b| b.leaf("早睡"))
+            .node("健康", |b| b.leaf("饮食"))
+            .build();
+        let with_content = tree.root_ids[1].clone();
+        let completed = tree.root_ids[2].clone();
+        tree.nodes.get_mut(&with_content).unwrap().content = "每天记录".to_string();
+        tree.toggle_complete(&completed);
+
+        assert_eq!(tree.merge_roots_by_title(), (0, 2));
+        assert_eq!(tree.root_ids.len(), 3);
+        assert_eq!(tree.nodes[&with_content].content, "每天记录");
+        assert_eq!(tree.nodes[&completed].status, NodeStatus::Completed);
+        assert!(tree.nodes[&completed].completed_at.is_some());
+        assert_eq!(tree.get_children(&with_content)[0].title, "早睡");
+        assert!(tree.validate().is_empty());
+    }
+
+    #[test]
+    fn test_move_node() {
+        let mut tree = FocusTree::new();
+        let a = tree
+            .add_node("A".to_string(), "".to_string(), None)
+            .unwrap();
+        let b = tree
+            .add_node("B".to_string(), "".to_string(), None)
+            .unwrap();
+        let child = tree
+            .add_node("Child".to_string(), "".to_string(), Some(a.clone()))
+            .unwrap();
+
+        assert_eq!(tree.move_node(&child, Some(&b)), Ok(true));
+        assert!(tree.get_children(&a).is_empty());
+        assert_eq!(tree.get_children(&b)[0].id, child);
+
+        assert_eq!(tree.move_node(&b, Some(&b)), Err(TreeError::MoveIntoSelf));
+        assert_eq!(
+            tree.move_node(&b, Some(&child)),
+            Err(TreeError::MoveIntoDescendant)
+        );
+
+        assert_eq!(tree.move_node(&child, None), Ok(true));
+        assert_eq!(tree.root_ids, vec![a, b, child]);
+    }
+
+    #[test]
+    fn test_move_node_to_current_parent_is_noop() {
+        let mut tree = FocusTree::new();
+        let root = tree
+            .add_node("Root".to_string(), "".to_string(), None)
+            .unwrap();
+        let first = tree
+            .add_node("First".to_string(), "".to_string(), Some(root.clone()))
+            .unwrap();
+        let _second = tree
+            .add_node("Second".to_string(), "".to_string(), Some(root.clone()))
+            .unwrap();
+        tree.dirty = false;
+
+        let before = tree.children_map.clone();
+        assert_eq!(tree.move_node(&first, Some(&root)), Ok(false));
+        assert_eq!(tree.children_map, before);
+        assert_eq!(tree.children_map[&root].len(), 2);
+        assert!(!tree.dirty);
+
+        assert_eq!(tree.move_node(&root, None), Ok(false));
+        assert_eq!(tree.root_ids, vec![root]);
+    }
+
+    #[test]
+    fn test_to_data_is_deterministic() {
+        let mut tree = FocusTree::new();
+        for i in 0..10 {
+            let root = tree
+                .add_node(format!("Root {}", i), "".to_string(), None)
+                .unwrap();
+            for j in 0..3 {
+                tree.add_node(
+                    format!("Child {}-{}", i, j),
+                    "".to_string(),
+                    Some(root.clone()),
+                )
+                .unwrap();
+            }
+        }
+        // 孤立节点也应稳定输出
+        let mut orphan = FocusNode::new("Orphan".to_string(), "".to_string(), None);
+        orphan.parent_id = "missing".to_string();
+        tree.insert_node(orphan);
+
+        // 重新加载会得到迭代顺序不同的 HashMap
+        let reloaded = FocusTree::from_data(tree.to_data());
+
+        let first = tree.to_data();
+        let mut second = reloaded.to_data();
+        second.meta.last_modified = first.meta.last_modified;
+        assert_eq!(
+            toml::to_string_pretty(&first).unwrap(),
+            toml::to_string_pretty(&second).unwrap()
+        );
+        assert_eq!(first.nodes.len(), 41);
+    }
+
+    /// 校验每个父节点的子列表顺序与 flatten_for_display 中的出现顺序一致
+    fn assert_order_matches_display(tree: &FocusTree) {
+        let display = tree.flatten_for_display();
+        let displayed_roots: Vec<&str> = display
+            .iter()
+            .filter(|(depth, _)| *depth == 0)
+            .map(|(_, n)| n.id.as_str())
+            .collect();
+        assert_eq!(displayed_roots, tree.root_ids);
+
+        for (parent_id, children) in &tree.children_map {
+            let displayed: Vec<&str> = display
+                .iter()
+                .filter(|(_, n)| &n.parent_id == parent_id)
+                .map(|(_, n)| n.id.as_str())
+                .collect();
+            assert_eq!(&displayed, children);
+        }
+    }
+
+    #[test]
+    fn test_child_order_is_stable() {
+        let mut tree = FocusTree::new();
+        let a = tree
+            .add_node("A".to_string(), "".to_string(), None)
+            .unwrap();
+        let b = tree
+            .add_node("B".to_string(), "".to_string(), None)
+            .unwrap();
+        let a1 = tree
+            .add_node("A1".to_string(), "".to_string(), Some(a.clone()))
+            .unwrap();
+        let a2 = tree
+            .add_node("A2".to_string(), "".to_string(), Some(a.clone()))
+            .unwrap();
+        let a3 = tree
+            .add_node("A3".to_string(), "".to_string(), Some(a.clone()))
+            .unwrap();
+        let b1 = tree
+            .add_node("B1".to_string(), "".to_string(), Some(b.clone()))
+            .unwrap();
+        assert_order_matches_display(&tree);
+
+        tree.move_node(&a2, Some(&b)).unwrap();
+        tree.move_node(&a1, Some(&b)).unwrap();
+        tree.move_node(&b1, None).unwrap();
+        assert_eq!(tree.children_map[&a], vec![a3.clone()]);
+        assert_eq!(tree.children_map[&b], vec![a2.clone(), a1.clone()]);
+        assert_eq!(tree.root_ids, vec![a.clone(), b.clone(), b1.clone()]);
+        assert_order_matches_display(&tree);
+
+        tree.delete_node(&a2);
+        assert_eq!(tree.children_map[&b], vec![a1.clone()]);
+        assert_order_matches_display(&tree);
+
+        // 保存再加载后顺序不变
+        let reloaded = FocusTree::from_data(tree.to_data());
+        assert_eq!(reloaded.root_ids, tree.root_ids);
+        assert_eq!(reloaded.children_map[&a], tree.children_map[&a]);
+        assert_eq!(reloaded.children_map[&b], tree.children_map[&b]);
+        assert_order_matches_display(&reloaded);
+    }
+
+    #[test]
+    fn test_archive_completed() {
+        let mut tree = FocusTree::new();
+        let done = tree
+            .add_node("Done".to_string(), "".to_string(), None)
+            .unwrap();
+        let _done_child = tree
+            .add_node("Done child".to_string(), "".to_string(), Some(done.clone()))
+            .unwrap();
+        let active = tree
+            .add_node("Active".to_string(), "".to_string(), None)
+            .unwrap();
+        let done_leaf = tree
+            .add_node(
+                "Done leaf".to_string(),
+                "".to_string(),
+                Some(active.clone()),
+            )
+            .unwrap();
+        let also_done = tree
+            .add_node("Also done".to_string(), "".to_string(), None)
+            .unwrap();
+        for id in [&done, &done_leaf, &also_done] {
+            assert_eq!(tree.toggle_complete(id), Some(NodeStatus::Completed));
+        }
+
+        assert_eq!(tree.archive_completed(), 3);
+        assert_eq!(tree.archive_completed(), 0);
+
+        let visible: Vec<&str> = tree
+            .flatten_filtered(&DisplayFilter::default())
+            .iter()
+            .map(|(_, n)| n.title.as_str())
+            .collect();
+        assert_eq!(visible, vec!["Active"]);
+        assert_eq!(tree.flatten_for_display().len(), 5);
+
+        // 取消完成会同时取消归档
+        tree.toggle_complete(&done_leaf);
+        assert_eq!(tree.flatten_filtered(&DisplayFilter::default()).len(), 2);
+    }
+
+    #[test]
+    fn test_is_leaf() {
+        let mut tree = FocusTree::new();
+        let root = tree
+            .add_node("Root".to_string(), "".to_string(), None)
+            .unwrap();
+        let child = tree
+            .add_node("Child".to_string(), "".to_string(), Some(root.clone()))
+            .unwrap();
+
+        assert!(tree.is_leaf(&child));
+        assert!(!tree.is_leaf(&root));
+
+        // 即使残留空列表也视为叶子
+        tree.children_map.insert(child.clone(), Vec::new());
+        assert!(tree.is_leaf(&child));
+    }
+
+    fn assert_no_empty_children(tree: &FocusTree) {
+        assert!(
+            tree.children_map
+                .values()
+                .all(|children| !children.is_empty())
+        );
+    }
+
+    #[test]
+    fn test_no_empty_children_entries() {
+        let mut tree = FocusTree::new();
+        let a = tree
+            .add_node("A".to_string(), "".to_string(), None)
+            .unwrap();
+        let b = tree
+            .add_node("B".to_string(), "".to_string(), None)
+            .unwrap();
+        let a1 = tree
+            .add_node("A1".to_string(), "".to_string(), Some(a.clone()))
+            .unwrap();
+        let a2 = tree
+            .add_node("A2".to_string(), "".to_string(), Some(a.clone()))
+            .unwrap();
+        let b1 = tree
+            .add_node("B1".to_string(), "".to_string(), Some(b.clone()))
+            .unwrap();
+        let _b1x = tree
+            .add_node("B1x".to_string(), "".to_string(), Some(b1.clone()))
+            .unwrap();
+
+        tree.delete_node(&a1);
+        assert!(tree.children_map.contains_key(&a));
+        tree.delete_node(&a2);
+        assert!(!tree.children_map.contains_key(&a));
+        assert!(tree.is_leaf(&a));
+        assert_no_empty_children(&tree);
+
+        tree.move_node(&b1, Some(&a)).unwrap();
+        assert!(!tree.children_map.contains_key(&b));
+        assert_no_empty_children(&tree);
+
+        let deleted = tree.fail_node(&b1);
+        assert_eq!(deleted.len(), 1);
+        assert!(!tree.children_map.contains_key(&b1));
+        assert_no_empty_children(&tree);
+        assert!(tree.validate().is_empty());
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut tree = FocusTree::new();
+        tree.max_depth = Some(2);
+        let root = tree
+            .add_node("O".to_string(), "".to_string(), None)
+            .unwrap();
+        let kr = tree
+            .add_node("KR".to_string(), "".to_string(), Some(root.clone()))
+            .unwrap();
+        assert_eq!(tree.level(&kr), 2);
+
+        // 已达上限，不能再添加子节点
+        assert_eq!(
+            tree.add_node("Task".to_string(), "".to_string(), Some(kr.clone())),
+            Err(TreeError::DepthExceeded(2))
+        );
+        assert_eq!(tree.nodes.len(), 2);
+
+        // 移动子树时同样检查整棵子树的高度
+        let other = tree
+            .add_node("O2".to_string(), "".to_string(), None)
+            .unwrap();
+        assert_eq!(
+            tree.move_node(&root, Some(&other)),
+            Err(TreeError::DepthExceeded(2))
+        );
+        assert_eq!(tree.move_node(&kr, Some(&other)), Ok(true));
+
+        tree.max_depth = None;
+        assert!(
+            tree.add_node("Task".to_string(), "".to_string(), Some(kr))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_replace_text() {
+        let mut tree = FocusTree::new();
+        let a = tree
+            .add_node(
+                "每天健身".to_string(),
+                "健身前热身，健身后拉伸".to_string(),
+                None,
+            )
+            .unwrap();
+        let b = tree
+            .add_node("早起".to_string(), "".to_string(), None)
+            .unwrap();
+        tree.dirty = false;
+
+        assert_eq!(tree.find_text("健身"), vec![a.clone()]);
+        assert_eq!(tree.replace_in_titles("健身", "运动"), (1, 0));
+        assert_eq!(tree.replace_in_content("健身", "运动"), 2);
+        assert!(tree.dirty);
+        assert_eq!(tree.nodes[&a].title, "每天运动");
+        assert_eq!(tree.nodes[&a].content, "运动前热身，运动后拉伸");
+        assert!(tree.nodes[&a].updated_at.is_some());
+        assert!(tree.nodes[&b].updated_at.is_none());
+
+        tree.dirty = false;
+        assert_eq!(tree.replace_in_content("健身", "运动"), 0);
+        assert_eq!(tree.replace_in_titles("", "x"), (0, 0));
+        assert!(!tree.dirty);
+
+        // 替换后为空或只剩空白的标题保持不变
+        assert_eq!(tree.replace_in_titles("早起", " "), (0, 1));
+        assert_eq!(tree.replace_in_titles("每天", ""), (1, 0));
+        assert_eq!(tree.nodes[&b].title, "早起");
+        assert_eq!(tree.nodes[&a].title, "运动");
+    }
+
+    #[test]
+    fn test_tree_builder() {
+        let tree = TreeBuilder::new()
+            .node("健康", |b| {
+                b.node("运动", |b| b.leaf("跑步").leaf("游泳")).leaf("早起")
+            })
+            .leaf("阅读")
+            .build();
+
+        let roots: Vec<&str> = tree.get_roots().iter().map(|n| n.title.as_str()).collect();
+        assert_eq!(roots, vec!["健康", "阅读"]);
+
+        let health = &tree.get_roots()[0].id;
+        let children: Vec<&str> = tree
+            .get_children(health)
+            .iter()
+            .map(|n| n.title.as_str())
+            .collect();
+        assert_eq!(children, vec!["运动", "早起"]);
+
+        let sport = &tree.get_children(health)[0].id;
+        let grandchildren: Vec<&str> = tree
+            .get_children(sport)
+            .iter()
+            .map(|n| n.title.as_str())
+            .collect();
+        assert_eq!(grandchildren, vec!["跑步", "游泳"]);
+        assert_eq!(tree.level(&tree.get_children(sport)[1].id), 3);
+        assert!(tree.validate().is_empty());
+    }
+
+    #[test]
+    fn test_subtree_size() {
+        let tree = TreeBuilder::new()
+            .node("parent", |b| b.leaf("a").leaf("b"))
+            .node("deep", |b| b.node("l2", |b| b.node("l3", |b| b.leaf("l4"))))
+            .build();
+        let parent = &tree.root_ids[0];
+        let deep = &tree.root_ids[1];
+        let leaf = &tree.get_children(parent)[0].id;
+
+        assert_eq!(tree.subtree_size(leaf), 1);
+        assert_eq!(tree.subtree_size(parent), 3);
+        assert_eq!(tree.subtree_size(deep), 4);
+    }
+
+    #[test]
+    fn test_rolled_up_estimate() {
+        let mut tree = TreeBuilder::new()
+            .node("plan", |b| {
+                b.node("phase", |b| b.leaf("x").leaf("y")).leaf("z")
+            })
+            .build();
+        let plan = tree.root_ids[0].clone();
+        let phase = tree.get_children(&plan)[0].id.clone();
+        let z = tree.get_children(&plan)[1].id.clone();
+        let x = tree.get_children(&phase)[0].id.clone();
+        let y = tree.get_children(&phase)[1].id.clone();
+
+        tree.set_estimate(&x, 2.0);
+        tree.set_estimate(&y, 1.5);
+        tree.set_estimate(&z, 3.0);
+        tree.set_estimate(&phase, 0.5);
+
+        assert_eq!(tree.rolled_up_estimate(&x), 2.0);
+        assert_eq!(tree.rolled_up_estimate(&phase), 4.0);
+        assert_eq!(tree.rolled_up_estimate(&plan), 7.0);
+        assert_eq!(tree.rolled_up_estimate("missing"), 0.0);
+    }
+
+    #[test]
+    fn test_purge_failed() {
+        let mut tree = TreeBuilder::new()
+            .node("active", |b| b.leaf("failed child").leaf("kept child"))
+            .leaf("failed")
+            .leaf("completed")
+            .build();
+        let ids: HashMap<String, String> = tree
+            .nodes
+            .values()
+            .map(|n| (n.title.clone(), n.id.clone()))
+            .collect();
+        tree.fail_node(&ids["failed child"]);
+        tree.fail_node(&ids["failed"]);
+        tree.toggle_complete(&ids["completed"]);
+        tree.dirty = false;
+
+        assert_eq!(tree.purge_failed(), 2);
+        assert!(tree.dirty);
+        let mut titles: Vec<&str> = tree.nodes.values().map(|n| n.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["active", "completed", "kept child"]);
+        assert!(tree.validate().is_empty());
+
+        tree.dirty = false;
+        assert_eq!(tree.purge_failed(), 0);
+        assert!(!tree.dirty);
+    }
+
+    #[test]
+    fn test_prune_to_depth() {
+        let tree = TreeBuilder::new()
+            .node("a", |b| b.node("a1", |b| b.leaf("a1x")).leaf("a2"))
+            .node("b", |b| b.node("b1", |b| b.node("b1x", |b| b.leaf("b1xy"))))
+            .build();
+
+        let data = tree.prune_to_depth(2);
+        let titles: Vec<&str> = data.nodes.iter().map(|n| n.title.as_str()).collect();
+        assert_eq!(titles, vec!["a", "a1", "a2", "b", "b1"]);
+        assert!(data.nodes.iter().all(|n| tree.level(&n.id) <= 2));
+
+        // 剩余的父节点不再引用被裁掉的子节点
+        let pruned = FocusTree::from_data(data);
+        assert!(pruned.validate().is_empty());
+        for id in pruned.children_map.values().flatten() {
+            assert!(pruned.nodes.contains_key(id));
+        }
+        assert!(pruned.nodes.values().all(|n| pruned.level(&n.id) <= 2));
+
+        assert!(tree.prune_to_depth(0).nodes.is_empty());
+        assert_eq!(tree.prune_to_depth(10).nodes.len(), tree.nodes.len());
+    }
+
+    #[test]
+    fn test_status_names() {
+        let strings = Strings::default();
+        assert_eq!(strings.status(&NodeStatus::Active), "活跃");
+        assert_eq!(strings.status(&NodeStatus::Failed), "失败");
+        assert_eq!(strings.status(&NodeStatus::Completed), "已完成");
+        assert_eq!(format!("{:?}", NodeStatus::Completed), "Completed");
+    }
+
     #[test]
     fn test_days_active() {
         use chrono::Duration;
@@ -325,15 +2176,666 @@ mod tests {
         assert_eq!(node.days_active(), 5);
     }
 
+    #[test]
+    fn test_created_today() {
+        use chrono::Duration;
+
+        let mut node = FocusNode::new("Test".to_string(), "".to_string(), None);
+        assert!(node.created_today());
+
+        node.created_at = Local::now() - Duration::days(2);
+        assert!(!node.created_today());
+    }
+
+    #[test]
+    fn test_updated_at() {
+        let mut tree = FocusTree::new();
+        let id = tree
+            .add_node("task".to_string(), "".to_string(), None)
+            .unwrap();
+        assert!(tree.nodes[&id].updated_at.is_none());
+
+        tree.dirty = false;
+        tree.update_content(&id, "new content".to_string());
+        let node = &tree.nodes[&id];
+        assert_eq!(node.content, "new content");
+        assert!(node.updated_at.is_some_and(|t| t >= node.created_at));
+        assert!(tree.dirty);
+    }
+
     #[test]
     fn test_recover_node() {
         let mut tree = FocusTree::new();
-        let id = tree.add_node("task".to_string(), "".to_string(), None);
+        let id = tree
+            .add_node("task".to_string(), "".to_string(), None)
+            .unwrap();
 
         tree.fail_node(&id);
         assert_eq!(tree.nodes.get(&id).unwrap().status, NodeStatus::Failed);
 
         tree.recover_node(&id);
         assert_eq!(tree.nodes.get(&id).unwrap().status, NodeStatus::Active);
+
+        let log: Vec<String> = tree.nodes[&id]
+            .log
+            .iter()
+            .map(|e| Strings::default().log_entry(e))
+            .collect();
+        assert_eq!(log, vec!["标记为失败", "恢复为活跃"]);
+    }
+
+    fn load_fixture(toml_str: &str) -> FocusTree {
+        let data: FocusTreeData = toml::from_str(toml_str).unwrap();
+        FocusTree::from_data(data)
+    }
+
+    const FIXTURE_META: &str = r#"
+[meta]
+version = "1.0"
+last_modified = "2024-01-01T00:00:00+08:00"
+"#;
+
+    fn fixture_node(id: &str, parent_id: &str) -> String {
+        format!(
+            "[[nodes]]\nid = \"{}\"\nparent_id = \"{}\"\ntitle = \"{}\"\ncreated_at = \"2024-01-01T00:00:00+08:00\"\n",
+            id, parent_id, id
+        )
+    }
+
+    #[test]
+    fn test_migrate_from_1_0() {
+        let toml_str = [
+            FIXTURE_META.to_string(),
+            fixture_node("done", ""),
+            "status = \"completed\"\n".to_string(),
+            fixture_node("open", ""),
+        ]
+        .join("\n");
+        let mut tree = load_fixture(&toml_str);
+        assert!(tree.dirty);
+        assert_eq!(tree.unknown_version, None);
+        let done = &tree.nodes["done"];
+        assert_eq!(done.completed_at, Some(done.created_at));
+        assert_eq!(tree.nodes["open"].completed_at, None);
+
+        let path = crate::test_util::temp_path("migrate.toml");
+        crate::storage::save_tree(&mut tree, &path, &Strings::default()).unwrap();
+        let saved: FocusTreeData =
+            toml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(saved.meta.version, DATA_VERSION);
+        assert!(!FocusTree::from_data(saved).dirty);
+    }
+
+    #[test]
+    fn test_unknown_version_loads_without_migration() {
+        let toml_str = [FIXTURE_META.replace("1.0", "9.0"), fixture_node("a", "")].join("\n");
+        let tree = load_fixture(&toml_str);
+        assert_eq!(tree.unknown_version.as_deref(), Some("9.0"));
+        assert!(!tree.dirty);
+        assert_eq!(tree.nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_color_serde_round_trip() {
+        // 旧数据没有 color 字段
+        let tree = load_fixture(&[FIXTURE_META.to_string(), fixture_node("a", "")].join("\n"));
+        assert_eq!(tree.nodes["a"].color, None);
+
+        let mut tree = tree;
+        tree.set_color("a", Some("#ff8800".to_string()));
+        let toml_str = toml::to_string(&tree.to_data()).unwrap();
+        let loaded = load_fixture(&toml_str);
+        assert_eq!(loaded.nodes["a"].color.as_deref(), Some("#ff8800"));
+
+        tree.set_color("a", None);
+        let toml_str = toml::to_string(&tree.to_data()).unwrap();
+        assert!(!toml_str.contains("color"));
+        assert_eq!(load_fixture(&toml_str).nodes["a"].color, None);
+    }
+
+    #[test]
+    fn test_notifications() {
+        let toml_str = [
+            FIXTURE_META.to_string(),
+            fixture_node("week", ""),
+            fixture_node("other", ""),
+            fixture_node("late", ""),
+            fixture_node("done", ""),
+        ]
+        .join("\n");
+        let mut tree = load_fixture(&toml_str);
+        // 所有 fixture 节点创建于同一时刻，按本地日期计算
+        let created = tree.nodes["week"].created_at.date_naive();
+        let today = created + chrono::Days::new(7);
+        let due = created + chrono::Days::new(4);
+        tree.nodes.get_mut("late").unwrap().due = Some(due);
+        tree.nodes.get_mut("done").unwrap().due = Some(due);
+        tree.toggle_complete("done");
+        tree.archive_completed();
+
+        let notes = tree.notifications(today);
+        let milestones = notes
+            .iter()
+            .filter(|n| matches!(n, Notification::Milestone { days: 7, .. }))
+            .count();
+        // 已完成（已归档）节点不提醒
+        assert_eq!(milestones, 3);
+        assert!(notes.contains(&Notification::Overdue {
+            title: "late".to_string(),
+            due,
+        }));
+        assert_eq!(notes.len(), 4);
+
+        // 未达到里程碑、截止日当天均不提醒
+        assert!(tree.notifications(due).is_empty());
+
+        // 错过里程碑当天时之后补发，记录后不再重复提醒
+        let later = today + chrono::Days::new(1);
+        assert_eq!(tree.notifications(later).len(), 4);
+        tree.dirty = false;
+        assert_eq!(tree.mark_milestones_notified(later), 3);
+        assert!(tree.dirty);
+        assert_eq!(tree.nodes["week"].notified_milestone, Some(7));
+        assert!(
+            tree.notifications(later)
+                .iter()
+                .all(|n| matches!(n, Notification::Overdue { .. }))
+        );
+        assert_eq!(tree.mark_milestones_notified(later), 0);
+
+        // 跨过多个里程碑时只提醒最高的一个
+        let much_later = created + chrono::Days::new(120);
+        let milestones: Vec<i64> = tree
+            .notifications(much_later)
+            .iter()
+            .filter_map(|n| match n {
+                Notification::Milestone { days, .. } => Some(*days),
+                Notification::Overdue { .. } => None,
+            })
+            .collect();
+        assert_eq!(milestones, [100, 100, 100]);
+    }
+
+    #[test]
+    fn test_validate_clean() {
+        let toml_str = [
+            FIXTURE_META.to_string(),
+            fixture_node("a", ""),
+            fixture_node("b", "a"),
+            fixture_node("c", "b"),
+        ]
+        .join("\n");
+        let tree = load_fixture(&toml_str);
+        assert!(tree.validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate_corrupt() {
+        let toml_str = [
+            FIXTURE_META.to_string(),
+            fixture_node("root", ""),
+            fixture_node("orphan", "missing"),
+            fixture_node("x", "y"),
+            fixture_node("y", "x"),
+        ]
+        .join("\n");
+        let mut tree = load_fixture(&toml_str);
+        tree.children_map
+            .entry("root".to_string())
+            .or_default()
+            .push("ghost".to_string());
+
+        let issues = tree.validate();
+        assert_eq!(
+            issues,
+            vec![
+                TreeIssue::Orphan {
+                    id: "orphan".to_string(),
+                    parent_id: "missing".to_string(),
+                },
+                TreeIssue::Cycle {
+                    id: "x".to_string()
+                },
+                TreeIssue::Cycle {
+                    id: "y".to_string()
+                },
+                TreeIssue::DanglingChild {
+                    parent_id: "root".to_string(),
+                    child_id: "ghost".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_children_by_status() {
+        let mut tree = TreeBuilder::new()
+            .node("root", |b| {
+                b.leaf("failed1")
+                    .leaf("done1")
+                    .leaf("active1")
+                    .leaf("paused")
+                    .leaf("failed2")
+                    .leaf("active2")
+                    .leaf("done2")
+            })
+            .leaf("failed_root")
+            .leaf("active_root")
+            .build();
+        for node in tree.nodes.values_mut() {
+            node.status = match node.title.as_str() {
+                "failed1" | "failed2" | "failed_root" => NodeStatus::Failed,
+                "done1" | "done2" => NodeStatus::Completed,
+                "paused" => NodeStatus::Paused,
+                _ => NodeStatus::Active,
+            };
+        }
+        let titles = |filter: &DisplayFilter| -> Vec<String> {
+            tree.flatten_filtered(filter)
+                .into_iter()
+                .map(|(_, n)| n.title.clone())
+                .collect()
+        };
+
+        let sorted = DisplayFilter {
+            sort_by_status: true,
+            ..DisplayFilter::default()
+        };
+        assert_eq!(
+            titles(&sorted),
+            [
+                "root",
+                "active1",
+                "active2",
+                "paused",
+                "done1",
+                "done2",
+                "failed1",
+                "failed2",
+                "failed_root",
+                "active_root",
+            ]
+        );
+        // 关闭时保持手动顺序，保存顺序也不受影响
+        assert_eq!(titles(&DisplayFilter::default())[1], "failed1");
+        assert_eq!(tree.to_data().nodes[1].title, "failed1");
+    }
+
+    #[test]
+    fn test_validate_and_repair() {
+        let toml_str = [
+            FIXTURE_META.to_string(),
+            fixture_node("root", ""),
+            fixture_node("child", "root"),
+            fixture_node("orphan", "missing"),
+            fixture_node("x", "y"),
+            fixture_node("y", "x"),
+        ]
+        .join("\n");
+        let mut tree = load_fixture(&toml_str);
+        tree.root_ids.push("gone".to_string());
+        tree.children_map.insert("child".to_string(), Vec::new());
+        tree.dirty = false;
+
+        let issues = tree.validate_and_repair();
+        assert_eq!(
+            issues,
+            vec![
+                TreeIssue::Orphan {
+                    id: "orphan".to_string(),
+                    parent_id: "missing".to_string(),
+                },
+                TreeIssue::Cycle {
+                    id: "x".to_string()
+                },
+                TreeIssue::Cycle {
+                    id: "y".to_string()
+                },
+                TreeIssue::DanglingChild {
+                    parent_id: String::new(),
+                    child_id: "gone".to_string(),
+                },
+                TreeIssue::EmptyChildren {
+                    parent_id: "child".to_string(),
+                },
+            ]
+        );
+        assert_eq!(issues[3].node_id(), None);
+        assert_eq!(issues[4].node_id(), Some("child"));
+
+        assert!(tree.validate().is_empty());
+        assert!(tree.dirty);
+        assert_eq!(tree.root_ids, ["root", "orphan", "x"]);
+        assert_eq!(tree.nodes["y"].parent_id, "x");
+        assert_eq!(tree.flatten_for_display().len(), 5);
+
+        // 没有问题时不做任何修改
+        tree.dirty = false;
+        assert!(tree.validate_and_repair().is_empty());
+        assert!(!tree.dirty);
+    }
+
+    #[test]
+    fn test_roots_by_staleness() {
+        let mut tree = TreeBuilder::new()
+            .leaf("new")
+            .node("old", |b| b.leaf("child"))
+            .leaf("middle")
+            .leaf("also new")
+            .build();
+        let now = Local::now();
+        for node in tree.nodes.values_mut() {
+            let days = match node.title.as_str() {
+                "old" => 30,
+                "middle" | "child" => 10,
+                _ => 0,
+            };
+            node.created_at = now - chrono::Duration::days(days);
+        }
+
+        let titles: Vec<&str> = tree
+            .roots_by_staleness()
+            .iter()
+            .map(|node| node.title.as_str())
+            .collect();
+        assert_eq!(titles, ["old", "middle", "new", "also new"]);
+    }
+
+    #[test]
+    fn test_auto_fail_stale() {
+        let mut tree = TreeBuilder::new()
+            .node("parent", |b| b.leaf("at limit").leaf("past limit"))
+            .leaf("touched")
+            .leaf("done")
+            .build();
+        let now = Local::now();
+        let limit = chrono::Duration::days(7);
+        let id_of = |tree: &FocusTree, title: &str| {
+            tree.nodes
+                .values()
+                .find(|n| n.title == title)
+                .unwrap()
+                .id
+                .clone()
+        };
+        for node in tree.nodes.values_mut() {
+            node.created_at = now - limit - chrono::Duration::days(30);
+            match node.title.as_str() {
+                "at limit" => node.updated_at = Some(now - limit),
+                "past limit" => node.updated_at = Some(now - limit - chrono::Duration::seconds(1)),
+                "touched" => node.updated_at = Some(now - chrono::Duration::days(1)),
+                "done" => node.status = NodeStatus::Completed,
+                _ => {}
+            }
+        }
+        let past = id_of(&tree, "past limit");
+
+        // 正好 7 天不算过期；非叶子与非活跃节点不受影响
+        assert_eq!(tree.auto_fail_stale_at(7, now), vec![past.clone()]);
+        assert_eq!(tree.nodes[&past].status, NodeStatus::Failed);
+        assert_eq!(
+            tree.nodes[&id_of(&tree, "parent")].status,
+            NodeStatus::Active
+        );
+        assert!(tree.dirty);
+
+        // 已失败的节点不会重复处理
+        assert!(tree.auto_fail_stale_at(7, now).is_empty());
+    }
+
+    #[test]
+    fn test_cycle_status() {
+        let mut tree = TreeBuilder::new().leaf("a").build();
+        let id = tree.root_ids[0].clone();
+
+        let order: Vec<NodeStatus> = (0..3).filter_map(|_| tree.cycle_status(&id)).collect();
+        assert_eq!(
+            order,
+            [
+                NodeStatus::Completed,
+                NodeStatus::Paused,
+                NodeStatus::Active
+            ]
+        );
+        assert!(tree.dirty);
+
+        // 失败只能通过 f 进入，也不能通过循环离开
+        tree.fail_node(&id);
+        assert_eq!(tree.nodes[&id].next_status(), None);
+        assert_eq!(tree.cycle_status(&id), None);
+        assert_eq!(tree.nodes[&id].status, NodeStatus::Failed);
+        for status in order {
+            let mut node = FocusNode::new("n".to_string(), String::new(), None);
+            node.status = status;
+            assert_ne!(node.next_status(), Some(NodeStatus::Failed));
+        }
+    }
+
+    #[test]
+    fn test_reinsert_deleted_subtree() {
+        let mut tree = TreeBuilder::new()
+            .leaf("first")
+            .node("middle", |b| {
+                b.node("a", |b| b.leaf("a1").leaf("a2")).leaf("b").leaf("c")
+            })
+            .leaf("last")
+            .build();
+        let before = tree.to_data().nodes;
+
+        let middle = tree.root_ids[1].clone();
+        let removed = tree.subtree_snapshot(&middle).unwrap();
+        assert_eq!(removed.nodes.len(), 6);
+        assert_eq!(removed.position, 1);
+        tree.delete_node(&middle);
+        assert_eq!(tree.nodes.len(), 2);
+
+        assert_eq!(tree.reinsert_nodes(removed).unwrap(), middle);
+        assert_eq!(tree.to_data().nodes, before);
+        assert!(tree.validate().is_empty());
+
+        // 原父节点已不存在时拒绝恢复
+        let a = tree.get_children(&middle)[0].id.clone();
+        let removed = tree.subtree_snapshot(&a).unwrap();
+        tree.delete_node(&middle);
+        assert_eq!(tree.reinsert_nodes(removed), Err(TreeError::NotFound));
+
+        // 节点仍在树中（如重新加载了数据文件）时拒绝，不产生重复的 ID
+        let removed = tree.subtree_snapshot(&tree.root_ids[0].clone()).unwrap();
+        let first = removed.nodes[0].id.clone();
+        let roots = tree.root_ids.clone();
+        assert_eq!(
+            tree.reinsert_nodes(removed),
+            Err(TreeError::IdExists(first))
+        );
+        assert_eq!(tree.root_ids, roots);
+    }
+
+    #[test]
+    fn test_count_by_status() {
+        assert_eq!(FocusTree::new().count_by_status(), StatusCounts::default());
+
+        let mut tree = TreeBuilder::new()
+            .node("a", |b| b.leaf("a1").leaf("a2").leaf("a3"))
+            .leaf("b")
+            .leaf("c")
+            .leaf("d")
+            .build();
+        for node in tree.nodes.values_mut() {
+            node.status = match node.title.as_str() {
+                "a1" | "a2" => NodeStatus::Failed,
+                "b" | "c" => NodeStatus::Completed,
+                "d" => NodeStatus::Paused,
+                _ => NodeStatus::Active,
+            };
+        }
+
+        let counts = tree.count_by_status();
+        assert_eq!(
+            counts,
+            StatusCounts {
+                active: 2,
+                failed: 2,
+                completed: 2,
+                paused: 1,
+            }
+        );
+        assert_eq!(counts.total(), tree.nodes.len());
+    }
+
+    #[test]
+    fn test_completion_percent() {
+        let counts = |active, failed, completed, paused| StatusCounts {
+            active,
+            failed,
+            completed,
+            paused,
+        };
+        assert_eq!(StatusCounts::default().completion_percent(false), 0);
+        assert_eq!(StatusCounts::default().completion_percent(true), 0);
+        assert_eq!(counts(3, 0, 2, 0).completion_percent(false), 40);
+        // 失败与暂停默认不计入分母
+        assert_eq!(counts(3, 4, 2, 1).completion_percent(false), 40);
+        assert_eq!(counts(3, 4, 2, 1).completion_percent(true), 20);
+        assert_eq!(counts(0, 2, 0, 1).completion_percent(false), 0);
+        assert_eq!(counts(0, 0, 3, 0).completion_percent(false), 100);
+        assert_eq!(counts(2, 0, 1, 0).completion_percent(false), 33);
+    }
+
+    #[test]
+    fn test_log_events_round_trip() {
+        let mut tree = TreeBuilder::new().leaf("a").build();
+        let id = tree.root_ids[0].clone();
+        tree.cycle_status(&id);
+        tree.nodes.get_mut(&id).unwrap().log.push(LogEntry {
+            at: Local::now(),
+            text: "旧版本的记录".to_string(),
+            event: None,
+        });
+
+        let data = toml::to_string_pretty(&tree.to_data()).unwrap();
+        let loaded = FocusTree::from_data(toml::from_str(&data).unwrap());
+        assert_eq!(loaded.nodes[&id].log, tree.nodes[&id].log);
+        assert_eq!(
+            loaded.nodes[&id].log[0].event,
+            Some(LogEvent::StatusChanged {
+                from: NodeStatus::Active,
+                to: NodeStatus::Completed,
+            })
+        );
+
+        // 旧数据只有文字
+        let entry: LogEntry =
+            toml::from_str("at = \"2024-01-01T00:00:00+08:00\"\ntext = \"标记为完成\"").unwrap();
+        assert_eq!(entry.event, None);
+        assert_eq!(Strings::default().log_entry(&entry), "标记为完成");
+    }
+
+    #[test]
+    fn test_completed_at_set_and_cleared() {
+        let mut tree = TreeBuilder::new().leaf("a").build();
+        let id = tree.root_ids[0].clone();
+        assert_eq!(tree.nodes[&id].completed_at, None);
+
+        tree.toggle_complete(&id);
+        assert!(tree.nodes[&id].completed_at.is_some());
+        tree.toggle_complete(&id);
+        assert_eq!(tree.nodes[&id].completed_at, None);
+
+        // 循环切换离开已完成、失败后恢复都会清除完成时间
+        tree.cycle_status(&id);
+        assert!(tree.nodes[&id].completed_at.is_some());
+        tree.cycle_status(&id);
+        assert_eq!(tree.nodes[&id].status, NodeStatus::Paused);
+        assert_eq!(tree.nodes[&id].completed_at, None);
+        tree.toggle_complete(&id);
+        tree.fail_node(&id);
+        assert_eq!(tree.nodes[&id].completed_at, None);
+        tree.recover_node(&id);
+        assert_eq!(tree.nodes[&id].completed_at, None);
+
+        // 旧数据没有该字段时默认为 None
+        let node: FocusNode = toml::from_str(
+            "id = \"x\"\ntitle = \"t\"\ncreated_at = \"2024-01-01T00:00:00+08:00\"\nstatus = \"completed\"",
+        )
+        .unwrap();
+        assert_eq!(node.completed_at, None);
+    }
+
+    #[test]
+    fn test_toggle_leaf_done() {
+        let mut tree = TreeBuilder::new().node("a", |b| b.leaf("x")).build();
+        let a = tree.root_ids[0].clone();
+        let x = tree.get_children(&a)[0].id.clone();
+
+        assert_eq!(tree.toggle_leaf_done(&x), Some(NodeStatus::Completed));
+        assert!(tree.nodes[&x].completed_at.is_some());
+        assert_eq!(tree.toggle_leaf_done(&x), Some(NodeStatus::Active));
+        assert!(tree.nodes[&x].completed_at.is_none());
+
+        // 父节点与暂停的叶子不变
+        tree.dirty = false;
+        assert_eq!(tree.toggle_leaf_done(&a), None);
+        assert_eq!(tree.nodes[&a].status, NodeStatus::Active);
+        tree.nodes.get_mut(&x).unwrap().status = NodeStatus::Paused;
+        assert_eq!(tree.toggle_leaf_done(&x), None);
+        assert!(!tree.dirty);
+        assert_eq!(tree.toggle_leaf_done("missing"), None);
+    }
+
+    #[test]
+    fn test_completed_since() {
+        let mut tree = TreeBuilder::new()
+            .leaf("recent")
+            .leaf("old")
+            .leaf("legacy")
+            .leaf("active")
+            .build();
+        let now = Local::now();
+        for node in tree.nodes.values_mut() {
+            let completed_at = match node.title.as_str() {
+                "recent" => Some(now - chrono::Duration::days(3)),
+                "old" => Some(now - chrono::Duration::days(8)),
+                _ => None,
+            };
+            if node.title != "active" {
+                node.status = NodeStatus::Completed;
+            }
+            node.completed_at = completed_at;
+        }
+
+        let titles = |days| -> Vec<String> {
+            tree.completed_since(now.date_naive() - chrono::Duration::days(days))
+                .iter()
+                .map(|n| n.title.clone())
+                .collect()
+        };
+        assert_eq!(titles(7), ["recent"]);
+        assert_eq!(titles(10), ["recent", "old"]);
+        assert_eq!(titles(3), ["recent"]);
+        assert!(titles(1).is_empty());
+    }
+
+    #[test]
+    fn test_fail_with_reason() {
+        let mut tree = TreeBuilder::new()
+            .node("goal", |b| b.leaf("step"))
+            .leaf("other")
+            .build();
+        let goal = tree.root_ids[0].clone();
+        let deleted = tree.fail_node_with_reason(&goal, "  太忙了 ");
+        assert_eq!(deleted.len(), 1);
+
+        let log: Vec<String> = tree.nodes[&goal]
+            .log
+            .iter()
+            .map(|e| Strings::default().log_entry(e))
+            .collect();
+        assert_eq!(log, ["失败原因: 太忙了", "标记为失败"]);
+
+        // 原因为空时不记录
+        let other = tree.root_ids[1].clone();
+        tree.fail_node_with_reason(&other, " ");
+        assert_eq!(tree.nodes[&other].log.len(), 1);
     }
 }
@@ -1,8 +1,29 @@
-use std::fs;
-use std::io;
-use std::path::Path;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
-use crate::models::{FocusTree, FocusTreeData};
+use chrono::{Local, NaiveDate};
+
+use crate::i18n::{Key, Strings};
+use crate::models::{FocusTree, FocusTreeData, NodeStatus, TreeBuilder};
+
+/// 进度快照 CSV 的表头，各状态列之和等于 total
+pub const SNAPSHOT_HEADER: &str = "date,total,active,failed,completed,paused,streak_days";
+
+/// 旧版快照表头：没有 paused 列，暂停的节点只计入 total
+const SNAPSHOT_HEADER_V1: &str = "date,total,active,failed,completed,streak_days";
+
+/// 数据文件不存在时的初始内容
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Seed {
+    /// 空树
+    #[default]
+    Empty,
+    /// 内置的示例树
+    Example,
+    /// 从模板文件加载
+    Template(PathBuf),
+}
 
 /// 从TOML文件加载树
 pub fn load_tree(path: &Path) -> io::Result<FocusTree> {
@@ -18,17 +39,481 @@ pub fn load_tree(path: &Path) -> io::Result<FocusTree> {
 }
 
 /// 保存树到TOML文件
-pub fn save_tree(tree: &mut FocusTree, path: &Path) -> io::Result<()> {
+///
+/// 从更新版本的数据文件加载的树拒绝保存：当前程序不认识的字段会在保存时丢失
+pub fn save_tree(tree: &mut FocusTree, path: &Path, strings: &Strings) -> io::Result<()> {
     if !tree.dirty {
         return Ok(());
     }
+    if let Some(version) = &tree.unknown_version {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            strings.format(Key::ErrNewerDataFile, &[version]),
+        ));
+    }
 
     let data = tree.to_data();
     let content =
         toml::to_string_pretty(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
     fs::write(path, content)?;
-    
+
     tree.dirty = false;
     Ok(())
 }
+
+/// 内置的示例树：一个根节点和两个子节点
+pub fn example_tree() -> FocusTree {
+    TreeBuilder::new()
+        .node("示例国策", |b| b.leaf("每天早起").leaf("每周运动三次"))
+        .build()
+}
+
+/// 加载数据文件；文件不存在时按 `seed` 生成初始树（标记为待保存）
+pub fn load_tree_or_seed(path: &Path, seed: &Seed) -> io::Result<FocusTree> {
+    if path.exists() {
+        return load_tree(path);
+    }
+    seed_tree(seed)
+}
+
+/// 按 `seed` 生成初始树，非空时标记为待保存
+pub fn seed_tree(seed: &Seed) -> io::Result<FocusTree> {
+    let mut tree = match seed {
+        Seed::Empty => return Ok(FocusTree::new()),
+        Seed::Example => example_tree(),
+        Seed::Template(template) => load_tree(template)?,
+    };
+    tree.dirty = true;
+    Ok(tree)
+}
+
+/// 解析缩进的文本 / Markdown 大纲：每 2 个空格（或 1 个制表符）为一层，
+/// 行首的 `-`、`*`、`+` 列表标记会被去掉，空行忽略
+///
+/// 缩进不是 2 的倍数时取最近的层级，比上一行深超过一层时视为下一层
+pub fn import_outline(text: &str) -> FocusTreeData {
+    let mut tree = FocusTree::new();
+    let mut parents: Vec<String> = Vec::new(); // parents[i] 为第 i 层最近的节点
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let title = trimmed
+            .strip_prefix(['-', '*', '+'])
+            .map(str::trim_start)
+            .unwrap_or(trimmed)
+            .trim_end();
+        if title.is_empty() {
+            continue;
+        }
+        let indent: usize = line[..line.len() - trimmed.len()]
+            .chars()
+            .map(|c| if c == '\t' { 2 } else { 1 })
+            .sum();
+        let level = indent.div_ceil(2).min(parents.len());
+        parents.truncate(level);
+        let parent = parents.last().cloned();
+        if let Ok(id) = tree.add_node(title.to_string(), String::new(), parent) {
+            parents.push(id);
+        }
+    }
+    tree.to_data()
+}
+
+/// 界面上用作状态标记的符号，用户手动输入到标题里时在导出时去掉
+const STATUS_GLYPHS: &[char] = &['●', '✗', '✓', '‖', '✅', '❌', '🆕'];
+
+/// 去掉标题中手动输入的界面装饰：行首的状态符号、行尾的 `[✓]` 状态标记与 `(12 天)` 天数
+///
+/// 去掉后为空时保留原标题
+fn export_title(title: &str) -> &str {
+    let mut clean =
+        title.trim_start_matches(|c: char| STATUS_GLYPHS.contains(&c) || c.is_whitespace());
+    clean = clean.trim_end();
+    if let Some((head, icon)) = clean.strip_suffix(']').and_then(|s| s.rsplit_once('['))
+        && !icon.is_empty()
+        && icon.chars().all(|c| STATUS_GLYPHS.contains(&c))
+    {
+        clean = head.trim_end();
+    }
+    if let Some((head, days)) = clean.strip_suffix(')').and_then(|s| s.rsplit_once('('))
+        && let Some(count) = days.trim().strip_suffix(['天', 'd']).map(str::trim_end)
+        && !count.is_empty()
+        && count.chars().all(|c| c.is_ascii_digit())
+    {
+        clean = head.trim_end();
+    }
+    if clean.is_empty() { title } else { clean }
+}
+
+/// 导出为 Markdown 任务列表：每层缩进 2 个空格，复选框由节点状态决定（仅已完成勾选），
+/// 节点内容作为缩进的续行写在条目下方
+pub fn export_markdown(tree: &FocusTree) -> String {
+    let mut out = String::new();
+    for (depth, node) in tree.flatten_for_display() {
+        let indent = "  ".repeat(depth);
+        let mark = if node.status == NodeStatus::Completed {
+            'x'
+        } else {
+            ' '
+        };
+        out.push_str(&format!(
+            "{}- [{}] {}\n",
+            indent,
+            mark,
+            export_title(&node.title)
+        ));
+        for line in node.content.lines() {
+            if line.trim().is_empty() {
+                out.push('\n');
+            } else {
+                out.push_str(&format!("{}  {}\n", indent, line));
+            }
+        }
+    }
+    out
+}
+
+/// 生成某一天的进度快照行（不含换行）：日期、节点总数、各状态数量、活跃节点的连续天数之和
+pub fn snapshot_row(tree: &FocusTree, date: NaiveDate) -> String {
+    let counts = tree.count_by_status();
+    let streak_days: i64 = tree
+        .nodes
+        .values()
+        .filter(|n| n.status == NodeStatus::Active)
+        .map(|n| n.days_active())
+        .sum();
+    format!(
+        "{},{},{},{},{},{},{}",
+        date.format("%Y-%m-%d"),
+        counts.total(),
+        counts.active,
+        counts.failed,
+        counts.completed,
+        counts.paused,
+        streak_days
+    )
+}
+
+/// 将旧版快照转换为带 paused 列的格式：旧行的暂停数量为 total 减去其余状态之和；
+/// 不是旧版表头时返回 None
+fn migrate_snapshot_csv(content: &str) -> Option<String> {
+    let mut lines = content.lines();
+    if lines.next()? != SNAPSHOT_HEADER_V1 {
+        return None;
+    }
+    let mut migrated = format!("{}\n", SNAPSHOT_HEADER);
+    for line in lines {
+        let fields: Vec<&str> = line.split(',').collect();
+        let counts: Option<Vec<i64>> = fields.get(1..5)?.iter().map(|f| f.parse().ok()).collect();
+        match (counts, fields.len()) {
+            (Some(counts), 6) => {
+                let paused = counts[0] - counts[1..].iter().sum::<i64>();
+                let (head, tail) = fields.split_at(5);
+                migrated.push_str(&format!(
+                    "{},{},{}\n",
+                    head.join(","),
+                    paused,
+                    tail.join(",")
+                ));
+            }
+            // 无法识别的行原样保留
+            _ => migrated.push_str(&format!("{}\n", line)),
+        }
+    }
+    Some(migrated)
+}
+
+/// CSV 内容中是否已有该日期的快照行
+fn has_snapshot_for(content: &str, date: NaiveDate) -> bool {
+    let prefix = format!("{},", date.format("%Y-%m-%d"));
+    content.lines().any(|line| line.starts_with(&prefix))
+}
+
+/// 追加今天的进度快照到 CSV 文件（文件不存在时先写表头），同一天只记录一次，返回是否写入
+pub fn append_snapshot_csv(tree: &FocusTree, path: &Path) -> io::Result<bool> {
+    append_snapshot_on(tree, path, Local::now().date_naive())
+}
+
+fn append_snapshot_on(tree: &FocusTree, path: &Path, date: NaiveDate) -> io::Result<bool> {
+    let existing = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    if has_snapshot_for(&existing, date) {
+        return Ok(false);
+    }
+    if let Some(migrated) = migrate_snapshot_csv(&existing) {
+        fs::write(path, format!("{}{}\n", migrated, snapshot_row(tree, date)))?;
+        return Ok(true);
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if existing.is_empty() {
+        writeln!(file, "{}", SNAPSHOT_HEADER)?;
+    }
+    writeln!(file, "{}", snapshot_row(tree, date))?;
+    Ok(true)
+}
+
+/// 在目录中创建并删除一个临时文件，检查是否可以写入
+pub fn check_writable(dir: &Path) -> io::Result<()> {
+    let probe = dir.join(format!(".rhizome-write-test-{}", uuid::Uuid::new_v4()));
+    fs::write(&probe, b"")?;
+    fs::remove_file(&probe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Lang;
+    use crate::test_util::temp_path;
+
+    #[test]
+    fn test_unknown_version_never_written() {
+        let path = temp_path("newer.toml");
+        let original = r#"nodes = []
+
+[meta]
+version = "9.0"
+last_modified = "2024-01-01T00:00:00+08:00"
+future_field = 1
+"#;
+        fs::write(&path, original).unwrap();
+        let mut tree = load_tree(&path).unwrap();
+        assert_eq!(tree.unknown_version.as_deref(), Some("9.0"));
+
+        tree.add_node("new".to_string(), String::new(), None)
+            .unwrap();
+        let err = save_tree(&mut tree, &path, &Strings::new(Lang::En)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Data file version 9.0 is newer than this program, refusing to overwrite it"
+        );
+        assert!(tree.dirty);
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_seed_example() {
+        let missing = temp_path("missing.toml");
+        let tree = load_tree_or_seed(&missing, &Seed::Example).unwrap();
+        assert!(tree.dirty);
+
+        let roots = tree.get_roots();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0].title, "示例国策");
+        let children: Vec<&str> = tree
+            .get_children(&roots[0].id)
+            .iter()
+            .map(|n| n.title.as_str())
+            .collect();
+        assert_eq!(children, vec!["每天早起", "每周运动三次"]);
+
+        assert!(
+            load_tree_or_seed(&missing, &Seed::Empty)
+                .unwrap()
+                .nodes
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_seed_ignored_when_data_exists() {
+        let path = temp_path("data.toml");
+        let mut tree = FocusTree::new();
+        tree.add_node("mine".to_string(), "".to_string(), None)
+            .unwrap();
+        save_tree(&mut tree, &path, &Strings::default()).unwrap();
+
+        let loaded = load_tree_or_seed(&path, &Seed::Example).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.nodes.len(), 1);
+        assert!(!loaded.dirty);
+    }
+
+    #[test]
+    fn test_seed_template() {
+        let template = temp_path("template.toml");
+        save_tree(&mut example_tree(), &template, &Strings::default()).unwrap();
+
+        let tree = load_tree_or_seed(
+            &temp_path("missing.toml"),
+            &Seed::Template(template.clone()),
+        );
+        std::fs::remove_file(&template).unwrap();
+        let tree = tree.unwrap();
+        assert_eq!(tree.nodes.len(), 3);
+        assert!(tree.dirty);
+    }
+
+    #[test]
+    fn test_snapshot_row() {
+        let mut tree = example_tree();
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        for (i, node) in tree.nodes.values_mut().enumerate() {
+            node.created_at = Local::now() - chrono::Duration::days(10);
+            if i == 0 {
+                node.status = NodeStatus::Failed;
+            }
+        }
+        assert_eq!(snapshot_row(&tree, date), "2024-05-01,3,2,1,0,0,20");
+        assert_eq!(
+            snapshot_row(&FocusTree::new(), date),
+            "2024-05-01,0,0,0,0,0,0"
+        );
+
+        // 各状态列之和等于 total
+        let id = tree.root_ids[0].clone();
+        tree.nodes.get_mut(&id).unwrap().status = NodeStatus::Paused;
+        let row = snapshot_row(&tree, date);
+        let fields: Vec<usize> = row.split(',').skip(1).map(|f| f.parse().unwrap()).collect();
+        assert_eq!(fields[0], fields[1..5].iter().sum::<usize>());
+        assert_eq!(fields[4], 1);
+    }
+
+    #[test]
+    fn test_migrate_snapshot_header() {
+        let path = temp_path("progress.csv");
+        std::fs::write(
+            &path,
+            format!("{}\n2024-05-01,4,2,1,0,20\n", SNAPSHOT_HEADER_V1),
+        )
+        .unwrap();
+        let day2 = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+        assert!(append_snapshot_on(&example_tree(), &path, day2).unwrap());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], SNAPSHOT_HEADER);
+        assert_eq!(lines[1], "2024-05-01,4,2,1,0,1,20");
+        assert!(lines[2].starts_with("2024-05-02,3,3,0,0,0,"));
+        assert_eq!(migrate_snapshot_csv(&content), None);
+    }
+
+    #[test]
+    fn test_snapshot_same_day_dedup() {
+        let path = temp_path("snapshot.toml");
+        let tree = example_tree();
+        let day1 = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let day2 = NaiveDate::from_ymd_opt(2024, 5, 2).unwrap();
+
+        assert!(append_snapshot_on(&tree, &path, day1).unwrap());
+        assert!(!append_snapshot_on(&tree, &path, day1).unwrap());
+        assert!(append_snapshot_on(&tree, &path, day2).unwrap());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], SNAPSHOT_HEADER);
+        assert!(lines[1].starts_with("2024-05-01,3,3,"));
+        assert!(lines[2].starts_with("2024-05-02,"));
+        assert!(has_snapshot_for(&content, day2));
+        assert!(!has_snapshot_for(&content, day2.succ_opt().unwrap()));
+    }
+
+    #[test]
+    fn test_import_outline() {
+        let outline = "\
+- 健康
+  - 运动
+    - 每周跑步三次
+    - 每天拉伸
+  - 早睡
+- 学习
+
+  * 每天读书
+";
+        let tree = FocusTree::from_data(import_outline(outline));
+        let flat: Vec<(usize, &str)> = tree
+            .flatten_for_display()
+            .iter()
+            .map(|(depth, node)| (*depth, node.title.as_str()))
+            .collect();
+        assert_eq!(
+            flat,
+            [
+                (0, "健康"),
+                (1, "运动"),
+                (2, "每周跑步三次"),
+                (2, "每天拉伸"),
+                (1, "早睡"),
+                (0, "学习"),
+                (1, "每天读书"),
+            ]
+        );
+        assert!(tree.validate().is_empty());
+    }
+
+    #[test]
+    fn test_export_markdown() {
+        let mut tree = TreeBuilder::new()
+            .node("✓ 健康 [✓]", |b| {
+                b.leaf("运动 (12 天)").leaf("早睡 (3d)")
+            })
+            .leaf("学习 (第 2 版)")
+            .build();
+        let root = tree.root_ids[0].clone();
+        let children: Vec<String> = tree
+            .get_children(&root)
+            .iter()
+            .map(|n| n.id.clone())
+            .collect();
+        tree.toggle_complete(&children[0]);
+        tree.fail_node(&children[1]);
+        tree.nodes.get_mut(&root).unwrap().content = "每天记录\n\n睡前复盘".to_string();
+
+        assert_eq!(
+            export_markdown(&tree),
+            "\
+- [ ] 健康
+  每天记录
+
+  睡前复盘
+  - [x] 运动
+  - [ ] 早睡
+- [ ] 学习 (第 2 版)
+"
+        );
+    }
+
+    #[test]
+    fn test_import_outline_inconsistent_indent() {
+        // 3 个空格取最近的第 2 层，跳过层级的缩进视为下一层
+        let outline = "a\n   b\n        c\n\td\n e";
+        let tree = FocusTree::from_data(import_outline(outline));
+        let flat: Vec<(usize, &str)> = tree
+            .flatten_for_display()
+            .iter()
+            .map(|(depth, node)| (*depth, node.title.as_str()))
+            .collect();
+        assert_eq!(flat, [(0, "a"), (1, "b"), (2, "c"), (1, "d"), (1, "e")]);
+    }
+
+    #[test]
+    fn test_check_writable() {
+        let dir = temp_path("dir");
+        assert!(check_writable(&dir).is_err());
+
+        fs::create_dir(&dir).unwrap();
+        assert!(check_writable(&dir).is_ok());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        let mut permissions = fs::metadata(&dir).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&dir, permissions.clone()).unwrap();
+        // root 不受目录权限限制，只在权限生效时检查
+        let enforced = fs::write(dir.join("x"), b"").is_err();
+        if enforced {
+            assert!(check_writable(&dir).is_err());
+        }
+
+        #[allow(clippy::permissions_set_readonly_false)]
+        permissions.set_readonly(false);
+        fs::set_permissions(&dir, permissions).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,8 @@
+//! 测试共用的辅助函数
+
+use std::path::PathBuf;
+
+/// 系统临时目录中一个尚不存在的唯一路径，`name` 为文件名（如 `data.toml`）
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("rhizome-{}-{}", uuid::Uuid::new_v4(), name))
+}
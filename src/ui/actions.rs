@@ -2,24 +2,214 @@
 //!
 //! 用户交互转化为明确的语义化 Action
 
+use crate::i18n::{Key, Strings};
+
 /// 用户操作枚举
 #[derive(Debug, Clone, PartialEq)]
 pub enum Action {
     Quit,
+    QuitDiscard,
+    SaveNow,
     MoveSelectionUp,
     MoveSelectionDown,
+    CollapseOrParent, // 折叠选中节点，已折叠或是叶子时跳到父节点
+    ExpandOrChild,    // 展开选中节点，已展开时跳到第一个子节点
+    ExpandOne,        // 只展开选中节点一层，子节点保持折叠
+    CollapseOne,      // 只折叠选中节点，不移动选中
+    ScrollRowLeft,    // 选中行标题向右移，显示被截掉的开头
+    ScrollRowRight,   // 选中行标题向左移，显示被截掉的结尾
+    CenterSelection,  // 滚动节点列表，使选中行位于中间
 
     // 触发特定功能
     StartAddNode,
+    StartAddRoot,
     StartEditContent,
+    OpenInEditor,
     StartEditTitle,
     StartMoveNode,
+    StartLinkNode,
     StartDeleteNode,
     StartFailNode,
+    StartPurgeFailed,
+    Undo,
+    ToggleComplete,
+    ToggleLeafDone,
+    CycleStatus,
+    ArchiveCompleted,
+    ToggleShowArchived,
+    CycleColor,
+    StartEditEstimate,
+    StartEditDue,
+    ToggleWrapTitles,
+    ToggleShowIds,
+    ToggleShowDepth,
+    ToggleStatusSort,
+    ToggleToc,
+    StartReplace,
+    StartGoto,
+    NextOverdue,    // 跳到下一个逾期节点，到末尾后从头开始
+    PrevOverdue,    // 跳到上一个逾期节点，到开头后从末尾开始
+    GotoId(String), // 跳转到指定 ID 的节点
+    OpenStaleRoots,
+    OpenRecent,
+    ReviewCompleted,     // 仅在命令面板中提供
+    MergeDuplicateRoots, // 仅在命令面板中提供
+    CheckIntegrity,      // 校验树结构，发现问题时列出并询问是否修复
+    ShowKeys,
+    OpenCommandPalette,
+    NextDetailsTab,
 
     // 表单/通用交互
-    Cancel,      // Esc / n
-    Submit,      // Enter / y / m
-    Input(char), // 输入字符
-    DeleteChar,  // Backspace
+    Cancel,            // Esc / n
+    Submit,            // Enter / y / m
+    SubmitQuick,       // Shift+Enter / Alt+Enter：跳过内容直接添加
+    Input(char),       // 输入字符
+    DeleteChar,        // Backspace
+    DeleteWord,        // Ctrl+W
+    DeleteToLineStart, // Ctrl+U
+    SwitchInputField,  // Tab
+}
+
+impl Action {
+    /// 可在命令面板中执行的全部操作
+    pub fn all() -> &'static [Action] {
+        &[
+            Action::StartAddNode,
+            Action::StartAddRoot,
+            Action::StartEditContent,
+            Action::OpenInEditor,
+            Action::StartEditTitle,
+            Action::StartMoveNode,
+            Action::StartLinkNode,
+            Action::StartDeleteNode,
+            Action::StartFailNode,
+            Action::StartPurgeFailed,
+            Action::Undo,
+            Action::ToggleComplete,
+            Action::ToggleLeafDone,
+            Action::CycleStatus,
+            Action::ArchiveCompleted,
+            Action::ToggleShowArchived,
+            Action::CycleColor,
+            Action::StartEditEstimate,
+            Action::StartEditDue,
+            Action::ToggleWrapTitles,
+            Action::ToggleShowIds,
+            Action::ToggleShowDepth,
+            Action::ToggleStatusSort,
+            Action::ToggleToc,
+            Action::StartReplace,
+            Action::StartGoto,
+            Action::NextOverdue,
+            Action::PrevOverdue,
+            Action::OpenStaleRoots,
+            Action::OpenRecent,
+            Action::ReviewCompleted,
+            Action::MergeDuplicateRoots,
+            Action::CheckIntegrity,
+            Action::ShowKeys,
+            Action::NextDetailsTab,
+            Action::MoveSelectionUp,
+            Action::MoveSelectionDown,
+            Action::CollapseOrParent,
+            Action::ExpandOrChild,
+            Action::ExpandOne,
+            Action::CollapseOne,
+            Action::ScrollRowLeft,
+            Action::ScrollRowRight,
+            Action::CenterSelection,
+            Action::SaveNow,
+            Action::Quit,
+            Action::QuitDiscard,
+        ]
+    }
+
+    /// 是否会修改树（只读模式下被忽略）
+    pub fn is_mutating(&self) -> bool {
+        matches!(
+            self,
+            Action::StartAddNode
+                | Action::StartAddRoot
+                | Action::StartEditContent
+                | Action::OpenInEditor
+                | Action::StartEditTitle
+                | Action::StartMoveNode
+                | Action::StartLinkNode
+                | Action::StartDeleteNode
+                | Action::StartFailNode
+                | Action::StartPurgeFailed
+                | Action::Undo
+                | Action::ToggleComplete
+                | Action::ToggleLeafDone
+                | Action::CycleStatus
+                | Action::ArchiveCompleted
+                | Action::CycleColor
+                | Action::StartEditEstimate
+                | Action::StartEditDue
+                | Action::StartReplace
+                | Action::MergeDuplicateRoots
+        )
+    }
+
+    /// 操作的显示名称
+    pub fn name(&self, strings: &Strings) -> &'static str {
+        strings.get(match self {
+            Action::Quit => Key::ActionQuit,
+            Action::QuitDiscard => Key::ActionQuitDiscard,
+            Action::SaveNow => Key::ActionSave,
+            Action::MoveSelectionUp => Key::ActionMoveUp,
+            Action::MoveSelectionDown => Key::ActionMoveDown,
+            Action::CollapseOrParent => Key::ActionCollapse,
+            Action::ExpandOrChild => Key::ActionExpand,
+            Action::ExpandOne => Key::ActionExpandOne,
+            Action::CollapseOne => Key::ActionCollapseOne,
+            Action::ScrollRowLeft => Key::ActionScrollLeft,
+            Action::ScrollRowRight => Key::ActionScrollRight,
+            Action::CenterSelection => Key::ActionCenterSelection,
+            Action::StartAddNode => Key::ActionAdd,
+            Action::StartAddRoot => Key::ActionAddRoot,
+            Action::StartEditContent => Key::ActionEditContent,
+            Action::OpenInEditor => Key::ActionOpenInEditor,
+            Action::StartEditTitle => Key::ActionRename,
+            Action::StartMoveNode => Key::ActionMove,
+            Action::StartLinkNode => Key::ActionLink,
+            Action::StartDeleteNode => Key::ActionDelete,
+            Action::StartFailNode => Key::ActionFail,
+            Action::StartPurgeFailed => Key::ActionPurgeFailed,
+            Action::Undo => Key::ActionUndo,
+            Action::ToggleComplete => Key::ActionComplete,
+            Action::ToggleLeafDone => Key::ActionToggleLeafDone,
+            Action::CycleStatus => Key::ActionCycleStatus,
+            Action::ArchiveCompleted => Key::ActionArchive,
+            Action::ToggleShowArchived => Key::ActionShowArchived,
+            Action::CycleColor => Key::ActionCycleColor,
+            Action::StartEditEstimate => Key::ActionEstimate,
+            Action::StartEditDue => Key::ActionDue,
+            Action::ToggleWrapTitles => Key::ActionWrapTitles,
+            Action::ToggleShowIds => Key::ActionShowIds,
+            Action::ToggleShowDepth => Key::ActionShowDepth,
+            Action::ToggleStatusSort => Key::ActionStatusSort,
+            Action::ToggleToc => Key::ActionToc,
+            Action::StartReplace => Key::ActionReplace,
+            Action::StartGoto | Action::GotoId(_) => Key::ActionGoto,
+            Action::NextOverdue => Key::ActionNextOverdue,
+            Action::PrevOverdue => Key::ActionPrevOverdue,
+            Action::OpenStaleRoots => Key::ActionStaleRoots,
+            Action::OpenRecent => Key::ActionRecent,
+            Action::ReviewCompleted => Key::ActionReviewCompleted,
+            Action::MergeDuplicateRoots => Key::ActionMergeRoots,
+            Action::CheckIntegrity => Key::ActionCheckIntegrity,
+            Action::ShowKeys => Key::ActionShowKeys,
+            Action::OpenCommandPalette => Key::ActionPalette,
+            Action::NextDetailsTab => Key::ActionNextTab,
+            Action::Cancel => Key::ActionCancel,
+            Action::Submit => Key::ActionSubmit,
+            Action::SubmitQuick => Key::ActionSubmitQuick,
+            Action::Input(_) => Key::ActionInput,
+            Action::DeleteChar => Key::ActionDeleteChar,
+            Action::DeleteWord => Key::ActionDeleteWord,
+            Action::DeleteToLineStart => Key::ActionDeleteToLineStart,
+            Action::SwitchInputField => Key::ActionSwitchField,
+        })
+    }
 }
@@ -4,28 +4,112 @@
 
 use std::io;
 
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 use super::actions::Action;
+use super::logic::DispatchResult;
 use super::state::{App, AppMode};
 
-/// 根据当前模式和按键获取对应的 Action
-pub fn get_action(mode: &AppMode, key: KeyCode) -> Option<Action> {
+/// 与模式无关的全局按键（如 Ctrl+C 退出、Ctrl+S 保存）
+pub fn global_action(key: &KeyEvent) -> Option<Action> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Char('c') if ctrl => Some(Action::Quit),
+        KeyCode::Char('s') if ctrl => Some(Action::SaveNow),
+        _ => None,
+    }
+}
+
+/// 根据当前模式和按键（含修饰键）获取对应的 Action
+pub fn get_action(mode: &AppMode, key: KeyEvent) -> Option<Action> {
+    if let Some(action) = global_action(&key) {
+        return Some(action);
+    }
+
+    // 输入框中的行编辑快捷键
+    if mode.is_text_input() && key.modifiers.contains(KeyModifiers::CONTROL) {
+        match key.code {
+            KeyCode::Char('w') => return Some(Action::DeleteWord),
+            KeyCode::Char('u') => return Some(Action::DeleteToLineStart),
+            _ => {}
+        }
+    }
+
+    if *mode == AppMode::Normal && key.modifiers.contains(KeyModifiers::CONTROL) {
+        match key.code {
+            KeyCode::Char('z') => return Some(Action::Undo),
+            KeyCode::Char('r') => return Some(Action::OpenRecent),
+            KeyCode::Char('t') => return Some(Action::ToggleToc),
+            KeyCode::Char('i') => return Some(Action::CheckIntegrity),
+            _ => {}
+        }
+    }
+
+    // 添加节点时 Shift/Alt+Enter 跳过内容输入
+    if *mode == AppMode::AddingNode
+        && key.code == KeyCode::Enter
+        && key
+            .modifiers
+            .intersects(KeyModifiers::SHIFT | KeyModifiers::ALT)
+    {
+        return Some(Action::SubmitQuick);
+    }
+
+    // 以下均为普通按键绑定，带 Ctrl/Alt 的组合键不匹配（Shift 已体现在字符大小写中）
+    if key
+        .modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    {
+        return None;
+    }
+
+    let key = key.code;
     match mode {
         AppMode::Normal => match key {
             KeyCode::Char('q') => Some(Action::Quit),
+            KeyCode::Char('Q') => Some(Action::QuitDiscard),
             KeyCode::Char('j') | KeyCode::Down => Some(Action::MoveSelectionDown),
             KeyCode::Char('k') | KeyCode::Up => Some(Action::MoveSelectionUp),
+            KeyCode::Char('h') | KeyCode::Left => Some(Action::CollapseOrParent),
+            KeyCode::Char('l') | KeyCode::Right => Some(Action::ExpandOrChild),
+            KeyCode::Char('+') => Some(Action::ExpandOne),
+            KeyCode::Char('-') => Some(Action::CollapseOne),
+            KeyCode::Char('H') => Some(Action::ScrollRowLeft),
+            KeyCode::Char('L') => Some(Action::ScrollRowRight),
+            KeyCode::Char('z') => Some(Action::CenterSelection),
             KeyCode::Char('a') => Some(Action::StartAddNode),
+            KeyCode::Char('A') => Some(Action::StartAddRoot),
             KeyCode::Char('e') => Some(Action::StartEditContent),
+            KeyCode::Char('O') => Some(Action::OpenInEditor),
             KeyCode::Char('r') => Some(Action::StartEditTitle),
             KeyCode::Char('m') => Some(Action::StartMoveNode),
+            KeyCode::Char('n') => Some(Action::StartLinkNode),
             KeyCode::Char('d') => Some(Action::StartDeleteNode),
             KeyCode::Char('f') => Some(Action::StartFailNode),
+            KeyCode::Char('F') => Some(Action::StartPurgeFailed),
+            KeyCode::Char('c') => Some(Action::ToggleComplete),
+            KeyCode::Char('x') => Some(Action::ToggleLeafDone),
+            KeyCode::Char('s') => Some(Action::CycleStatus),
+            KeyCode::Char('C') => Some(Action::ArchiveCompleted),
+            KeyCode::Char('v') => Some(Action::ToggleShowArchived),
+            KeyCode::Char('t') => Some(Action::CycleColor),
+            KeyCode::Char('E') => Some(Action::StartEditEstimate),
+            KeyCode::Char('u') => Some(Action::StartEditDue),
+            KeyCode::Char('w') => Some(Action::ToggleWrapTitles),
+            KeyCode::Char('i') => Some(Action::ToggleShowIds),
+            KeyCode::Char('D') => Some(Action::ToggleShowDepth),
+            KeyCode::Char('S') => Some(Action::ToggleStatusSort),
+            KeyCode::Char('R') => Some(Action::StartReplace),
+            KeyCode::Char('g') => Some(Action::StartGoto),
+            KeyCode::Char('o') => Some(Action::OpenStaleRoots),
+            KeyCode::Char(':') => Some(Action::OpenCommandPalette),
+            KeyCode::Char('?') => Some(Action::ShowKeys),
+            KeyCode::Tab => Some(Action::NextDetailsTab),
             _ => None,
         },
-        AppMode::AddingNode | AppMode::EditingContent(_) | AppMode::EditingTitle(_) => match key {
+        AppMode::AddingNode | AppMode::EditingNode(_) => match key {
             KeyCode::Esc => Some(Action::Cancel),
+            KeyCode::Tab => Some(Action::SwitchInputField),
             KeyCode::Enter => Some(Action::Submit),
             KeyCode::Backspace => Some(Action::DeleteChar),
             KeyCode::Char(c) => Some(Action::Input(c)),
@@ -36,21 +120,364 @@ pub fn get_action(mode: &AppMode, key: KeyCode) -> Option<Action> {
             KeyCode::Char('m') | KeyCode::Char('M') => Some(Action::Submit),
             KeyCode::Char('j') | KeyCode::Down => Some(Action::MoveSelectionDown),
             KeyCode::Char('k') | KeyCode::Up => Some(Action::MoveSelectionUp),
+            KeyCode::Char('h') | KeyCode::Left => Some(Action::CollapseOrParent),
+            KeyCode::Char('l') | KeyCode::Right => Some(Action::ExpandOrChild),
+            _ => None,
+        },
+        AppMode::LinkingNode(_) => match key {
+            KeyCode::Esc => Some(Action::Cancel),
+            KeyCode::Char('n') | KeyCode::Enter => Some(Action::Submit),
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::MoveSelectionDown),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::MoveSelectionUp),
+            KeyCode::Char('h') | KeyCode::Left => Some(Action::CollapseOrParent),
+            KeyCode::Char('l') | KeyCode::Right => Some(Action::ExpandOrChild),
+            _ => None,
+        },
+        AppMode::CommandPalette => match key {
+            KeyCode::Esc => Some(Action::Cancel),
+            KeyCode::Enter => Some(Action::Submit),
+            KeyCode::Up => Some(Action::MoveSelectionUp),
+            KeyCode::Down => Some(Action::MoveSelectionDown),
+            KeyCode::Backspace => Some(Action::DeleteChar),
+            KeyCode::Char(c) => Some(Action::Input(c)),
+            _ => None,
+        },
+        AppMode::ReplaceFrom
+        | AppMode::ReplaceTo(_)
+        | AppMode::GotoInput
+        | AppMode::FailReason(_)
+        | AppMode::EstimateInput(_)
+        | AppMode::DueInput(_) => match key {
+            KeyCode::Esc => Some(Action::Cancel),
+            KeyCode::Enter => Some(Action::Submit),
+            KeyCode::Backspace => Some(Action::DeleteChar),
+            KeyCode::Char(c) => Some(Action::Input(c)),
+            _ => None,
+        },
+        AppMode::StaleRoots => match key {
+            KeyCode::Esc | KeyCode::Char('o') => Some(Action::Cancel),
+            KeyCode::Enter => Some(Action::Submit),
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::MoveSelectionDown),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::MoveSelectionUp),
+            KeyCode::Char('f') => Some(Action::StartFailNode),
+            KeyCode::Char('c') => Some(Action::ToggleComplete),
+            KeyCode::Char('d') => Some(Action::StartDeleteNode),
+            _ => None,
+        },
+        AppMode::RecentList => match key {
+            KeyCode::Esc => Some(Action::Cancel),
+            KeyCode::Enter => Some(Action::Submit),
+            KeyCode::Char('j') | KeyCode::Down => Some(Action::MoveSelectionDown),
+            KeyCode::Char('k') | KeyCode::Up => Some(Action::MoveSelectionUp),
+            _ => None,
+        },
+        AppMode::KeyHelp => match key {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('?') | KeyCode::Char('q') => {
+                Some(Action::Cancel)
+            }
             _ => None,
         },
         AppMode::Confirm(_) => match key {
-            KeyCode::Char('y') | KeyCode::Char('Y') => Some(Action::Submit),
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => Some(Action::Submit),
             KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => Some(Action::Cancel),
             _ => None,
         },
     }
 }
 
+/// 双键序列的前缀键
+const SEQUENCE_PREFIXES: [char; 2] = [']', '['];
+
+/// 双键序列对应的操作
+fn sequence_action(prefix: char, c: char) -> Option<Action> {
+    match (prefix, c) {
+        (']', 'o') => Some(Action::NextOverdue),
+        ('[', 'o') => Some(Action::PrevOverdue),
+        _ => None,
+    }
+}
+
+/// Normal 模式下触发该操作的按键提示
+pub fn key_hint(action: &Action) -> &'static str {
+    match action {
+        Action::Quit => "q",
+        Action::QuitDiscard => "Q",
+        Action::SaveNow => "Ctrl+S",
+        Action::MoveSelectionUp => "k/↑",
+        Action::MoveSelectionDown => "j/↓",
+        Action::CollapseOrParent => "h/←",
+        Action::ExpandOrChild => "l/→",
+        Action::ExpandOne => "+",
+        Action::CollapseOne => "-",
+        Action::ScrollRowLeft => "H",
+        Action::ScrollRowRight => "L",
+        Action::CenterSelection => "z",
+        Action::StartAddNode => "a",
+        Action::StartAddRoot => "A",
+        Action::StartEditContent => "e",
+        Action::OpenInEditor => "O",
+        Action::StartEditTitle => "r",
+        Action::StartMoveNode => "m",
+        Action::StartLinkNode => "n",
+        Action::StartDeleteNode => "d",
+        Action::StartFailNode => "f",
+        Action::StartPurgeFailed => "F",
+        Action::Undo => "Ctrl+Z",
+        Action::ToggleComplete => "c",
+        Action::ToggleLeafDone => "x",
+        Action::CycleStatus => "s",
+        Action::ArchiveCompleted => "C",
+        Action::ToggleShowArchived => "v",
+        Action::CycleColor => "t",
+        Action::StartEditEstimate => "E",
+        Action::StartEditDue => "u",
+        Action::ToggleWrapTitles => "w",
+        Action::ToggleShowIds => "i",
+        Action::ToggleShowDepth => "D",
+        Action::ToggleStatusSort => "S",
+        Action::ToggleToc => "Ctrl+T",
+        Action::StartReplace => "R",
+        Action::StartGoto => "g",
+        Action::OpenStaleRoots => "o",
+        Action::OpenRecent => "Ctrl+R",
+        Action::NextOverdue => "]o",
+        Action::PrevOverdue => "[o",
+        Action::CheckIntegrity => "Ctrl+I",
+        Action::ShowKeys => "?",
+        Action::OpenCommandPalette => ":",
+        Action::NextDetailsTab => "Tab",
+        _ => "",
+    }
+}
+
 /// 处理按键事件
-pub fn handle_key_event(app: &mut App, key: KeyCode) -> io::Result<bool> {
+pub fn handle_key_event(app: &mut App, key: KeyEvent) -> io::Result<DispatchResult> {
+    // Normal 模式下的双键序列：先记下前缀键，下一个键决定操作，不匹配时按普通按键处理
+    if app.mode == AppMode::Normal
+        && key.modifiers.difference(KeyModifiers::SHIFT).is_empty()
+        && let KeyCode::Char(c) = key.code
+    {
+        if let Some(action) = app
+            .pending_key
+            .take()
+            .and_then(|prefix| sequence_action(prefix, c))
+        {
+            return Ok(app.dispatch(action));
+        }
+        if SEQUENCE_PREFIXES.contains(&c) {
+            app.pending_key = Some(c);
+            return Ok(DispatchResult::Continue);
+        }
+    }
+    app.pending_key = None;
+
     if let Some(action) = get_action(&app.mode, key) {
         Ok(app.dispatch(action))
     } else {
-        Ok(false)
+        Ok(DispatchResult::Continue)
+    }
+}
+
+/// 依次处理一批按键事件（两次重绘之间积累的全部事件），遇到退出时立即停止
+pub fn handle_key_events(
+    app: &mut App,
+    keys: impl IntoIterator<Item = KeyEvent>,
+) -> io::Result<DispatchResult> {
+    for key in keys {
+        let result = handle_key_event(app, key)?;
+        if result.is_quit() {
+            return Ok(result);
+        }
+    }
+    Ok(DispatchResult::Continue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::state::ConfirmAction;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_ctrl_c_quits() {
+        let ctrl_c = key(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(global_action(&ctrl_c), Some(Action::Quit));
+        assert_eq!(get_action(&AppMode::Normal, ctrl_c), Some(Action::Quit));
+        assert_eq!(get_action(&AppMode::AddingNode, ctrl_c), Some(Action::Quit));
+        assert_eq!(
+            global_action(&key(KeyCode::Char('c'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            get_action(
+                &AppMode::AddingNode,
+                key(KeyCode::Char('s'), KeyModifiers::CONTROL)
+            ),
+            Some(Action::SaveNow)
+        );
+    }
+
+    #[test]
+    fn test_modifier_combinations() {
+        // 普通按键保持原有绑定
+        assert_eq!(
+            get_action(
+                &AppMode::Normal,
+                key(KeyCode::Char('j'), KeyModifiers::NONE)
+            ),
+            Some(Action::MoveSelectionDown)
+        );
+        // Ctrl/Alt 组合不会误触发普通绑定，也不会写入输入框
+        assert_eq!(
+            get_action(
+                &AppMode::Normal,
+                key(KeyCode::Char('a'), KeyModifiers::CONTROL)
+            ),
+            None
+        );
+        assert_eq!(
+            get_action(
+                &AppMode::AddingNode,
+                key(KeyCode::Char('x'), KeyModifiers::ALT)
+            ),
+            None
+        );
+        // Shift 通过大写字符体现
+        assert_eq!(
+            get_action(
+                &AppMode::MovingNode("id".to_string()),
+                key(KeyCode::Char('M'), KeyModifiers::SHIFT)
+            ),
+            Some(Action::Submit)
+        );
+        assert_eq!(
+            get_action(
+                &AppMode::AddingNode,
+                key(KeyCode::Char('A'), KeyModifiers::SHIFT)
+            ),
+            Some(Action::Input('A'))
+        );
+    }
+
+    #[test]
+    fn test_hl_navigation_keys() {
+        let none = KeyModifiers::NONE;
+        for mode in [AppMode::Normal, AppMode::MovingNode("id".to_string())] {
+            for code in [KeyCode::Char('h'), KeyCode::Left] {
+                assert_eq!(
+                    get_action(&mode, key(code, none)),
+                    Some(Action::CollapseOrParent)
+                );
+            }
+            for code in [KeyCode::Char('l'), KeyCode::Right] {
+                assert_eq!(
+                    get_action(&mode, key(code, none)),
+                    Some(Action::ExpandOrChild)
+                );
+            }
+        }
+        // 输入框中仍然是普通字符
+        assert_eq!(
+            get_action(&AppMode::AddingNode, key(KeyCode::Char('h'), none)),
+            Some(Action::Input('h'))
+        );
+    }
+
+    #[test]
+    fn test_line_editing_keys() {
+        let ctrl_w = key(KeyCode::Char('w'), KeyModifiers::CONTROL);
+        let ctrl_u = key(KeyCode::Char('u'), KeyModifiers::CONTROL);
+        assert_eq!(
+            get_action(&AppMode::AddingNode, ctrl_w),
+            Some(Action::DeleteWord)
+        );
+        assert_eq!(
+            get_action(&AppMode::CommandPalette, ctrl_u),
+            Some(Action::DeleteToLineStart)
+        );
+        // 普通模式下不生效
+        assert_eq!(get_action(&AppMode::Normal, ctrl_w), None);
+        let ctrl_z = key(KeyCode::Char('z'), KeyModifiers::CONTROL);
+        assert_eq!(get_action(&AppMode::Normal, ctrl_z), Some(Action::Undo));
+        assert_eq!(get_action(&AppMode::AddingNode, ctrl_z), None);
+    }
+
+    #[test]
+    fn test_drain_queued_keys() {
+        let tree = crate::models::TreeBuilder::new()
+            .leaf("a")
+            .leaf("b")
+            .leaf("c")
+            .leaf("d")
+            .leaf("e")
+            .build();
+        let mut app = App::new(tree, crate::config::Config::default());
+        let j = key(KeyCode::Char('j'), KeyModifiers::NONE);
+
+        assert!(!handle_key_events(&mut app, [j; 3]).unwrap().is_quit());
+        assert_eq!(app.selected_index, 3);
+
+        // 超出末尾时停在最后一个节点
+        assert!(!handle_key_events(&mut app, [j; 3]).unwrap().is_quit());
+        assert_eq!(app.selected_index, 4);
+
+        // 退出之后的事件不再处理
+        let q = key(KeyCode::Char('q'), KeyModifiers::NONE);
+        let k = key(KeyCode::Char('k'), KeyModifiers::NONE);
+        assert_eq!(
+            handle_key_events(&mut app, [k, q, k]).unwrap(),
+            DispatchResult::Quit
+        );
+        assert_eq!(app.selected_index, 3);
+    }
+
+    #[test]
+    fn test_key_sequences() {
+        let tree = crate::models::TreeBuilder::new()
+            .leaf("a")
+            .leaf("b")
+            .build();
+        let mut app = App::new(tree, crate::config::Config::default());
+        let char_key = |c| key(KeyCode::Char(c), KeyModifiers::NONE);
+
+        handle_key_events(&mut app, [char_key(']'), char_key('o')]).unwrap();
+        assert_eq!(app.message.as_deref(), Some("没有逾期的节点"));
+        assert_eq!(app.pending_key, None);
+
+        // 不匹配的第二个键按普通按键处理
+        handle_key_events(&mut app, [char_key('['), char_key('j')]).unwrap();
+        assert_eq!(app.selected_index, 1);
+        assert_eq!(app.pending_key, None);
+    }
+
+    #[test]
+    fn test_quick_add_keys() {
+        for modifiers in [KeyModifiers::SHIFT, KeyModifiers::ALT] {
+            assert_eq!(
+                get_action(&AppMode::AddingNode, key(KeyCode::Enter, modifiers)),
+                Some(Action::SubmitQuick)
+            );
+        }
+        assert_eq!(
+            get_action(
+                &AppMode::AddingNode,
+                key(KeyCode::Enter, KeyModifiers::NONE)
+            ),
+            Some(Action::Submit)
+        );
+    }
+
+    #[test]
+    fn test_enter_confirms() {
+        let mode = AppMode::Confirm(ConfirmAction::Delete("id".to_string()));
+        let enter = key(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(get_action(&mode, enter), Some(Action::Submit));
+        assert_eq!(
+            get_action(&mode, key(KeyCode::Esc, KeyModifiers::NONE)),
+            Some(Action::Cancel)
+        );
     }
 }
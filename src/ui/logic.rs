@@ -3,23 +3,142 @@
 //! 包含核心的 dispatch 逻辑和各种业务处理方法
 
 use super::actions::Action;
-use super::state::{App, AppMode, ConfirmAction, InputField};
-use crate::models::NodeStatus;
+use super::input::key_hint;
+use chrono::{Local, NaiveDate};
+use std::collections::{HashSet, VecDeque};
+
+use super::state::{
+    App, AppMode, ConfirmAction, FLASH_DURATION, InputField, RECENT_CAPACITY, parse_color,
+};
+use super::text::truncate_to_width;
+use crate::audit::{AuditRecord, log_action};
+use crate::config::DeleteMode;
+#[cfg(feature = "hooks")]
+use crate::hooks::render_command;
+use crate::i18n::{Key, Strings};
+use crate::models::{DisplayFilter, NodeStatus, TreeError};
+use crate::storage::{load_tree, save_tree};
+use crate::watch::{ReloadDecision, reload_decision};
+use std::io;
+use std::path::PathBuf;
+use std::time::Instant;
+use unicode_width::UnicodeWidthStr;
+
+/// 每次横向滚动选中行的列数
+const ROW_SCROLL_STEP: u16 = 4;
+
+/// dispatch 的结果：继续运行，或退出（保存 / 放弃修改）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchResult {
+    Continue,
+    Quit,
+    QuitDiscard,
+}
+
+impl DispatchResult {
+    /// 是否退出程序
+    pub fn is_quit(self) -> bool {
+        self != DispatchResult::Continue
+    }
+}
 
 impl App {
-    /// 核心逻辑分发
-    pub fn dispatch(&mut self, action: Action) -> bool {
+    /// 核心逻辑分发，返回是否退出；选中的节点变化时重置行内横向滚动
+    pub fn dispatch(&mut self, action: Action) -> DispatchResult {
+        let before = self.selected_node_id();
+        let quit = self.handle_action(action);
+        if self.selected_node_id() != before {
+            self.row_h_scroll = 0;
+        }
+        quit
+    }
+
+    fn handle_action(&mut self, action: Action) -> DispatchResult {
+        if self.readonly && action.is_mutating() {
+            self.reject(self.strings.get(Key::MsgReadonly).to_string());
+            return DispatchResult::Continue;
+        }
         match action {
-            Action::Quit => return true,
+            Action::Quit => return DispatchResult::Quit,
+            // 没有未保存的修改时无需确认
+            Action::QuitDiscard if self.tree.dirty => {
+                self.mode = AppMode::Confirm(ConfirmAction::QuitDiscard);
+            }
+            Action::QuitDiscard => return DispatchResult::QuitDiscard,
+            Action::SaveNow => self.save_now(),
+            Action::MoveSelectionUp if self.mode == AppMode::CommandPalette => {
+                self.palette_index = self.palette_index.saturating_sub(1);
+            }
+            Action::MoveSelectionDown if self.mode == AppMode::CommandPalette => {
+                if self.palette_index + 1 < filter_actions(&self.input_buffer, &self.strings).len()
+                {
+                    self.palette_index += 1;
+                }
+            }
+            Action::MoveSelectionUp if self.mode == AppMode::RecentList => {
+                self.palette_index = self.palette_index.saturating_sub(1);
+            }
+            Action::MoveSelectionDown if self.mode == AppMode::RecentList => {
+                if self.palette_index + 1 < self.recent_ids().len() {
+                    self.palette_index += 1;
+                }
+            }
+            Action::MoveSelectionUp if self.mode == AppMode::StaleRoots => self.move_stale(-1),
+            Action::MoveSelectionDown if self.mode == AppMode::StaleRoots => self.move_stale(1),
             Action::MoveSelectionUp => self.move_up(),
             Action::MoveSelectionDown => self.move_down(),
+            Action::CollapseOrParent => self.collapse_or_parent(),
+            Action::ExpandOrChild => self.expand_or_child(),
+            Action::ExpandOne => self.expand_one(),
+            Action::CollapseOne => self.collapse_one(),
 
             Action::StartAddNode => self.start_add_node(),
+            Action::StartAddRoot => self.start_add_root(),
             Action::StartEditContent => self.start_edit_content(),
+            Action::OpenInEditor => self.editor_request = self.selected_node_id(),
             Action::StartEditTitle => self.start_edit_title(),
             Action::StartMoveNode => self.start_move_node(),
+            Action::StartLinkNode => self.start_link_node(),
             Action::StartDeleteNode => self.start_delete_node(),
             Action::StartFailNode => self.start_fail_node(),
+            Action::Undo => self.undo(),
+            Action::StartPurgeFailed => {
+                self.mode = AppMode::Confirm(ConfirmAction::PurgeFailed);
+            }
+            Action::ToggleComplete => self.toggle_complete(),
+            Action::ToggleLeafDone => self.toggle_leaf_done(),
+            Action::CycleStatus => self.cycle_status(),
+            Action::ArchiveCompleted => self.archive_completed(),
+            Action::ToggleShowArchived => self.toggle_show_archived(),
+            Action::CycleColor => self.cycle_color(),
+            Action::StartEditEstimate => self.start_edit_estimate(),
+            Action::StartEditDue => self.start_edit_due(),
+            Action::ToggleWrapTitles => self.toggle_wrap_titles(),
+            Action::ToggleShowIds => self.toggle_show_ids(),
+            Action::ToggleShowDepth => self.toggle_show_depth(),
+            Action::ToggleStatusSort => self.toggle_status_sort(),
+            Action::ToggleToc => self.toggle_toc(),
+            Action::ScrollRowLeft => {
+                self.row_h_scroll = self.row_h_scroll.saturating_sub(ROW_SCROLL_STEP);
+            }
+            Action::ScrollRowRight => self.scroll_row_right(),
+            Action::CenterSelection => self.center_pending = true,
+            Action::StartReplace => self.start_replace(),
+            Action::StartGoto => self.start_goto(),
+            Action::NextOverdue => self.jump_to_overdue(true),
+            Action::PrevOverdue => self.jump_to_overdue(false),
+            Action::GotoId(id) => self.goto_id(&id),
+            Action::OpenStaleRoots => self.open_stale_roots(),
+            Action::OpenRecent => self.open_recent(),
+            Action::ReviewCompleted => self.review_completed(),
+            Action::MergeDuplicateRoots => {
+                self.mode = AppMode::Confirm(ConfirmAction::MergeRoots);
+            }
+            Action::CheckIntegrity => self.check_integrity(),
+            Action::ShowKeys => self.mode = AppMode::KeyHelp,
+            Action::OpenCommandPalette => self.open_command_palette(),
+            Action::SwitchInputField => self.switch_input_field(),
+            Action::NextDetailsTab => self.details_tab = self.details_tab.next(),
 
             Action::Cancel => self.cancel(),
 
@@ -32,41 +151,92 @@ impl App {
                     }
                     InputField::Content => self.confirm_add_node(),
                 },
-                AppMode::EditingContent(id) => {
+                AppMode::EditingNode(id) => {
                     let id = id.clone();
-                    self.confirm_edit_content(id);
+                    self.confirm_edit_node(id);
                 }
-                AppMode::EditingTitle(id) => {
+                AppMode::LinkingNode(id) => {
                     let id = id.clone();
-                    self.confirm_edit_title(id);
+                    self.confirm_link_node(id);
                 }
                 AppMode::MovingNode(id) => {
                     let id = id.clone();
                     self.confirm_move_node(id);
                 }
+                AppMode::ReplaceFrom => self.confirm_replace_from(),
+                AppMode::ReplaceTo(from) => {
+                    let from = from.clone();
+                    self.confirm_replace_to(from);
+                }
+                AppMode::FailReason(id) => {
+                    let id = id.clone();
+                    self.confirm_fail_node(id);
+                }
+                AppMode::EstimateInput(id) => {
+                    let id = id.clone();
+                    self.confirm_estimate(id);
+                }
+                AppMode::DueInput(id) => {
+                    let id = id.clone();
+                    self.confirm_due(id);
+                }
+                AppMode::GotoInput => {
+                    let id = std::mem::take(&mut self.input_buffer);
+                    self.mode = AppMode::Normal;
+                    self.goto_id(id.trim());
+                }
+                AppMode::Confirm(ConfirmAction::QuitDiscard) => {
+                    self.mode = AppMode::Normal;
+                    return DispatchResult::QuitDiscard;
+                }
                 AppMode::Confirm(_) => self.execute_confirm(),
+                AppMode::CommandPalette => return self.execute_palette_selection(),
+                AppMode::StaleRoots => self.mode = AppMode::Normal,
+                AppMode::RecentList => self.jump_to_recent(),
+                AppMode::KeyHelp => self.mode = AppMode::Normal,
                 AppMode::Normal => {}
             },
 
+            Action::SubmitQuick => {
+                if self.mode == AppMode::AddingNode
+                    && self.input_field == InputField::Title
+                    && !self.input_buffer.is_empty()
+                {
+                    // 不进入内容输入，内容留空
+                    self.temp_title = std::mem::take(&mut self.input_buffer);
+                    self.confirm_add_node();
+                }
+            }
+
             Action::Input(c) => {
-                if matches!(
-                    self.mode,
-                    AppMode::AddingNode | AppMode::EditingContent(_) | AppMode::EditingTitle(_)
-                ) {
+                if self.mode.is_text_input() {
                     self.input_buffer.push(c);
+                    self.palette_index = 0;
                 }
             }
 
             Action::DeleteChar => {
-                if matches!(
-                    self.mode,
-                    AppMode::AddingNode | AppMode::EditingContent(_) | AppMode::EditingTitle(_)
-                ) {
+                if self.mode.is_text_input() {
                     self.input_buffer.pop();
+                    self.palette_index = 0;
+                }
+            }
+
+            Action::DeleteWord => {
+                if self.mode.is_text_input() {
+                    delete_word_before_end(&mut self.input_buffer);
+                    self.palette_index = 0;
+                }
+            }
+
+            Action::DeleteToLineStart => {
+                if self.mode.is_text_input() {
+                    delete_to_line_start(&mut self.input_buffer);
+                    self.palette_index = 0;
                 }
             }
         }
-        false
+        DispatchResult::Continue
     }
 
     // ============ 导航相关 ============
@@ -85,10 +255,116 @@ impl App {
         }
     }
 
+    /// 选中行的标题向左滚动，最多滚到只剩最后一个字符
+    fn scroll_row_right(&mut self) {
+        if let Some(node) = self.selected_node() {
+            let max = node.title.width().saturating_sub(1) as u16;
+            self.row_h_scroll = (self.row_h_scroll + ROW_SCROLL_STEP).min(max);
+        }
+    }
+
+    /// 折叠选中节点；叶子或已折叠的节点则跳到父节点
+    pub fn collapse_or_parent(&mut self) {
+        let Some(node) = self.selected_node() else {
+            return;
+        };
+        let id = node.id.clone();
+        let parent = (!node.is_root()).then(|| node.parent_id.clone());
+        if !self.tree.is_leaf(&id) && self.collapse(&id) {
+            self.refresh_display_list();
+        } else if let Some(parent) = parent {
+            self.select_node(&parent);
+        }
+    }
+
+    /// 展开选中节点；已展开时跳到第一个可见的子节点
+    pub fn expand_or_child(&mut self) {
+        let Some(id) = self.selected_node_id() else {
+            return;
+        };
+        if self.expand(&id) {
+            self.refresh_display_list();
+        } else if let Some((depth, _)) = self.display_list.get(self.selected_index)
+            && self
+                .display_list
+                .get(self.selected_index + 1)
+                .is_some_and(|(next, _)| next > depth)
+        {
+            self.selected_index += 1;
+        }
+    }
+
+    /// 只展开选中节点一层：其有子节点的子节点折叠起来，逐层深入；已展开时不变
+    pub fn expand_one(&mut self) {
+        let Some(id) = self.selected_node_id() else {
+            return;
+        };
+        if self.tree.is_leaf(&id) || !self.is_collapsed(&id) {
+            return;
+        }
+        self.expand(&id);
+        let children: Vec<String> = self.tree.children_map.get(&id).cloned().unwrap_or_default();
+        for child in children {
+            if !self.tree.is_leaf(&child) {
+                self.collapse(&child);
+            }
+        }
+        self.refresh_display_list();
+    }
+
+    /// 只折叠选中节点，叶子或已折叠时不变
+    pub fn collapse_one(&mut self) {
+        let Some(id) = self.selected_node_id() else {
+            return;
+        };
+        if !self.tree.is_leaf(&id) && self.collapse(&id) {
+            self.refresh_display_list();
+        }
+    }
+
+    /// 切换目录模式：打开时只列出根节点并选中当前节点所在的根；
+    /// 关闭时展开选中的根节点并折叠其他根节点，聚焦到该子树
+    pub fn toggle_toc(&mut self) {
+        let root = self
+            .selected_node_id()
+            .and_then(|id| self.tree.root_of(&id));
+        self.display_filter.roots_only = !self.display_filter.roots_only;
+        if !self.display_filter.roots_only
+            && let Some(root) = &root
+        {
+            for other in &self.tree.root_ids {
+                if other != root && !self.tree.is_leaf(other) {
+                    self.display_filter.collapsed.insert(other.clone());
+                    self.expanded.remove(other);
+                }
+            }
+            self.expand(root);
+        }
+        self.select_after_refresh = root;
+        self.refresh_display_list();
+        let key = if self.display_filter.roots_only {
+            Key::MsgTocOn
+        } else {
+            Key::MsgTocOff
+        };
+        self.message = Some(self.strings.get(key).to_string());
+    }
+
     // ============ 添加节点相关 ============
 
-    /// 开始添加节点
+    /// 开始添加节点，父节点为当前选中的节点
     pub fn start_add_node(&mut self) {
+        self.open_add_dialog(self.selected_node_id());
+    }
+
+    /// 开始添加根节点，与当前选中的节点无关
+    pub fn start_add_root(&mut self) {
+        self.open_add_dialog(None);
+    }
+
+    /// 打开添加对话框，`parent` 为 None 时添加为根节点
+    fn open_add_dialog(&mut self, parent: Option<String>) {
+        self.add_parent = parent;
         self.mode = AppMode::AddingNode;
         self.input_buffer.clear();
         self.input_field = InputField::Title;
@@ -102,119 +378,243 @@ impl App {
         self.input_field = InputField::Content;
     }
 
-    /// 确认添加节点
+    /// 确认添加节点；开启 `sticky_add` 时添加成功后留在对话框中，继续在同一父节点下添加
     pub fn confirm_add_node(&mut self) {
         let title = self.temp_title.clone();
         let content = self.input_buffer.clone();
-        let parent_id = self.selected_node_id();
-        self.tree.add_node(title, content, parent_id);
+        let parent_id = self.add_parent.clone();
+        let added = match self.tree.add_node(title, content, parent_id) {
+            Ok(id) => {
+                self.message = Some(self.strings.get(Key::MsgAdded).to_string());
+                self.audit_node("add", &id);
+                push_recent(&mut self.recent, id.clone());
+                self.select_after_refresh = Some(id);
+                true
+            }
+            Err(e) => {
+                self.reject(self.strings.tree_error(&e));
+                false
+            }
+        };
         self.refresh_display_list();
-        self.mode = AppMode::Normal;
         self.temp_title.clear();
-        self.message = Some("节点已添加".to_string());
+        if added && self.config.sticky_add {
+            self.message = Some(self.strings.get(Key::MsgAddedSticky).to_string());
+            self.input_buffer.clear();
+            self.input_field = InputField::Title;
+        } else {
+            self.mode = AppMode::Normal;
+        }
     }
 
-    // ============ 编辑内容相关 ============
+    // ============ 编辑节点相关 ============
 
-    /// 开始编辑内容
+    /// 开始编辑内容（打开编辑对话框并聚焦内容）
     pub fn start_edit_content(&mut self) {
+        self.start_edit_node(InputField::Content);
+    }
+
+    /// 开始编辑标题（打开编辑对话框并聚焦标题）
+    pub fn start_edit_title(&mut self) {
+        self.start_edit_node(InputField::Title);
+    }
+
+    /// 打开标题与内容合并的编辑对话框，当前字段的值放在 input_buffer 中
+    fn start_edit_node(&mut self, field: InputField) {
         if let Some(node) = self.selected_node() {
-            let id = node.id.clone();
-            let content = node.content.clone();
-            self.mode = AppMode::EditingContent(id);
-            self.input_buffer = content;
+            let (id, title, content) = (node.id.clone(), node.title.clone(), node.content.clone());
+            self.temp_title = title;
+            self.temp_content = content;
+            self.input_buffer.clear();
+            self.mode = AppMode::EditingNode(id);
+            self.input_field = field;
+            self.load_input_field();
         }
     }
 
-    /// 确认编辑内容
-    pub fn confirm_edit_content(&mut self, node_id: String) {
-        if let Some(node) = self.tree.nodes.get_mut(&node_id) {
-            node.content = self.input_buffer.clone();
+    /// 在标题与内容之间切换输入焦点
+    pub fn switch_input_field(&mut self) {
+        if !matches!(self.mode, AppMode::EditingNode(_)) {
+            return;
         }
-        self.mode = AppMode::Normal;
-        self.input_buffer.clear();
-        self.message = Some("内容已更新".to_string());
+        self.store_input_field();
+        self.input_field = match self.input_field {
+            InputField::Title => InputField::Content,
+            InputField::Content => InputField::Title,
+        };
+        self.load_input_field();
     }
 
-    // ============ 编辑标题相关 ============
-
-    /// 开始编辑标题
-    pub fn start_edit_title(&mut self) {
-        if let Some(node) = self.selected_node() {
-            let id = node.id.clone();
-            let title = node.title.clone();
-            self.mode = AppMode::EditingTitle(id);
-            self.input_buffer = title;
+    /// 将 input_buffer 写回当前字段的暂存区
+    fn store_input_field(&mut self) {
+        let value = std::mem::take(&mut self.input_buffer);
+        match self.input_field {
+            InputField::Title => self.temp_title = value,
+            InputField::Content => self.temp_content = value,
         }
     }
 
-    /// 确认编辑标题
-    pub fn confirm_edit_title(&mut self, node_id: String) {
-        if let Some(node) = self.tree.nodes.get_mut(&node_id) {
-            node.title = self.input_buffer.clone();
+    /// 将当前字段的暂存值取到 input_buffer
+    fn load_input_field(&mut self) {
+        self.input_buffer = match self.input_field {
+            InputField::Title => std::mem::take(&mut self.temp_title),
+            InputField::Content => std::mem::take(&mut self.temp_content),
+        };
+    }
+
+    /// 确认编辑，同时提交标题与内容
+    pub fn confirm_edit_node(&mut self, node_id: String) {
+        self.store_input_field();
+        if self.temp_title.is_empty() {
+            self.input_field = InputField::Title;
+            self.load_input_field();
+            self.message = Some(self.strings.get(Key::MsgTitleEmpty).to_string());
+            return;
+        }
+
+        let title = std::mem::take(&mut self.temp_title);
+        let content = std::mem::take(&mut self.temp_content);
+        if let Some(node) = self.tree.nodes.get(&node_id) {
+            let old_title = node.title.clone();
+            let title_changed = old_title != title;
+            let content_changed = node.content != content;
+            if title_changed {
+                self.message = Some(self.strings.format(
+                    Key::MsgRenamed,
+                    &[&rename_diff_part(&old_title), &rename_diff_part(&title)],
+                ));
+                self.tree.update_title(&node_id, title);
+                // 单级撤销：只保留最近一次删除或重命名
+                self.last_rename = Some((node_id.clone(), old_title));
+                self.last_deleted = None;
+            } else if content_changed {
+                self.message = Some(self.strings.get(Key::MsgUpdated).to_string());
+            }
+            if content_changed {
+                self.tree.update_content(&node_id, content);
+            }
+            if title_changed || content_changed {
+                self.audit_node("edit", &node_id);
+                push_recent(&mut self.recent, node_id);
+            } else {
+                self.message = Some(self.strings.get(Key::MsgUnchanged).to_string());
+            }
         }
         self.mode = AppMode::Normal;
         self.input_buffer.clear();
-        self.message = Some("标题已更新".to_string());
+    }
+
+    /// 写回外部编辑器的编辑结果；编辑器失败或结果为空时保留原内容
+    pub fn apply_external_edit(&mut self, node_id: String, result: io::Result<String>) {
+        let content = match result {
+            Ok(content) => content,
+            Err(e) => {
+                self.reject(self.strings.format(Key::MsgEditorFailed, &[&e]));
+                return;
+            }
+        };
+        let Some(node) = self.tree.nodes.get(&node_id) else {
+            return;
+        };
+        if content.trim().is_empty() {
+            self.message = Some(self.strings.get(Key::MsgEditorEmpty).to_string());
+        } else if node.content == content {
+            self.message = Some(self.strings.get(Key::MsgUnchanged).to_string());
+        } else {
+            self.tree.update_content(&node_id, content);
+            self.message = Some(self.strings.get(Key::MsgUpdated).to_string());
+            self.audit_node("edit", &node_id);
+            push_recent(&mut self.recent, node_id);
+        }
     }
 
     // ============ 移动节点相关 ============
 
     /// 开始移动节点
     pub fn start_move_node(&mut self) {
+        // 只有一个可见节点时，唯一的目标就是它自己
+        if self.display_list.len() < 2 {
+            if !self.display_list.is_empty() {
+                self.message = Some(self.strings.get(Key::MsgNoMoveTarget).to_string());
+            }
+            return;
+        }
         if let Some(id) = self.selected_node_id() {
             self.mode = AppMode::MovingNode(id);
-            self.message = Some("请选择新的父节点（或根节点），按 'm' 确认移动".to_string());
+            self.message = Some(self.strings.get(Key::MsgPickParent).to_string());
         }
     }
 
-    /// 确认移动节点
+    /// 确认移动节点，子树较大时先弹出确认
     pub fn confirm_move_node(&mut self, node_id: String) {
-        let new_parent_id = self.selected_node_id();
+        let target = self.selected_node_id();
+        let valid_target = target
+            .as_ref()
+            .is_none_or(|t| *t != node_id && !self.tree.is_descendant(&node_id, t));
+        if valid_target
+            && needs_move_confirm(
+                self.tree.subtree_size(&node_id),
+                self.config.move_confirm_threshold,
+            )
+        {
+            self.mode = AppMode::Confirm(ConfirmAction::Move {
+                node: node_id,
+                target,
+            });
+            return;
+        }
+        self.perform_move(node_id, target);
+    }
 
-        // 防止将节点移动到自己或自己的子节点下
-        if let Some(new_parent) = &new_parent_id {
-            if new_parent == &node_id {
-                self.message = Some("不能将节点移动到自己下面".to_string());
-                self.mode = AppMode::Normal;
-                return;
+    /// 执行移动并回到普通模式
+    fn perform_move(&mut self, node_id: String, new_parent_id: Option<String>) {
+        match self.tree.move_node(&node_id, new_parent_id.as_deref()) {
+            Ok(true) => {
+                self.message = Some(self.strings.get(Key::MsgMoved).to_string());
+                self.audit_node("move", &node_id);
+                self.select_after_refresh = Some(node_id);
             }
-            // 检查是否是移动到自己的后代
-            let descendants = self.tree.get_all_descendants(&node_id);
-            if descendants.contains(new_parent) {
-                self.message = Some("不能将节点移动到其子节点下".to_string());
-                self.mode = AppMode::Normal;
-                return;
+            Ok(false) => {
+                self.message = Some(self.strings.get(Key::MsgPositionUnchanged).to_string())
             }
+            Err(e) => self.reject(self.strings.tree_error(&e)),
         }
+        self.refresh_display_list();
+        self.mode = AppMode::Normal;
+    }
 
-        // 执行移动
-        if let Some(node) = self.tree.nodes.get_mut(&node_id) {
-            // 从旧父节点中移除
-            if node.is_root() {
-                self.tree.root_ids.retain(|id| id != &node_id);
-            } else if let Some(siblings) = self.tree.children_map.get_mut(&node.parent_id) {
-                siblings.retain(|id| id != &node_id);
-            }
-
-            // 更新父节点
-            node.parent_id = new_parent_id.clone().unwrap_or_default();
+    // ============ 关联节点相关 ============
 
-            // 添加到新父节点
-            if node.is_root() {
-                self.tree.root_ids.push(node_id.clone());
-            } else {
-                self.tree
-                    .children_map
-                    .entry(node.parent_id.clone())
-                    .or_default()
-                    .push(node_id.clone());
+    /// 开始为选中的节点添加关联，之后导航到要关联的节点
+    pub fn start_link_node(&mut self) {
+        if self.display_list.len() < 2 {
+            if !self.display_list.is_empty() {
+                self.message = Some(self.strings.get(Key::MsgNoLinkTarget).to_string());
             }
+            return;
+        }
+        if let Some(id) = self.selected_node_id() {
+            self.mode = AppMode::LinkingNode(id);
+            self.message = Some(self.strings.get(Key::MsgPickLinkTarget).to_string());
         }
+    }
 
-        self.refresh_display_list();
+    /// 将当前选中的节点关联到发起关联的节点，完成后选回发起的节点
+    pub fn confirm_link_node(&mut self, node_id: String) {
         self.mode = AppMode::Normal;
-        self.message = Some("节点已移动".to_string());
+        let Some(target) = self.selected_node_id() else {
+            return;
+        };
+        match self.tree.add_link(&node_id, &target) {
+            Ok(true) => {
+                self.message = Some(self.strings.get(Key::MsgLinked).to_string());
+                self.audit_node("link", &node_id);
+            }
+            Ok(false) => self.message = Some(self.strings.get(Key::MsgAlreadyLinked).to_string()),
+            Err(e) => self.reject(self.strings.tree_error(&e)),
+        }
+        self.select_after_refresh = Some(node_id);
+        self.refresh_display_list();
     }
 
     // ============ 删除/失败节点相关 ============
@@ -226,21 +626,465 @@ impl App {
         }
     }
 
+    /// 撤销最近一次重命名，或将最近一次删除的子树恢复到原位置
+    pub fn undo(&mut self) {
+        if let Some((id, old_title)) = self.last_rename.take() {
+            self.undo_rename(id, old_title);
+            return;
+        }
+        let Some(removed) = self.last_deleted.take() else {
+            self.reject(self.strings.get(Key::MsgNothingToUndo).to_string());
+            return;
+        };
+        let count = removed.nodes.len();
+        match self.tree.reinsert_nodes(removed) {
+            Ok(id) => {
+                self.message = Some(self.strings.format(Key::MsgDeleteUndone, &[&count]));
+                self.audit_node("undo", &id);
+                self.refresh_display_list();
+                self.reveal(&id);
+                self.select_node(&id);
+            }
+            Err(TreeError::NotFound) => {
+                self.reject(self.strings.get(Key::MsgUndoFailed).to_string())
+            }
+            Err(e) => self.reject(self.strings.tree_error(&e)),
+        }
+    }
+
+    /// 恢复重命名前的标题
+    fn undo_rename(&mut self, id: String, old_title: String) {
+        if !self.tree.nodes.contains_key(&id) {
+            self.reject(self.strings.get(Key::ErrNotFound).to_string());
+            return;
+        }
+        self.message = Some(
+            self.strings
+                .format(Key::MsgRenameUndone, &[&rename_diff_part(&old_title)]),
+        );
+        self.tree.update_title(&id, old_title);
+        self.audit_node("undo_rename", &id);
+        self.select_node(&id);
+    }
+
     /// 开始标记节点失败
     pub fn start_fail_node(&mut self) {
         if let Some(node) = self.selected_node() {
             match node.status {
-                NodeStatus::Active => {
+                NodeStatus::Active | NodeStatus::Completed | NodeStatus::Paused => {
                     let id = node.id.clone();
                     self.mode = AppMode::Confirm(ConfirmAction::Fail(id));
                 }
                 NodeStatus::Failed => {
                     let id = node.id.clone();
                     self.tree.recover_node(&id);
-                    self.message = Some("节点已恢复为活跃状态".to_string());
+                    self.message = Some(self.strings.get(Key::MsgRecovered).to_string());
+                    self.audit_node("recover", &id);
+                }
+            }
+        }
+    }
+
+    /// 按配置自动将长期未更新的活跃叶子节点标记为失败（启动时调用）
+    pub fn auto_fail_stale(&mut self) {
+        let Some(days) = self.config.auto_fail_days.filter(|_| !self.readonly) else {
+            return;
+        };
+        let failed = self.tree.auto_fail_stale(days);
+        if failed.is_empty() {
+            return;
+        }
+        for id in &failed {
+            self.audit_node("auto_fail", id);
+        }
+        self.refresh_display_list();
+        self.message = Some(
+            self.strings
+                .format(Key::MsgAutoFailed, &[&failed.len(), &days]),
+        );
+    }
+
+    /// 以输入的原因标记节点失败（原因写入节点日志）
+    pub fn confirm_fail_node(&mut self, node_id: String) {
+        let reason = std::mem::take(&mut self.input_buffer);
+        let deleted = self.tree.fail_node_with_reason(&node_id, &reason);
+        self.message = Some(self.strings.format(Key::MsgFailed, &[&deleted.len()]));
+        self.audit_node("fail", &node_id);
+        self.refresh_display_list();
+        self.mode = AppMode::Normal;
+    }
+
+    /// 校验树结构：没有问题时提示，有问题时列出并询问是否修复（只读模式下只提示数量）
+    pub fn check_integrity(&mut self) {
+        let issues = self.tree.validate();
+        if issues.is_empty() {
+            self.message = Some(
+                self.strings
+                    .format(Key::MsgIntegrityOk, &[&self.tree.nodes.len()]),
+            );
+        } else if self.readonly {
+            self.message = Some(
+                self.strings
+                    .format(Key::MsgIntegrityIssues, &[&issues.len()]),
+            );
+        } else {
+            self.mode = AppMode::Confirm(ConfirmAction::RepairTree(issues));
+        }
+    }
+
+    // ============ 完成/归档相关 ============
+
+    /// 切换选中节点的完成状态
+    pub fn toggle_complete(&mut self) {
+        if let Some(id) = self.selected_node_id() {
+            match self.tree.toggle_complete(&id) {
+                Some(NodeStatus::Completed) => {
+                    self.message = Some(self.strings.get(Key::MsgCompleted).to_string());
+                    self.audit_node("complete", &id);
+                    self.run_complete_hook(&id);
+                }
+                Some(_) => {
+                    self.message = Some(self.strings.get(Key::MsgRecovered).to_string());
+                    self.audit_node("uncomplete", &id);
+                }
+                None => self.reject(self.strings.get(Key::MsgRecoverFirst).to_string()),
+            }
+            self.refresh_display_list();
+        }
+    }
+
+    /// 像复选框一样勾选 / 取消勾选选中的叶子节点，不需要确认
+    pub fn toggle_leaf_done(&mut self) {
+        if let Some(id) = self.selected_node_id() {
+            match self.tree.toggle_leaf_done(&id) {
+                Some(NodeStatus::Completed) => {
+                    self.message = Some(self.strings.get(Key::MsgCompleted).to_string());
+                    self.audit_node("complete", &id);
+                    self.run_complete_hook(&id);
+                }
+                Some(_) => {
+                    self.message = Some(self.strings.get(Key::MsgRecovered).to_string());
+                    self.audit_node("uncomplete", &id);
+                }
+                None => self.reject(self.strings.get(Key::MsgCheckLeafOnly).to_string()),
+            }
+            self.refresh_display_list();
+        }
+    }
+
+    /// 在活跃、已完成、暂停之间循环切换选中节点的状态
+    pub fn cycle_status(&mut self) {
+        if let Some(id) = self.selected_node_id() {
+            match self.tree.cycle_status(&id) {
+                Some(status) => {
+                    let name = self.strings.status(&status);
+                    self.message = Some(self.strings.format(Key::MsgStatusSet, &[&name]));
+                    self.audit_node("status", &id);
+                    if status == NodeStatus::Completed {
+                        self.run_complete_hook(&id);
+                    }
                 }
+                None => self.reject(self.strings.get(Key::MsgRecoverFirst).to_string()),
+            }
+            self.refresh_display_list();
+        }
+    }
+
+    /// 在状态栏列出本周（按 `week_start` 计算周的起点）完成的节点
+    pub fn review_completed(&mut self) {
+        let week_start = self.config.week_start.first_day(Local::now().date_naive());
+        let titles: Vec<&str> = self
+            .tree
+            .completed_since(week_start)
+            .iter()
+            .map(|node| node.title.as_str())
+            .collect();
+        self.message = Some(self.strings.format(
+            Key::MsgCompletedWeek,
+            &[&week_start, &titles.len(), &titles.join("、")],
+        ));
+    }
+
+    /// 归档所有已完成节点
+    pub fn archive_completed(&mut self) {
+        let count = self.tree.archive_completed();
+        self.refresh_display_list();
+        self.message = Some(self.strings.format(Key::MsgArchived, &[&count]));
+        if count > 0 {
+            self.audit(
+                "archive_completed",
+                "",
+                &self.strings.format(Key::AuditNodes, &[&count]),
+            );
+        }
+    }
+
+    /// 切换是否显示已归档节点
+    pub fn toggle_show_archived(&mut self) {
+        self.display_filter.show_archived = !self.display_filter.show_archived;
+        self.refresh_display_list();
+        let key = if self.display_filter.show_archived {
+            Key::MsgShowArchived
+        } else {
+            Key::MsgHideArchived
+        };
+        self.message = Some(self.strings.get(key).to_string());
+    }
+
+    /// 将选中节点的颜色标签切换为调色板中的下一个
+    pub fn cycle_color(&mut self) {
+        if let Some(node) = self.selected_node() {
+            let id = node.id.clone();
+            let color = next_tag_color(node.color.as_deref());
+            self.message = Some(match &color {
+                Some(color) => self.strings.format(Key::MsgColorSet, &[color]),
+                None => self.strings.get(Key::MsgColorCleared).to_string(),
+            });
+            match color.as_deref().and_then(parse_color) {
+                Some(parsed) => self.title_colors.insert(id.clone(), parsed),
+                None => self.title_colors.remove(&id),
+            };
+            self.tree.set_color(&id, color);
+            self.audit_node("color", &id);
+        }
+    }
+
+    /// 开始输入选中节点的预估工作量（已有预估时预填）
+    pub fn start_edit_estimate(&mut self) {
+        if let Some(node) = self.selected_node() {
+            let (id, estimate) = (node.id.clone(), node.estimate);
+            self.input_buffer = if estimate > 0.0 {
+                estimate.to_string()
+            } else {
+                String::new()
+            };
+            self.mode = AppMode::EstimateInput(id);
+        }
+    }
+
+    /// 保存输入的预估工作量，留空表示清除；不是非负数时保持输入状态并提示
+    pub fn confirm_estimate(&mut self, node_id: String) {
+        let input = self.input_buffer.trim();
+        let estimate = if input.is_empty() {
+            Some(0.0)
+        } else {
+            input
+                .parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite() && *v >= 0.0)
+        };
+        let Some(estimate) = estimate else {
+            self.reject(self.strings.format(Key::MsgInvalidEstimate, &[&input]));
+            return;
+        };
+        self.tree.set_estimate(&node_id, estimate);
+        self.audit_node("estimate", &node_id);
+        self.message = Some(self.strings.format(Key::MsgEstimateSet, &[&estimate]));
+        self.input_buffer.clear();
+        self.mode = AppMode::Normal;
+    }
+
+    /// 开始输入选中节点的截止日期（已有时预填）
+    pub fn start_edit_due(&mut self) {
+        if let Some(node) = self.selected_node() {
+            let (id, due) = (node.id.clone(), node.due);
+            self.input_buffer = due
+                .map(|d| d.format(DUE_FORMAT).to_string())
+                .unwrap_or_default();
+            self.mode = AppMode::DueInput(id);
+        }
+    }
+
+    /// 保存输入的截止日期，留空表示清除；格式不对时保持输入状态并提示
+    pub fn confirm_due(&mut self, node_id: String) {
+        let input = self.input_buffer.trim();
+        let Some(due) = parse_due(input) else {
+            self.reject(self.strings.format(Key::MsgInvalidDue, &[&input]));
+            return;
+        };
+        self.tree.set_due(&node_id, due);
+        self.audit_node("due", &node_id);
+        self.message = Some(match due {
+            Some(due) => self
+                .strings
+                .format(Key::MsgDueSet, &[&due.format(DUE_FORMAT)]),
+            None => self.strings.get(Key::MsgDueCleared).to_string(),
+        });
+        self.input_buffer.clear();
+        self.mode = AppMode::Normal;
+    }
+
+    /// 切换长标题折行 / 截断显示
+    pub fn toggle_wrap_titles(&mut self) {
+        self.config.wrap_titles = !self.config.wrap_titles;
+        let key = if self.config.wrap_titles {
+            Key::MsgWrapOn
+        } else {
+            Key::MsgWrapOff
+        };
+        self.message = Some(self.strings.get(key).to_string());
+    }
+
+    /// 切换是否显示节点 ID 前缀
+    pub fn toggle_show_ids(&mut self) {
+        self.show_ids = !self.show_ids;
+        let key = if self.show_ids {
+            Key::MsgIdsShown
+        } else {
+            Key::MsgIdsHidden
+        };
+        self.message = Some(self.strings.get(key).to_string());
+    }
+
+    /// 切换是否在标题前显示层级
+    pub fn toggle_show_depth(&mut self) {
+        self.show_depth = !self.show_depth;
+        let key = if self.show_depth {
+            Key::MsgDepthShown
+        } else {
+            Key::MsgDepthHidden
+        };
+        self.message = Some(self.strings.get(key).to_string());
+    }
+
+    /// 切换子节点是否按状态排列，选中的节点保持不变
+    pub fn toggle_status_sort(&mut self) {
+        self.display_filter.sort_by_status = !self.display_filter.sort_by_status;
+        self.refresh_display_list();
+        let key = if self.display_filter.sort_by_status {
+            Key::MsgStatusSortOn
+        } else {
+            Key::MsgStatusSortOff
+        };
+        self.message = Some(self.strings.get(key).to_string());
+    }
+
+    // ============ 查找替换相关 ============
+
+    /// 开始查找替换
+    pub fn start_replace(&mut self) {
+        self.mode = AppMode::ReplaceFrom;
+        self.input_buffer.clear();
+    }
+
+    /// 输入查找文字后，转入替换文字输入
+    pub fn confirm_replace_from(&mut self) {
+        if self.input_buffer.is_empty() {
+            return;
+        }
+        let from = std::mem::take(&mut self.input_buffer);
+        self.mode = AppMode::ReplaceTo(from);
+    }
+
+    /// 输入替换文字后，有匹配时进入预览确认
+    pub fn confirm_replace_to(&mut self, from: String) {
+        let to = std::mem::take(&mut self.input_buffer);
+        if self.tree.find_text(&from).is_empty() {
+            self.message = Some(self.strings.format(Key::MsgNoMatch, &[&from]));
+            self.mode = AppMode::Normal;
+        } else {
+            self.mode = AppMode::Confirm(ConfirmAction::Replace { from, to });
+        }
+    }
+
+    // ============ 跳转相关 ============
+
+    /// 开始输入要跳转的节点 ID
+    pub fn start_goto(&mut self) {
+        self.mode = AppMode::GotoInput;
+        self.input_buffer.clear();
+    }
+
+    /// 跳转到指定 ID 的节点，必要时先使其可见
+    pub fn goto_id(&mut self, id: &str) {
+        if self.reveal(id) && self.select_node(id) {
+            self.message = None;
+        } else {
+            self.reject(self.strings.format(Key::MsgNodeNotFound, &[&id]));
+        }
+    }
+
+    /// 按显示顺序跳到下一个（或上一个）逾期节点，首尾循环，被折叠时展开其祖先
+    pub fn jump_to_overdue(&mut self, forward: bool) {
+        let today = Local::now().date_naive();
+        // 在不考虑折叠的完整顺序中查找，使折叠子树中的逾期节点也能被找到
+        let filter = DisplayFilter {
+            collapsed: HashSet::new(),
+            roots_only: false,
+            ..self.display_filter.clone()
+        };
+        let (order, overdue): (Vec<String>, Vec<bool>) = self
+            .tree
+            .flatten_filtered(&filter)
+            .into_iter()
+            .map(|(_, node)| (node.id.clone(), node.is_overdue(today)))
+            .unzip();
+        let current = self
+            .selected_node_id()
+            .and_then(|id| order.iter().position(|shown| *shown == id));
+        let Some(index) = cycle_search(order.len(), current, forward, |i| overdue[i]) else {
+            self.message = Some(self.strings.get(Key::MsgNoOverdue).to_string());
+            return;
+        };
+        self.display_filter.roots_only = false;
+        if self.reveal(&order[index]) && self.select_node(&order[index]) {
+            let position = overdue[..=index].iter().filter(|&&late| late).count();
+            let total = overdue.iter().filter(|&&late| late).count();
+            self.message = Some(
+                self.strings
+                    .format(Key::MsgOverduePosition, &[&position, &total]),
+            );
+        }
+    }
+
+    // ============ 陈旧根节点相关 ============
+
+    /// 打开按持续天数排列的根节点列表，并选中最久的根节点
+    pub fn open_stale_roots(&mut self) {
+        match self.stale_roots().first() {
+            Some(id) => {
+                self.select_node(id);
+                self.mode = AppMode::StaleRoots;
             }
+            None => self.message = Some(self.strings.get(Key::MsgNoRoots).to_string()),
+        }
+    }
+
+    /// 在根节点列表中上下移动，同步选中树中的节点
+    fn move_stale(&mut self, delta: isize) {
+        let roots = self.stale_roots();
+        if roots.is_empty() {
+            self.mode = AppMode::Normal;
+            return;
+        }
+        let current = self
+            .selected_node_id()
+            .and_then(|id| roots.iter().position(|root| *root == id))
+            .unwrap_or(0);
+        let next = current.saturating_add_signed(delta).min(roots.len() - 1);
+        self.select_node(&roots[next]);
+    }
+
+    // ============ 最近修改相关 ============
+
+    /// 打开最近修改的节点列表
+    pub fn open_recent(&mut self) {
+        if self.recent_ids().is_empty() {
+            self.message = Some(self.strings.get(Key::MsgNoRecent).to_string());
+            return;
         }
+        self.palette_index = 0;
+        self.mode = AppMode::RecentList;
+    }
+
+    /// 跳转到最近修改列表中选中的节点
+    fn jump_to_recent(&mut self) {
+        self.mode = AppMode::Normal;
+        if let Some(id) = self.recent_ids().get(self.palette_index) {
+            let id = id.clone();
+            self.goto_id(&id);
+        }
+        self.palette_index = 0;
     }
 
     /// 执行确认操作
@@ -248,13 +1092,102 @@ impl App {
         match &self.mode {
             AppMode::Confirm(ConfirmAction::Delete(id)) => {
                 let id = id.clone();
-                let deleted = self.tree.delete_node(&id);
-                self.message = Some(format!("已删除 {} 个节点", deleted.len()));
+                let title = self.tree.nodes.get(&id).map(|n| n.title.clone());
+                // 删除后选中其父节点
+                self.select_after_refresh = self
+                    .tree
+                    .nodes
+                    .get(&id)
+                    .filter(|n| !n.is_root())
+                    .map(|n| n.parent_id.clone());
+                match self.config.delete_mode {
+                    DeleteMode::Cascade => {
+                        self.last_deleted = self.tree.subtree_snapshot(&id);
+                        self.last_rename = None;
+                        let deleted = self.tree.delete_node(&id);
+                        self.message =
+                            Some(self.strings.format(Key::MsgDeleted, &[&deleted.len()]));
+                    }
+                    DeleteMode::Reparent => {
+                        // 叶子与级联删除一样可以撤销；子节点接到别处后无法整体撤销
+                        self.last_deleted = if self.tree.is_leaf(&id) {
+                            self.tree.subtree_snapshot(&id)
+                        } else {
+                            None
+                        };
+                        self.last_rename = None;
+                        let moved = self.tree.delete_node_reparent(&id);
+                        if let Some(first) = moved.first() {
+                            self.select_after_refresh = Some(first.clone());
+                        }
+                        self.message = Some(
+                            self.strings
+                                .format(Key::MsgDeletedReparented, &[&moved.len()]),
+                        );
+                    }
+                }
+                self.audit("delete", &id, &title.unwrap_or_default());
             }
             AppMode::Confirm(ConfirmAction::Fail(id)) => {
-                let id = id.clone();
-                let deleted = self.tree.fail_node(&id);
-                self.message = Some(format!("节点已标记失败，删除了 {} 个子节点", deleted.len()));
+                // 确认后再询问失败原因
+                self.mode = AppMode::FailReason(id.clone());
+                self.input_buffer.clear();
+                return;
+            }
+            AppMode::Confirm(ConfirmAction::Move { node, target }) => {
+                let (node, target) = (node.clone(), target.clone());
+                self.perform_move(node, target);
+            }
+            AppMode::Confirm(ConfirmAction::PurgeFailed) => {
+                let count = self.tree.purge_failed();
+                self.message = Some(self.strings.format(Key::MsgPurged, &[&count]));
+                if count > 0 {
+                    self.audit(
+                        "purge_failed",
+                        "",
+                        &self.strings.format(Key::AuditNodes, &[&count]),
+                    );
+                }
+            }
+            AppMode::Confirm(ConfirmAction::MergeRoots) => {
+                let (merged, skipped) = self.tree.merge_roots_by_title();
+                self.message = Some(if skipped > 0 {
+                    self.strings
+                        .format(Key::MsgMergedRootsSkipped, &[&merged, &skipped])
+                } else {
+                    self.strings.format(Key::MsgMergedRoots, &[&merged])
+                });
+                if merged > 0 {
+                    self.audit(
+                        "merge_roots",
+                        "",
+                        &self.strings.format(Key::AuditNodes, &[&merged]),
+                    );
+                }
+            }
+            AppMode::Confirm(ConfirmAction::Replace { from, to }) => {
+                let (from, to) = (from.clone(), to.clone());
+                let (count, skipped) = self.tree.replace_in_titles(&from, &to);
+                let count = count + self.tree.replace_in_content(&from, &to);
+                self.message = Some(if skipped > 0 {
+                    self.strings
+                        .format(Key::MsgReplacedSkipped, &[&count, &skipped])
+                } else {
+                    self.strings.format(Key::MsgReplaced, &[&count])
+                });
+                self.audit("replace", "", &format!("{} → {}", from, to));
+            }
+            AppMode::Confirm(ConfirmAction::ReloadExternal) => self.reload_from_disk(),
+            AppMode::Confirm(ConfirmAction::RepairTree(_)) => {
+                let count = self.tree.validate_and_repair().len();
+                self.message = Some(self.strings.format(Key::MsgRepaired, &[&count]));
+                if count > 0 {
+                    self.audit(
+                        "repair",
+                        "",
+                        &self.strings.format(Key::AuditIssues, &[&count]),
+                    );
+                }
             }
             _ => {}
         }
@@ -262,12 +1195,1605 @@ impl App {
         self.mode = AppMode::Normal;
     }
 
-    // ============ 通用操作 ============
+    // ============ 外部修改相关 ============
 
-    /// 取消当前操作
-    pub fn cancel(&mut self) {
-        self.mode = AppMode::Normal;
-        self.input_buffer.clear();
-        self.message = None;
+    /// 数据文件被外部修改：无未保存修改时直接重新加载，否则询问
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    pub fn on_external_change(&mut self) {
+        match reload_decision(self.tree.dirty) {
+            ReloadDecision::Reload => self.reload_from_disk(),
+            ReloadDecision::Ask => self.mode = AppMode::Confirm(ConfirmAction::ReloadExternal),
+        }
+    }
+
+    /// 从数据文件重新加载树（刷新时会尽量保持当前选中的节点）
+    fn reload_from_disk(&mut self) {
+        let Some(path) = &self.data_path else {
+            return;
+        };
+        match load_tree(path) {
+            // 内容与内存一致（例如刚由 Ctrl+S 写入）时无需重新加载
+            Ok(tree) if tree.to_data().nodes == self.tree.to_data().nodes => {}
+            Ok(mut tree) => {
+                tree.max_depth = self.tree.max_depth;
+                self.tree = tree;
+                // 撤销与最近修改记录的是被替换掉的树，不能再用于新树
+                self.last_deleted = None;
+                self.last_rename = None;
+                self.recent.clear();
+                self.refresh_title_colors();
+                self.refresh_display_list();
+                self.message = Some(self.strings.get(Key::MsgReloaded).to_string());
+            }
+            Err(e) => self.message = Some(self.strings.format(Key::MsgReloadFailed, &[&e])),
+        }
+    }
+
+    // ============ 命令面板相关 ============
+
+    /// 打开命令面板
+    pub fn open_command_palette(&mut self) {
+        self.mode = AppMode::CommandPalette;
+        self.input_buffer.clear();
+        self.palette_index = 0;
+    }
+
+    /// 执行命令面板中选中的操作
+    pub fn execute_palette_selection(&mut self) -> DispatchResult {
+        let action = filter_actions(&self.input_buffer, &self.strings)
+            .get(self.palette_index)
+            .cloned();
+        self.mode = AppMode::Normal;
+        self.input_buffer.clear();
+        self.palette_index = 0;
+        match action {
+            Some(action) => self.dispatch(action),
+            None => DispatchResult::Continue,
+        }
+    }
+
+    // ============ 通用操作 ============
+
+    /// 节点被标记完成后在后台运行 `on_complete_command`，只读与临时会话中不运行
+    #[cfg(feature = "hooks")]
+    fn run_complete_hook(&mut self, node_id: &str) {
+        if self.readonly || self.ephemeral {
+            return;
+        }
+        let (Some(template), Some(node)) = (
+            self.config.on_complete_command.as_deref(),
+            self.tree.nodes.get(node_id),
+        ) else {
+            return;
+        };
+        let command = render_command(template, &node.title, &node.id);
+        match crate::hooks::spawn(&command) {
+            Ok(child) => self.hook_children.push(child),
+            Err(e) => self.message = Some(self.strings.format(Key::MsgHookFailed, &[&e])),
+        }
+    }
+
+    #[cfg(not(feature = "hooks"))]
+    fn run_complete_hook(&mut self, _node_id: &str) {}
+
+    /// 回收已结束的完成命令，非零退出时在状态栏提示（不影响其他操作）
+    pub fn poll_hooks(&mut self) {
+        let mut failed = None;
+        self.hook_children
+            .retain_mut(|child| match child.try_wait() {
+                Ok(None) => true,
+                Ok(Some(status)) => {
+                    if !status.success() {
+                        failed = Some(status.to_string());
+                    }
+                    false
+                }
+                Err(e) => {
+                    failed = Some(e.to_string());
+                    false
+                }
+            });
+        if let Some(reason) = failed {
+            self.message = Some(self.strings.format(Key::MsgHookFailed, &[&reason]));
+        }
+    }
+
+    /// 记录节点的修改操作到活动日志
+    fn audit_node(&mut self, action: &str, node_id: &str) {
+        let title = self
+            .tree
+            .nodes
+            .get(node_id)
+            .map(|n| n.title.clone())
+            .unwrap_or_default();
+        self.audit(action, node_id, &title);
+    }
+
+    /// 追加活动日志，写入失败只提示、不影响操作本身
+    fn audit(&mut self, action: &str, node_id: &str, title: &str) {
+        if let Some(path) = &self.audit_path {
+            let record = AuditRecord::new(action, node_id, title);
+            if let Err(e) = log_action(path, &record) {
+                self.message = Some(self.strings.format(Key::MsgAuditFailed, &[&e]));
+            }
+        }
+    }
+
+    /// 提示操作被拒绝，并按配置闪烁提示栏或响铃
+    fn reject(&mut self, message: String) {
+        self.message = Some(message);
+        if self.config.error_alert.flash() {
+            self.flash_until = Some(Instant::now() + FLASH_DURATION);
+        }
+        if self.config.error_alert.bell() {
+            self.bell_pending = true;
+        }
+    }
+
+    /// 立即保存到数据文件，没有修改时只提示；只读模式下拒绝
+    pub fn save_now(&mut self) {
+        if self.readonly {
+            self.reject(self.strings.get(Key::MsgReadonly).to_string());
+            return;
+        }
+        if self.ephemeral {
+            self.message = Some(self.strings.get(Key::MsgEphemeral).to_string());
+            return;
+        }
+        let Some(path) = &self.data_path else {
+            return;
+        };
+        if !self.tree.dirty {
+            self.message = Some(self.strings.get(Key::MsgNoChanges).to_string());
+            return;
+        }
+        self.tree.selected_id = self.selected_node_id();
+        let result = save_tree(&mut self.tree, path, &self.strings);
+        self.save_failed = result.is_err();
+        self.message = Some(match result {
+            Ok(()) => self.strings.get(Key::MsgSaved).to_string(),
+            Err(e) => self.strings.format(Key::MsgSaveFailed, &[&e]),
+        });
+    }
+
+    /// 退出前保存到数据文件，返回写入的路径；只读模式、临时会话、没有修改或没有数据文件时不写入
+    pub fn save_on_exit(&mut self) -> io::Result<Option<PathBuf>> {
+        // 只读模式下即使树因迁移、清理关联等在加载后变脏，也不写回
+        if self.readonly || self.ephemeral {
+            return Ok(None);
+        }
+        let Some(path) = self.data_path.clone() else {
+            return Ok(None);
+        };
+        self.remember_selection();
+        if !self.tree.dirty {
+            return Ok(None);
+        }
+        save_tree(&mut self.tree, &path, &self.strings)?;
+        Ok(Some(path))
+    }
+
+    /// 取消当前操作
+    pub fn cancel(&mut self) {
+        self.mode = AppMode::Normal;
+        self.input_buffer.clear();
+        self.temp_title.clear();
+        self.temp_content.clear();
+        self.message = None;
+    }
+}
+
+/// 截止日期的输入与显示格式
+pub const DUE_FORMAT: &str = "%Y-%m-%d";
+
+/// 解析输入的截止日期：空白表示清除（`Some(None)`），无法解析时为 None
+pub fn parse_due(input: &str) -> Option<Option<NaiveDate>> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Some(None);
+    }
+    NaiveDate::parse_from_str(input, DUE_FORMAT).ok().map(Some)
+}
+
+/// 重命名提示中新旧标题各自的最大显示宽度
+const RENAME_DIFF_WIDTH: usize = 20;
+
+/// 重命名提示中的标题，过长时以 `…` 截断
+fn rename_diff_part(title: &str) -> String {
+    truncate_to_width(title, RENAME_DIFF_WIDTH)
+}
+
+/// 将节点放到最近修改列表的最前面（已存在时移动到最前），超出容量时丢弃最旧的
+fn push_recent(recent: &mut VecDeque<String>, id: String) {
+    recent.retain(|existing| *existing != id);
+    recent.push_front(id);
+    recent.truncate(RECENT_CAPACITY);
+}
+
+/// 移动的子树节点数超过阈值时需要确认
+fn needs_move_confirm(subtree_size: usize, threshold: usize) -> bool {
+    subtree_size > threshold
+}
+
+/// 删除末尾的一个词（连续的非空白字符）及其后的空白
+fn delete_word_before_end(text: &mut String) {
+    let trimmed = text.trim_end_matches(char::is_whitespace).len();
+    let start = text[..trimmed]
+        .rfind(char::is_whitespace)
+        .map(|i| i + text[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+    text.truncate(start);
+}
+
+/// 删除当前行（最后一个换行之后）的全部内容
+fn delete_to_line_start(text: &mut String) {
+    let start = text.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    text.truncate(start);
+}
+
+/// 颜色标签的循环顺序，最后一个之后回到无标签
+const TAG_COLORS: [&str; 6] = ["red", "yellow", "green", "blue", "magenta", "cyan"];
+
+/// 调色板中的下一个颜色；自定义颜色从头开始
+fn next_tag_color(current: Option<&str>) -> Option<String> {
+    let next = match current {
+        None => 0,
+        Some(color) => match TAG_COLORS.iter().position(|c| *c == color) {
+            Some(i) => i + 1,
+            None => 0,
+        },
+    };
+    TAG_COLORS.get(next).map(|c| c.to_string())
+}
+
+/// 模糊匹配：`query` 的字符按顺序出现在 `candidate` 中（忽略大小写）时返回得分，否则返回 None
+///
+/// 每个匹配字符得 1 分，紧接上一个匹配再加 5 分，在开头或单词开头匹配再加 3 分，
+/// 两个匹配之间每跳过一个字符扣 1 分
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let mut pending = query.chars().flat_map(char::to_lowercase).peekable();
+    let mut score = 0;
+    let mut last_match: Option<usize> = None;
+    let mut prev: Option<char> = None;
+    for (i, c) in candidate.chars().flat_map(char::to_lowercase).enumerate() {
+        let Some(&wanted) = pending.peek() else {
+            break;
+        };
+        if c == wanted {
+            score += 1;
+            match last_match {
+                Some(last) if last + 1 == i => score += 5,
+                Some(last) => score -= (i - last - 1) as i32,
+                None => {}
+            }
+            if prev.is_none_or(|p| !p.is_alphanumeric()) {
+                score += 3;
+            }
+            last_match = Some(i);
+            pending.next();
+        }
+        prev = Some(c);
+    }
+    pending.peek().is_none().then_some(score)
+}
+
+/// 让第 `index` 项位于高度为 `height` 的视口中间时的滚动偏移（以项为单位），不会滚过列表首尾
+///
+/// `heights` 为每一项实际占用的行数（分组标题、折行后的多行节点）
+pub fn center_offset(index: usize, heights: &[usize], height: usize) -> usize {
+    let Some(&own) = heights.get(index) else {
+        return 0;
+    };
+    // 选中项之上留出的行数
+    let target = height.saturating_sub(own).div_ceil(2);
+    let mut offset = index;
+    let mut above = 0;
+    while offset > 0 && above + heights[offset - 1] <= target {
+        offset -= 1;
+        above += heights[offset];
+    }
+    // 末尾的项恰好填满视口时的最大偏移
+    let mut last = heights.len();
+    let mut below = 0;
+    while last > 0 && below + heights[last - 1] <= height {
+        last -= 1;
+        below += heights[last];
+    }
+    offset.min(last)
+}
+
+/// 从 `current` 之后（`forward` 为 false 时向前）循环查找第一个满足 `is_match` 的下标，
+/// 都不满足时返回 None；`current` 为 None 时从列表首（或尾）开始
+pub fn cycle_search(
+    len: usize,
+    current: Option<usize>,
+    forward: bool,
+    is_match: impl Fn(usize) -> bool,
+) -> Option<usize> {
+    (1..=len)
+        .map(|step| match (current, forward) {
+            (Some(current), true) => (current + step) % len,
+            (Some(current), false) => (current + len - step % len) % len,
+            (None, true) => step - 1,
+            (None, false) => len - step,
+        })
+        .find(|&index| is_match(index))
+}
+
+/// 按关键字模糊筛选命令面板中的操作（匹配名称或按键），得分高的排在前面
+pub fn filter_actions(query: &str, strings: &Strings) -> Vec<Action> {
+    let query = query.trim();
+    let mut scored: Vec<(i32, &Action)> = Action::all()
+        .iter()
+        .filter_map(|action| {
+            let score = fuzzy_score(query, action.name(strings))
+                .max(fuzzy_score(query, key_hint(action)))?;
+            Some((score, action))
+        })
+        .collect();
+    // 稳定排序，得分相同时保持默认顺序
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored
+        .into_iter()
+        .map(|(_, action)| action.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, ErrorAlert, WeekStart};
+    use crate::i18n::Lang;
+    use crate::models::{FocusTree, TreeBuilder};
+    use crate::test_util::temp_path;
+    use crate::ui::state::{DetailsTab, InputField};
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "delete node"), None);
+        assert_eq!(fuzzy_score("ba", "abc"), None);
+
+        // 前缀、连续匹配优于分散匹配
+        let prefix = fuzzy_score("del", "Delete node").unwrap();
+        let inner = fuzzy_score("del", "Undo delete").unwrap();
+        let scattered = fuzzy_score("del", "Drop every label").unwrap();
+        assert!(prefix > inner);
+        assert!(inner > scattered);
+
+        // 中文按字符做子序列匹配
+        assert!(fuzzy_score("健博", "健身博客").is_some());
+        assert!(fuzzy_score("健身", "健身博客") > fuzzy_score("健博", "健身博客"));
+    }
+
+    #[test]
+    fn test_fuzzy_filter_orders_by_score() {
+        let en = Strings::new(Lang::En);
+        let actions = filter_actions("del", &en);
+        assert_eq!(actions[0], Action::StartDeleteNode);
+        assert_eq!(filter_actions("undo", &en)[0], Action::Undo);
+        // 子序列匹配
+        assert!(filter_actions("shwarc", &en).contains(&Action::ToggleShowArchived));
+    }
+
+    #[test]
+    fn test_filter_actions() {
+        let zh = Strings::default();
+        assert_eq!(filter_actions("", &zh), Action::all().to_vec());
+        assert_eq!(filter_actions("删除", &zh), vec![Action::StartDeleteNode]);
+        // 按键匹配忽略大小写，q 与 Q 都会列出
+        assert_eq!(
+            filter_actions("  Q ", &zh),
+            vec![Action::Quit, Action::QuitDiscard]
+        );
+        assert!(filter_actions("不存在的命令", &zh).is_empty());
+
+        let en = Strings::new(Lang::En);
+        assert_eq!(
+            filter_actions("delete node", &en),
+            vec![Action::StartDeleteNode]
+        );
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            app.dispatch(Action::Input(c));
+        }
+    }
+
+    #[test]
+    fn test_edit_content_updates_timestamp() {
+        let mut tree = FocusTree::new();
+        tree.add_node("task".to_string(), "".to_string(), None)
+            .unwrap();
+        let mut app = App::new(tree, Config::default());
+        assert!(app.selected_node().unwrap().updated_at.is_none());
+
+        app.dispatch(Action::StartEditContent);
+        assert_eq!(app.input_field, InputField::Content);
+        app.dispatch(Action::Input('x'));
+        app.dispatch(Action::Submit);
+
+        let node = app.selected_node().unwrap();
+        assert_eq!(node.content, "x");
+        assert_eq!(node.title, "task");
+        assert!(node.updated_at.is_some());
+    }
+
+    #[test]
+    fn test_combined_edit_updates_both_fields() {
+        let mut tree = FocusTree::new();
+        tree.add_node("old".to_string(), "rule".to_string(), None)
+            .unwrap();
+        let mut app = App::new(tree, Config::default());
+
+        app.dispatch(Action::StartEditTitle);
+        assert_eq!(app.input_field, InputField::Title);
+        assert_eq!(app.input_buffer, "old");
+        type_text(&mut app, " title");
+
+        app.dispatch(Action::SwitchInputField);
+        assert_eq!(app.input_field, InputField::Content);
+        assert_eq!(app.input_buffer, "rule");
+        type_text(&mut app, "s");
+
+        // 切回标题不会丢失已输入的内容
+        app.dispatch(Action::SwitchInputField);
+        assert_eq!(app.input_buffer, "old title");
+        app.dispatch(Action::Submit);
+
+        assert_eq!(app.mode, AppMode::Normal);
+        let node = app.selected_node().unwrap();
+        assert_eq!(node.title, "old title");
+        assert_eq!(node.content, "rules");
+    }
+
+    #[test]
+    fn test_combined_edit_rejects_empty_title() {
+        let mut tree = FocusTree::new();
+        tree.add_node("t".to_string(), "".to_string(), None)
+            .unwrap();
+        let mut app = App::new(tree, Config::default());
+
+        app.dispatch(Action::StartEditTitle);
+        app.dispatch(Action::DeleteChar);
+        app.dispatch(Action::Submit);
+        assert!(matches!(app.mode, AppMode::EditingNode(_)));
+        assert_eq!(app.selected_node().unwrap().title, "t");
+    }
+
+    #[test]
+    fn test_details_tab_cycles() {
+        let mut app = App::new(FocusTree::new(), Config::default());
+        assert_eq!(app.details_tab, DetailsTab::Content);
+        app.dispatch(Action::NextDetailsTab);
+        assert_eq!(app.details_tab, DetailsTab::Log);
+        app.dispatch(Action::NextDetailsTab);
+        assert_eq!(app.details_tab, DetailsTab::Meta);
+        app.dispatch(Action::NextDetailsTab);
+        assert_eq!(app.details_tab, DetailsTab::Content);
+    }
+
+    #[test]
+    fn test_archive_hides_completed() {
+        let mut tree = FocusTree::new();
+        tree.add_node("done".to_string(), "".to_string(), None)
+            .unwrap();
+        tree.add_node("todo".to_string(), "".to_string(), None)
+            .unwrap();
+        let mut app = App::new(tree, Config::default());
+
+        app.dispatch(Action::ToggleComplete);
+        app.dispatch(Action::ArchiveCompleted);
+        assert_eq!(app.display_list.len(), 1);
+        assert_eq!(app.selected_node().unwrap().title, "todo");
+
+        app.dispatch(Action::ToggleShowArchived);
+        assert_eq!(app.display_list.len(), 2);
+    }
+
+    #[test]
+    fn test_mutations_are_audited() {
+        let path = temp_path("activity.log");
+        let mut app = App::new(FocusTree::new(), Config::default());
+        app.audit_path = Some(path.clone());
+
+        app.dispatch(Action::StartAddNode);
+        type_text(&mut app, "早起");
+        app.dispatch(Action::Submit);
+        app.dispatch(Action::Submit);
+        app.dispatch(Action::StartDeleteNode);
+        app.dispatch(Action::Submit);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let records: Vec<AuditRecord> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].action, "add");
+        assert_eq!(records[1].action, "delete");
+        assert_eq!(records[1].title, "早起");
+        assert_eq!(records[0].node_id, records[1].node_id);
+    }
+
+    #[test]
+    fn test_review_completed_this_week() {
+        let mut tree = TreeBuilder::new().leaf("today").leaf("last month").build();
+        let now = Local::now();
+        for node in tree.nodes.values_mut() {
+            node.status = NodeStatus::Completed;
+            node.completed_at = Some(match node.title.as_str() {
+                "today" => now,
+                _ => now - chrono::Duration::days(30),
+            });
+        }
+        let config = Config {
+            week_start: WeekStart::Sunday,
+            ..Config::default()
+        };
+        let mut app = App::new(tree, config);
+        app.dispatch(Action::ReviewCompleted);
+        let week_start = WeekStart::Sunday.first_day(now.date_naive());
+        assert_eq!(
+            app.message,
+            Some(
+                app.strings
+                    .format(Key::MsgCompletedWeek, &[&week_start, &1, &"today"])
+            )
+        );
+    }
+
+    #[test]
+    fn test_search_and_replace() {
+        let mut tree = FocusTree::new();
+        tree.add_node("健身".to_string(), "每周健身三次".to_string(), None)
+            .unwrap();
+        let mut app = App::new(tree, Config::default());
+
+        app.dispatch(Action::StartReplace);
+        type_text(&mut app, "健身");
+        app.dispatch(Action::Submit);
+        type_text(&mut app, "运动");
+        app.dispatch(Action::Submit);
+        assert!(matches!(
+            app.mode,
+            AppMode::Confirm(ConfirmAction::Replace { .. })
+        ));
+        // 预览阶段尚未修改
+        assert_eq!(app.selected_node().unwrap().title, "健身");
+
+        app.dispatch(Action::Submit);
+        let node = app.selected_node().unwrap();
+        assert_eq!(node.title, "运动");
+        assert_eq!(node.content, "每周运动三次");
+        assert!(app.tree.dirty);
+
+        app.dispatch(Action::StartReplace);
+        type_text(&mut app, "不存在");
+        app.dispatch(Action::Submit);
+        app.dispatch(Action::Submit);
+        assert_eq!(app.mode, AppMode::Normal);
+        // 替换为空会留下空标题，只替换内容并在消息中说明
+        app.dispatch(Action::StartReplace);
+        type_text(&mut app, "运动");
+        app.dispatch(Action::Submit);
+        app.dispatch(Action::Submit);
+        app.dispatch(Action::Submit);
+        let node = app.selected_node().unwrap();
+        assert_eq!(node.title, "运动");
+        assert_eq!(node.content, "每周三次");
+        assert_eq!(
+            app.message,
+            Some(app.strings.format(Key::MsgReplacedSkipped, &[&1, &1]))
+        );
+    }
+
+    #[test]
+    fn test_goto_reveals_archived_node() {
+        let mut tree = FocusTree::new();
+        let done = tree
+            .add_node("done".to_string(), "".to_string(), None)
+            .unwrap();
+        let child = tree
+            .add_node("child".to_string(), "".to_string(), Some(done.clone()))
+            .unwrap();
+        tree.add_node("todo".to_string(), "".to_string(), None)
+            .unwrap();
+        tree.toggle_complete(&done);
+        tree.archive_completed();
+        let mut app = App::new(tree, Config::default());
+        assert_eq!(app.display_list.len(), 1);
+
+        app.dispatch(Action::GotoId(child.clone()));
+        assert!(app.display_filter.show_archived);
+        assert_eq!(app.selected_index, 1);
+        assert_eq!(app.selected_node_id(), Some(child));
+
+        app.dispatch(Action::StartGoto);
+        type_text(&mut app, "missing");
+        app.dispatch(Action::Submit);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.selected_index, 1);
+        assert!(app.message.as_deref().unwrap().contains("missing"));
+    }
+
+    #[test]
+    fn test_collapse_and_expand_with_h_l() {
+        let tree = TreeBuilder::new()
+            .node("parent", |b| b.node("child", |b| b.leaf("grandchild")))
+            .leaf("other")
+            .build();
+        let mut app = App::new(tree, Config::default());
+        assert_eq!(app.display_list.len(), 4);
+
+        // l 在已展开的节点上跳到第一个子节点，h 在叶子上跳回父节点
+        app.dispatch(Action::ExpandOrChild);
+        assert_eq!(app.selected_index, 1);
+        app.dispatch(Action::ExpandOrChild);
+        app.dispatch(Action::ExpandOrChild);
+        assert_eq!(app.selected_index, 2);
+        app.dispatch(Action::CollapseOrParent);
+        assert_eq!(app.selected_index, 1);
+
+        // h 折叠节点，再按 h 跳到父节点，l 重新展开
+        app.dispatch(Action::CollapseOrParent);
+        assert_eq!(app.display_list.len(), 3);
+        assert_eq!(app.selected_index, 1);
+        app.dispatch(Action::CollapseOrParent);
+        assert_eq!(app.selected_index, 0);
+        app.dispatch(Action::CollapseOrParent);
+        assert_eq!(app.display_list.len(), 2);
+        app.dispatch(Action::ExpandOrChild);
+        assert_eq!(app.display_list.len(), 3);
+
+        // 跳转到折叠子树中的节点会展开其祖先
+        let grandchild = app.tree.nodes.values().find(|n| n.title == "grandchild");
+        let grandchild = grandchild.unwrap().id.clone();
+        app.dispatch(Action::GotoId(grandchild.clone()));
+        assert_eq!(app.selected_node_id(), Some(grandchild));
+        assert!(!app.display_filter.show_archived);
+    }
+
+    #[test]
+    fn test_undo_delete() {
+        let tree = TreeBuilder::new()
+            .leaf("first")
+            .node("second", |b| b.leaf("child"))
+            .build();
+        let mut app = App::new(tree, Config::default());
+        let before = app.tree.to_data().nodes;
+
+        app.dispatch(Action::Undo);
+        assert_eq!(app.tree.nodes.len(), 3);
+
+        app.dispatch(Action::MoveSelectionDown);
+        app.dispatch(Action::StartDeleteNode);
+        app.dispatch(Action::Submit);
+        assert_eq!(app.tree.nodes.len(), 1);
+
+        app.dispatch(Action::Undo);
+        assert_eq!(app.tree.to_data().nodes, before);
+        assert_eq!(app.selected_index, 1);
+        assert!(app.last_deleted.is_none());
+    }
+
+    #[test]
+    fn test_rename_diff_and_undo() {
+        let tree = TreeBuilder::new().leaf("早起").build();
+        let mut app = App::new(tree, Config::default());
+
+        app.dispatch(Action::StartEditTitle);
+        type_text(&mut app, "跑步");
+        app.dispatch(Action::Submit);
+        assert_eq!(app.message.as_deref(), Some("标题: 早起 → 早起跑步"));
+        let id = app.selected_node_id().unwrap();
+        assert_eq!(app.last_rename, Some((id, "早起".to_string())));
+
+        // 过长的标题截断显示
+        app.dispatch(Action::StartEditTitle);
+        type_text(&mut app, &"很".repeat(20));
+        app.dispatch(Action::Submit);
+        let message = app.message.clone().unwrap();
+        assert!(message.starts_with("标题: 早起跑步 → 早起跑步很"));
+        assert!(message.ends_with('…'));
+
+        app.dispatch(Action::Undo);
+        assert_eq!(app.selected_node().unwrap().title, "早起跑步");
+        assert!(app.last_rename.is_none());
+        app.dispatch(Action::Undo);
+        assert_eq!(app.selected_node().unwrap().title, "早起跑步");
+    }
+
+    #[test]
+    fn test_toc_zooms_into_root() {
+        let tree = TreeBuilder::new()
+            .node("a", |b| b.leaf("x"))
+            .node("b", |b| b.leaf("y"))
+            .build();
+        let mut app = App::new(tree, Config::default());
+        app.dispatch(Action::MoveSelectionDown);
+        app.dispatch(Action::ToggleToc);
+        assert_eq!(app.display_list.len(), 2);
+        assert_eq!(app.selected_node().unwrap().title, "a");
+
+        app.dispatch(Action::MoveSelectionDown);
+        app.dispatch(Action::ToggleToc);
+        let titles: Vec<&str> = app
+            .display_list
+            .iter()
+            .map(|(_, id)| app.tree.nodes[id].title.as_str())
+            .collect();
+        assert_eq!(titles, ["a", "b", "y"]);
+        assert_eq!(app.selected_node().unwrap().title, "b");
+    }
+
+    #[test]
+    fn test_delete_mode() {
+        let tree = || {
+            TreeBuilder::new()
+                .node("parent", |b| b.node("middle", |b| b.leaf("x").leaf("y")))
+                .build()
+        };
+        let delete_middle = |config: Config| {
+            let mut app = App::new(tree(), config);
+            app.dispatch(Action::MoveSelectionDown);
+            app.dispatch(Action::StartDeleteNode);
+            app.dispatch(Action::Submit);
+            app
+        };
+
+        // 默认级联删除整棵子树
+        let app = delete_middle(Config::default());
+        assert_eq!(app.tree.nodes.len(), 1);
+
+        let app = delete_middle(Config {
+            delete_mode: DeleteMode::Reparent,
+            ..Config::default()
+        });
+        let parent = app.tree.root_ids[0].clone();
+        let titles: Vec<&str> = app
+            .tree
+            .get_children(&parent)
+            .iter()
+            .map(|n| n.title.as_str())
+            .collect();
+        assert_eq!(titles, ["x", "y"]);
+        assert_eq!(app.selected_node().unwrap().title, "x");
+        assert!(app.last_deleted.is_none());
+
+        // 删除叶子时没有子节点被移动，仍可撤销
+        let mut app = app;
+        app.dispatch(Action::StartDeleteNode);
+        app.dispatch(Action::Submit);
+        assert!(app.last_deleted.is_some());
+        app.dispatch(Action::Undo);
+        let titles: Vec<&str> = app
+            .tree
+            .get_children(&parent)
+            .iter()
+            .map(|n| n.title.as_str())
+            .collect();
+        assert_eq!(titles, ["x", "y"]);
+    }
+
+    #[test]
+    fn test_quick_add_skips_content() {
+        let mut app = App::new(FocusTree::new(), Config::default());
+        app.dispatch(Action::StartAddNode);
+        app.dispatch(Action::SubmitQuick);
+        assert_eq!(app.mode, AppMode::AddingNode);
+
+        type_text(&mut app, "quick");
+        app.dispatch(Action::SubmitQuick);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.input_field, InputField::Title);
+        let node = app.selected_node().unwrap();
+        assert_eq!(node.title, "quick");
+        assert_eq!(node.content, "");
+    }
+
+    #[test]
+    fn test_ephemeral_session_writes_nothing() {
+        let path = temp_path("ephemeral.toml");
+        let mut app = App::new(FocusTree::new(), Config::default());
+        app.data_path = Some(path.clone());
+        app.ephemeral = true;
+
+        app.dispatch(Action::StartAddNode);
+        type_text(&mut app, "scratch");
+        app.dispatch(Action::SubmitQuick);
+        assert!(app.tree.dirty);
+        app.dispatch(Action::SaveNow);
+        assert!(app.dispatch(Action::Quit).is_quit());
+        assert_eq!(app.save_on_exit().unwrap(), None);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_row_scroll_resets_on_selection_change() {
+        let tree = TreeBuilder::new().leaf("abcdefgh").leaf("b").build();
+        let mut app = App::new(tree, Config::default());
+        app.dispatch(Action::ScrollRowRight);
+        app.dispatch(Action::ScrollRowRight);
+        app.dispatch(Action::ScrollRowRight);
+        assert_eq!(app.row_h_scroll, 7);
+        app.dispatch(Action::ScrollRowLeft);
+        assert_eq!(app.row_h_scroll, 3);
+
+        app.dispatch(Action::MoveSelectionDown);
+        assert_eq!(app.row_h_scroll, 0);
+    }
+
+    #[test]
+    fn test_rejected_action_alerts() {
+        let tree = TreeBuilder::new().leaf("only").build();
+        let mut app = App::new(tree, Config::default());
+        app.dispatch(Action::Undo);
+        assert!(app.flash_until.is_none() && !app.bell_pending);
+
+        app.config.error_alert = ErrorAlert::Both;
+        app.dispatch(Action::Undo);
+        assert!(app.is_flashing(Instant::now()));
+        assert!(app.bell_pending);
+    }
+
+    #[test]
+    fn test_collapse_completed_subtrees() {
+        let mut tree = TreeBuilder::new()
+            .node("done", |b| b.leaf("x"))
+            .node("open", |b| b.leaf("y"))
+            .build();
+        let done = tree.root_ids[0].clone();
+        tree.toggle_complete(&done);
+        let config = Config {
+            collapse_completed: true,
+            ..Config::default()
+        };
+        let mut app = App::new(tree, config);
+        assert_eq!(app.display_list.len(), 3);
+        assert!(app.is_collapsed(&done));
+
+        // 手动展开后保持展开，刷新也不会再折叠
+        app.dispatch(Action::ExpandOrChild);
+        assert_eq!(app.display_list.len(), 4);
+        app.refresh_display_list();
+        assert_eq!(app.display_list.len(), 4);
+
+        app.dispatch(Action::CollapseOrParent);
+        assert_eq!(app.display_list.len(), 3);
+
+        // 关闭时已完成的节点照常展开
+        app.config.collapse_completed = false;
+        app.expanded.clear();
+        app.display_filter.collapsed.clear();
+        app.refresh_display_list();
+        assert_eq!(app.display_list.len(), 4);
+    }
+
+    #[test]
+    fn test_check_and_repair_integrity() {
+        let tree = TreeBuilder::new().node("a", |b| b.leaf("b")).build();
+        let mut app = App::new(tree, Config::default());
+        app.dispatch(Action::CheckIntegrity);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.message.as_ref().unwrap().contains("未发现问题"));
+
+        app.tree.root_ids.push("ghost".to_string());
+        app.dispatch(Action::CheckIntegrity);
+        assert!(matches!(
+            &app.mode,
+            AppMode::Confirm(ConfirmAction::RepairTree(issues)) if issues.len() == 1
+        ));
+        app.dispatch(Action::Submit);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.tree.validate().is_empty());
+        assert_eq!(app.display_list.len(), 2);
+    }
+
+    #[test]
+    fn test_expand_one_level() {
+        let tree = TreeBuilder::new()
+            .node("a", |b| b.node("b", |b| b.leaf("c")).leaf("d"))
+            .build();
+        let mut app = App::new(tree, Config::default());
+        app.dispatch(Action::CollapseOne);
+        assert_eq!(app.display_list.len(), 1);
+        assert_eq!(app.selected_node().unwrap().title, "a");
+
+        // 只显示直接子节点，孙节点保持折叠
+        app.dispatch(Action::ExpandOne);
+        let titles: Vec<&str> = app
+            .display_list
+            .iter()
+            .map(|(_, id)| app.tree.nodes[id].title.as_str())
+            .collect();
+        assert_eq!(titles, ["a", "b", "d"]);
+        assert_eq!(app.selected_node().unwrap().title, "a");
+
+        // 已展开时不变
+        app.dispatch(Action::ExpandOne);
+        assert_eq!(app.display_list.len(), 3);
+
+        app.dispatch(Action::MoveSelectionDown);
+        app.dispatch(Action::ExpandOne);
+        assert_eq!(app.display_list.len(), 4);
+        app.dispatch(Action::CollapseOne);
+        assert_eq!(app.display_list.len(), 3);
+        assert_eq!(app.selected_node().unwrap().title, "b");
+    }
+
+    #[test]
+    fn test_apply_external_edit() {
+        let mut app = App::new(TreeBuilder::new().leaf("a").build(), Config::default());
+        app.dispatch(Action::OpenInEditor);
+        let id = app.editor_request.take().unwrap();
+
+        app.apply_external_edit(id.clone(), Ok("长笔记\n第二行".to_string()));
+        assert_eq!(app.tree.nodes[&id].content, "长笔记\n第二行");
+
+        // 结果为空或编辑器失败时保留原内容
+        app.apply_external_edit(id.clone(), Ok("  \n".to_string()));
+        assert_eq!(
+            app.message.as_deref(),
+            Some(app.strings.get(Key::MsgEditorEmpty))
+        );
+        app.apply_external_edit(id.clone(), Err(io::Error::other("boom")));
+        assert!(app.message.as_deref().unwrap().contains("boom"));
+        assert_eq!(app.tree.nodes[&id].content, "长笔记\n第二行");
+    }
+
+    #[test]
+    fn test_toggle_leaf_done_key() {
+        let tree = TreeBuilder::new().node("a", |b| b.leaf("x")).build();
+        let mut app = App::new(tree, Config::default());
+        app.dispatch(Action::ToggleLeafDone);
+        assert_eq!(app.selected_node().unwrap().status, NodeStatus::Active);
+        assert_eq!(
+            app.message.as_deref(),
+            Some(app.strings.get(Key::MsgCheckLeafOnly))
+        );
+
+        app.dispatch(Action::MoveSelectionDown);
+        app.dispatch(Action::ToggleLeafDone);
+        assert_eq!(app.selected_node().unwrap().status, NodeStatus::Completed);
+        assert_eq!(app.mode, AppMode::Normal);
+        app.dispatch(Action::ToggleLeafDone);
+        assert_eq!(app.selected_node().unwrap().status, NodeStatus::Active);
+    }
+
+    #[test]
+    fn test_center_offset() {
+        let rows = [1; 100];
+        assert_eq!(center_offset(50, &rows, 10), 45);
+        assert_eq!(center_offset(51, &rows, 11), 46);
+        // 靠近开头 / 结尾时不滚过首尾
+        assert_eq!(center_offset(2, &rows, 10), 0);
+        assert_eq!(center_offset(98, &rows, 10), 90);
+        // 列表比视口短时不滚动
+        assert_eq!(center_offset(3, &rows[..5], 10), 0);
+        assert_eq!(center_offset(0, &[], 0), 0);
+
+        // 折行的多行项按实际行数计算：第 10 项占 3 行，其上留 4 行
+        let mut wrapped = [1; 20];
+        wrapped[10] = 3;
+        wrapped[8] = 2;
+        assert_eq!(center_offset(10, &wrapped, 11), 7);
+        // 末尾有多行项时最大偏移按行数计算
+        let mut tail = [1; 20];
+        tail[19] = 4;
+        assert_eq!(center_offset(18, &tail, 10), 13);
+    }
+
+    #[test]
+    fn test_cycle_search() {
+        // 逾期节点分散在第 1、4、5 行
+        let overdue = [false, true, false, false, true, true, false];
+        let is_match = |i: usize| overdue[i];
+        assert_eq!(cycle_search(7, Some(0), true, is_match), Some(1));
+        assert_eq!(cycle_search(7, Some(1), true, is_match), Some(4));
+        assert_eq!(cycle_search(7, Some(5), true, is_match), Some(1));
+        assert_eq!(cycle_search(7, Some(6), true, is_match), Some(1));
+        assert_eq!(cycle_search(7, Some(4), false, is_match), Some(1));
+        assert_eq!(cycle_search(7, Some(1), false, is_match), Some(5));
+        assert_eq!(cycle_search(7, Some(0), false, is_match), Some(5));
+        assert_eq!(cycle_search(7, None, true, is_match), Some(1));
+        assert_eq!(cycle_search(7, None, false, is_match), Some(5));
+        // 只有当前行满足时停在原处，都不满足时为 None
+        assert_eq!(cycle_search(3, Some(1), true, |i| i == 1), Some(1));
+        assert_eq!(cycle_search(7, Some(2), true, |_| false), None);
+        assert_eq!(cycle_search(0, None, true, |_| true), None);
+    }
+
+    #[test]
+    fn test_jump_to_overdue_reveals_collapsed() {
+        let mut tree = TreeBuilder::new()
+            .node("a", |b| b.node("b", |b| b.leaf("late")))
+            .leaf("c")
+            .leaf("also late")
+            .build();
+        let yesterday = Local::now().date_naive() - chrono::Duration::days(1);
+        for node in tree.nodes.values_mut() {
+            if node.title.contains("late") {
+                node.due = Some(yesterday);
+            }
+        }
+        let mut app = App::new(tree, Config::default());
+        app.dispatch(Action::CollapseOne);
+        assert_eq!(app.display_list.len(), 3);
+
+        app.dispatch(Action::NextOverdue);
+        assert_eq!(app.selected_node().unwrap().title, "late");
+        assert_eq!(app.display_list.len(), 5);
+        app.dispatch(Action::NextOverdue);
+        assert_eq!(app.selected_node().unwrap().title, "also late");
+        app.dispatch(Action::NextOverdue);
+        assert_eq!(app.selected_node().unwrap().title, "late");
+        app.dispatch(Action::PrevOverdue);
+        assert_eq!(app.selected_node().unwrap().title, "also late");
+
+        for node in app.tree.nodes.values_mut() {
+            node.due = None;
+        }
+        app.dispatch(Action::PrevOverdue);
+        assert_eq!(app.selected_node().unwrap().title, "also late");
+        assert_eq!(app.message.as_deref(), Some("没有逾期的节点"));
+    }
+
+    #[test]
+    fn test_link_node_flow() {
+        let tree = TreeBuilder::new()
+            .node("a", |b| b.leaf("x"))
+            .leaf("b")
+            .build();
+        let mut app = App::new(tree, Config::default());
+        let a = app.selected_node_id().unwrap();
+
+        // 关联到自身被拒绝
+        app.dispatch(Action::StartLinkNode);
+        assert_eq!(app.mode, AppMode::LinkingNode(a.clone()));
+        app.dispatch(Action::Submit);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.tree.nodes[&a].links.is_empty());
+
+        // 导航到 b 后确认，选中回到 a
+        app.dispatch(Action::StartLinkNode);
+        app.dispatch(Action::MoveSelectionDown);
+        app.dispatch(Action::MoveSelectionDown);
+        let b = app.selected_node_id().unwrap();
+        app.dispatch(Action::Submit);
+        assert_eq!(app.tree.nodes[&a].links, [b.as_str()]);
+        assert_eq!(app.selected_node_id(), Some(a));
+
+        app.dispatch(Action::StartLinkNode);
+        app.dispatch(Action::Cancel);
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_quit_discard() {
+        // 没有修改时直接退出
+        let mut app = App::new(FocusTree::new(), Config::default());
+        assert_eq!(
+            app.dispatch(Action::QuitDiscard),
+            DispatchResult::QuitDiscard
+        );
+
+        // 有未保存的修改时先确认，取消后继续运行
+        let mut app = App::new(TreeBuilder::new().leaf("a").build(), Config::default());
+        assert_eq!(app.dispatch(Action::QuitDiscard), DispatchResult::Continue);
+        assert_eq!(app.mode, AppMode::Confirm(ConfirmAction::QuitDiscard));
+        app.dispatch(Action::Cancel);
+        assert_eq!(app.mode, AppMode::Normal);
+
+        app.dispatch(Action::QuitDiscard);
+        assert_eq!(app.dispatch(Action::Submit), DispatchResult::QuitDiscard);
+        assert!(app.tree.dirty);
+        assert_eq!(app.dispatch(Action::Quit), DispatchResult::Quit);
+    }
+
+    #[test]
+    fn test_add_root_ignores_selection() {
+        let tree = TreeBuilder::new()
+            .node("a", |b| b.node("b", |b| b.leaf("deep")))
+            .build();
+        let mut app = App::new(tree, Config::default());
+        app.dispatch(Action::MoveSelectionDown);
+        app.dispatch(Action::MoveSelectionDown);
+        assert_eq!(app.selected_node().unwrap().title, "deep");
+
+        app.dispatch(Action::StartAddRoot);
+        type_text(&mut app, "top");
+        app.dispatch(Action::SubmitQuick);
+        let node = app.selected_node().unwrap();
+        assert_eq!(node.title, "top");
+        assert!(node.parent_id.is_empty());
+        assert_eq!(app.tree.root_ids.len(), 2);
+    }
+
+    #[test]
+    fn test_sticky_add_keeps_parent() {
+        let tree = TreeBuilder::new().leaf("parent").build();
+        let config = Config {
+            sticky_add: true,
+            ..Config::default()
+        };
+        let mut app = App::new(tree, config);
+        let parent = app.tree.root_ids[0].clone();
+
+        app.dispatch(Action::StartAddNode);
+        for title in ["one", "two", "three"] {
+            type_text(&mut app, title);
+            app.dispatch(Action::SubmitQuick);
+            assert_eq!(app.mode, AppMode::AddingNode);
+            assert!(app.input_buffer.is_empty());
+        }
+        // 两步输入同样沿用父节点
+        type_text(&mut app, "four");
+        app.dispatch(Action::Submit);
+        type_text(&mut app, "body");
+        app.dispatch(Action::Submit);
+        assert_eq!(app.input_field, InputField::Title);
+
+        app.dispatch(Action::Cancel);
+        assert_eq!(app.mode, AppMode::Normal);
+        let titles: Vec<&str> = app
+            .tree
+            .get_children(&parent)
+            .iter()
+            .map(|n| n.title.as_str())
+            .collect();
+        assert_eq!(titles, ["one", "two", "three", "four"]);
+        assert_eq!(app.selected_node().unwrap().title, "four");
+    }
+
+    #[test]
+    fn test_readonly_drops_mutations() {
+        let tree = TreeBuilder::new().leaf("a").leaf("b").build();
+        let mut app = App::new(tree, Config::default());
+        app.readonly = true;
+        app.tree.dirty = false;
+        let before = app.tree.to_data().nodes;
+
+        for action in Action::all().iter().filter(|a| a.is_mutating()) {
+            app.dispatch(action.clone());
+            assert_eq!(app.mode, AppMode::Normal, "{:?}", action);
+            assert_eq!(
+                app.message.as_deref(),
+                Some(app.strings.get(Key::MsgReadonly))
+            );
+        }
+        assert_eq!(app.tree.to_data().nodes, before);
+        assert!(!app.tree.dirty);
+
+        // 导航仍然可用
+        app.dispatch(Action::MoveSelectionDown);
+        assert_eq!(app.selected_index, 1);
+    }
+
+    #[test]
+    fn test_fail_records_reason() {
+        let tree = TreeBuilder::new().node("goal", |b| b.leaf("step")).build();
+        let mut app = App::new(tree, Config::default());
+        let goal = app.tree.root_ids[0].clone();
+
+        app.dispatch(Action::StartFailNode);
+        app.dispatch(Action::Submit);
+        assert_eq!(app.mode, AppMode::FailReason(goal.clone()));
+        assert_eq!(app.tree.nodes[&goal].status, NodeStatus::Active);
+
+        type_text(&mut app, "计划太激进");
+        app.dispatch(Action::Submit);
+        assert_eq!(app.mode, AppMode::Normal);
+        let node = &app.tree.nodes[&goal];
+        assert_eq!(node.status, NodeStatus::Failed);
+        assert!(
+            node.log
+                .iter()
+                .any(|e| app.strings.log_entry(e) == "失败原因: 计划太激进")
+        );
+        assert_eq!(app.tree.nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_due() {
+        assert_eq!(parse_due(""), Some(None));
+        assert_eq!(parse_due("  "), Some(None));
+        assert_eq!(
+            parse_due(" 2024-05-01 "),
+            Some(NaiveDate::from_ymd_opt(2024, 5, 1))
+        );
+        assert_eq!(parse_due("2024-02-30"), None);
+        assert_eq!(parse_due("5/1"), None);
+    }
+
+    #[test]
+    fn test_edit_due() {
+        let tree = TreeBuilder::new().leaf("goal").build();
+        let mut app = App::new(tree, Config::default());
+        let goal = app.tree.root_ids[0].clone();
+
+        app.dispatch(Action::StartEditDue);
+        type_text(&mut app, "明天");
+        app.dispatch(Action::Submit);
+        assert_eq!(app.mode, AppMode::DueInput(goal.clone()));
+        assert_eq!(app.tree.nodes[&goal].due, None);
+
+        app.input_buffer = "2024-05-01".to_string();
+        app.dispatch(Action::Submit);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(
+            app.tree.nodes[&goal].due,
+            NaiveDate::from_ymd_opt(2024, 5, 1)
+        );
+
+        // 再次打开时预填当前值，清空后提交即清除
+        app.dispatch(Action::StartEditDue);
+        assert_eq!(app.input_buffer, "2024-05-01");
+        app.input_buffer.clear();
+        app.dispatch(Action::Submit);
+        assert_eq!(app.tree.nodes[&goal].due, None);
+        assert_eq!(app.message.as_deref(), Some("已清除截止日期"));
+    }
+
+    #[test]
+    fn test_edit_estimate() {
+        let tree = TreeBuilder::new().leaf("goal").build();
+        let mut app = App::new(tree, Config::default());
+        let goal = app.tree.root_ids[0].clone();
+
+        app.dispatch(Action::StartEditEstimate);
+        type_text(&mut app, "abc");
+        app.dispatch(Action::Submit);
+        assert_eq!(app.mode, AppMode::EstimateInput(goal.clone()));
+        assert_eq!(app.tree.nodes[&goal].estimate, 0.0);
+
+        app.input_buffer = "2.5".to_string();
+        app.dispatch(Action::Submit);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.tree.nodes[&goal].estimate, 2.5);
+
+        // 再次打开时预填当前值，清空后提交即清除
+        app.dispatch(Action::StartEditEstimate);
+        assert_eq!(app.input_buffer, "2.5");
+        app.input_buffer.clear();
+        app.dispatch(Action::Submit);
+        assert_eq!(app.tree.nodes[&goal].estimate, 0.0);
+    }
+
+    #[test]
+    fn test_push_recent() {
+        let mut recent = VecDeque::new();
+        for id in ["a", "b", "c"] {
+            push_recent(&mut recent, id.to_string());
+        }
+        assert_eq!(recent, ["c", "b", "a"]);
+
+        // 重复编辑只会移动到最前，不会出现两次
+        push_recent(&mut recent, "a".to_string());
+        assert_eq!(recent, ["a", "c", "b"]);
+
+        for i in 0..RECENT_CAPACITY {
+            push_recent(&mut recent, i.to_string());
+        }
+        assert_eq!(recent.len(), RECENT_CAPACITY);
+        assert_eq!(recent.front().unwrap(), &(RECENT_CAPACITY - 1).to_string());
+        assert!(!recent.contains(&"a".to_string()));
+    }
+
+    #[test]
+    fn test_recent_list_jumps_to_node() {
+        let tree = TreeBuilder::new().leaf("first").leaf("second").build();
+        let mut app = App::new(tree, Config::default());
+        app.dispatch(Action::OpenRecent);
+        assert_eq!(app.mode, AppMode::Normal);
+
+        app.dispatch(Action::StartEditTitle);
+        type_text(&mut app, "!");
+        app.dispatch(Action::Submit);
+        app.dispatch(Action::MoveSelectionDown);
+        app.dispatch(Action::StartAddNode);
+        type_text(&mut app, "new");
+        app.dispatch(Action::Submit);
+        app.dispatch(Action::Submit);
+        let first = app.tree.root_ids[0].clone();
+        assert_eq!(app.recent_ids().len(), 2);
+        assert_eq!(app.recent_ids()[1], first);
+
+        app.dispatch(Action::OpenRecent);
+        app.dispatch(Action::MoveSelectionDown);
+        app.dispatch(Action::MoveSelectionDown);
+        app.dispatch(Action::Submit);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.selected_node_id(), Some(first));
+    }
+
+    #[test]
+    fn test_stale_roots_overlay() {
+        let mut tree = TreeBuilder::new()
+            .leaf("new")
+            .node("old", |b| b.leaf("child"))
+            .build();
+        let old = tree.root_ids[1].clone();
+        let new = tree.root_ids[0].clone();
+        tree.nodes.get_mut(&old).unwrap().created_at -= chrono::Duration::days(20);
+        let mut app = App::new(tree, Config::default());
+
+        app.dispatch(Action::OpenStaleRoots);
+        assert_eq!(app.mode, AppMode::StaleRoots);
+        assert_eq!(app.selected_node_id(), Some(old));
+
+        // 只在根节点之间移动，不会进入子节点
+        app.dispatch(Action::MoveSelectionDown);
+        app.dispatch(Action::MoveSelectionDown);
+        assert_eq!(app.selected_node_id(), Some(new.clone()));
+
+        app.dispatch(Action::ToggleComplete);
+        assert_eq!(app.tree.nodes[&new].status, NodeStatus::Completed);
+        assert_eq!(app.mode, AppMode::StaleRoots);
+
+        app.dispatch(Action::StartDeleteNode);
+        app.dispatch(Action::Submit);
+        assert!(!app.tree.nodes.contains_key(&new));
+        assert_eq!(app.mode, AppMode::Normal);
+    }
+
+    #[test]
+    fn test_external_change_respects_dirty_state() {
+        let path = temp_path("watch.toml");
+        let mut on_disk = FocusTree::new();
+        on_disk
+            .add_node("external".to_string(), "".to_string(), None)
+            .unwrap();
+        crate::storage::save_tree(&mut on_disk, &path, &Strings::default()).unwrap();
+
+        // 无未保存修改：直接重新加载
+        let mut app = App::new(FocusTree::new(), Config::default());
+        app.data_path = Some(path.clone());
+        app.on_external_change();
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.selected_node().unwrap().title, "external");
+
+        // 有未保存修改：先询问，取消则保留内存中的树
+        app.tree
+            .add_node("local".to_string(), "".to_string(), None)
+            .unwrap();
+        app.refresh_display_list();
+        app.on_external_change();
+        assert_eq!(app.mode, AppMode::Confirm(ConfirmAction::ReloadExternal));
+        app.dispatch(Action::Cancel);
+        assert_eq!(app.display_list.len(), 2);
+
+        // 确认则放弃内存中的修改，之前的撤销记录一并清除
+        app.dispatch(Action::StartDeleteNode);
+        app.dispatch(Action::Submit);
+        assert!(app.last_deleted.is_some());
+        app.on_external_change();
+        app.dispatch(Action::Submit);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(app.display_list.len(), 1);
+        assert!(!app.tree.dirty);
+        assert!(app.last_deleted.is_none());
+        assert!(app.recent.is_empty());
+        app.dispatch(Action::Undo);
+        assert_eq!(app.tree.nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_move_refused_with_single_node() {
+        let mut tree = FocusTree::new();
+        tree.add_node("only".to_string(), "".to_string(), None)
+            .unwrap();
+        let mut app = App::new(tree, Config::default());
+
+        app.dispatch(Action::StartMoveNode);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert!(app.message.is_some());
+    }
+
+    #[test]
+    fn test_readonly_never_writes_data_file() {
+        let path = temp_path("readonly.toml");
+        std::fs::write(&path, "原始内容").unwrap();
+        let mut app = App::new(TreeBuilder::new().leaf("a").build(), Config::default());
+        app.data_path = Some(path.clone());
+        app.readonly = true;
+        assert!(app.tree.dirty);
+
+        app.dispatch(Action::SaveNow);
+        assert_eq!(
+            app.message.as_deref(),
+            Some(app.strings.get(Key::MsgReadonly))
+        );
+        assert!(app.save_on_exit().unwrap().is_none());
+        assert!(app.tree.dirty);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "原始内容");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_save_now_clears_dirty() {
+        let path = temp_path("save.toml");
+        let mut app = App::new(FocusTree::new(), Config::default());
+        app.data_path = Some(path.clone());
+
+        app.dispatch(Action::SaveNow);
+        assert!(!path.exists());
+
+        app.dispatch(Action::StartAddNode);
+        type_text(&mut app, "早起");
+        app.dispatch(Action::Submit);
+        app.dispatch(Action::Submit);
+        assert!(app.tree.dirty);
+
+        app.dispatch(Action::SaveNow);
+        assert!(!app.tree.dirty);
+        assert_eq!(load_tree(&path).unwrap().nodes.len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_purge_failed_requires_confirm() {
+        let mut tree = FocusTree::new();
+        let id = tree
+            .add_node("failed".to_string(), "".to_string(), None)
+            .unwrap();
+        tree.fail_node(&id);
+        tree.add_node("active".to_string(), "".to_string(), None)
+            .unwrap();
+        let mut app = App::new(tree, Config::default());
+
+        app.dispatch(Action::StartPurgeFailed);
+        app.dispatch(Action::Cancel);
+        assert_eq!(app.display_list.len(), 2);
+
+        app.dispatch(Action::StartPurgeFailed);
+        app.dispatch(Action::Submit);
+        assert_eq!(app.display_list.len(), 1);
+        assert_eq!(app.selected_node().unwrap().title, "active");
+    }
+
+    #[test]
+    fn test_merge_roots_requires_confirm() {
+        let mut tree = TreeBuilder::new()
+            .node("健康", |b| b.leaf("跑步"))
+            .node("健康", |b| b.leaf("早睡"))
+            .node("健康", |b| b.leaf("饮食"))
+            .build();
+        let noted = tree.root_ids[2].clone();
+        tree.nodes.get_mut(&noted).unwrap().content = "笔记".to_string();
+        let mut app = App::new(tree, Config::default());
+
+        app.dispatch(Action::MergeDuplicateRoots);
+        app.dispatch(Action::Cancel);
+        assert_eq!(app.tree.root_ids.len(), 3);
+
+        app.dispatch(Action::MergeDuplicateRoots);
+        app.dispatch(Action::Submit);
+        assert_eq!(app.tree.root_ids.len(), 2);
+        assert_eq!(app.tree.nodes[&noted].content, "笔记");
+        assert_eq!(
+            app.message.as_deref(),
+            Some("合并了 1 个同名根节点，1 个有内容或日志的已保留")
+        );
+    }
+
+    #[test]
+    fn test_delete_word_before_end() {
+        let cases = [
+            ("hello world", "hello "),
+            ("hello world  ", "hello "),
+            ("single", ""),
+            ("坚持 每天早起", "坚持 "),
+            ("坚持每天早起", ""),
+            ("运动　健身", "运动　"), // 全角空格
+            ("", ""),
+            ("   ", ""),
+        ];
+        for (input, expected) in cases {
+            let mut text = input.to_string();
+            delete_word_before_end(&mut text);
+            assert_eq!(text, expected, "input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_delete_to_line_start() {
+        let mut text = "第一行\n第二行".to_string();
+        delete_to_line_start(&mut text);
+        assert_eq!(text, "第一行\n");
+
+        let mut text = "早起 readme".to_string();
+        delete_to_line_start(&mut text);
+        assert!(text.is_empty());
+    }
+
+    #[test]
+    fn test_move_confirm_threshold() {
+        assert!(!needs_move_confirm(1, 10));
+        assert!(!needs_move_confirm(10, 10));
+        assert!(needs_move_confirm(11, 10));
+
+        let tree = TreeBuilder::new()
+            .node("big", |b| b.leaf("a").leaf("b"))
+            .leaf("target")
+            .build();
+        let config = Config {
+            move_confirm_threshold: 2,
+            ..Config::default()
+        };
+        let mut app = App::new(tree, config);
+
+        app.dispatch(Action::StartMoveNode);
+        app.selected_index = 3;
+        app.dispatch(Action::Submit);
+        assert!(matches!(
+            app.mode,
+            AppMode::Confirm(ConfirmAction::Move { .. })
+        ));
+        assert_eq!(app.tree.root_ids.len(), 2);
+
+        app.dispatch(Action::Submit);
+        assert_eq!(app.mode, AppMode::Normal);
+        assert_eq!(app.tree.root_ids.len(), 1);
+        assert_eq!(app.selected_node().unwrap().title, "big");
+        assert_eq!(app.tree.level(&app.selected_node_id().unwrap()), 2);
+    }
+
+    #[test]
+    fn test_next_tag_color() {
+        assert_eq!(next_tag_color(None).as_deref(), Some("red"));
+        assert_eq!(next_tag_color(Some("red")).as_deref(), Some("yellow"));
+        assert_eq!(next_tag_color(Some("cyan")), None);
+        assert_eq!(next_tag_color(Some("#123456")).as_deref(), Some("red"));
+    }
+
+    #[test]
+    fn test_selection_follows_added_node() {
+        let tree = TreeBuilder::new()
+            .node("parent", |b| b.leaf("old child"))
+            .leaf("sibling")
+            .build();
+        let mut app = App::new(tree, Config::default());
+
+        app.dispatch(Action::StartAddNode);
+        type_text(&mut app, "new child");
+        app.dispatch(Action::Submit);
+        app.dispatch(Action::Submit);
+
+        assert_eq!(app.selected_node().unwrap().title, "new child");
+        assert_eq!(app.selected_index, 2);
+    }
+
+    #[test]
+    fn test_selection_after_delete_lands_on_parent() {
+        let tree = TreeBuilder::new()
+            .leaf("first")
+            .node("parent", |b| b.leaf("a").leaf("b"))
+            .build();
+        let mut app = App::new(tree, Config::default());
+        app.selected_index = 3;
+        assert_eq!(app.selected_node().unwrap().title, "b");
+
+        app.dispatch(Action::StartDeleteNode);
+        app.dispatch(Action::Submit);
+        assert_eq!(app.selected_node().unwrap().title, "parent");
+
+        // 删除根节点时沿用原索引
+        app.dispatch(Action::StartDeleteNode);
+        app.dispatch(Action::Submit);
+        assert_eq!(app.selected_node().unwrap().title, "first");
+    }
+
+    #[test]
+    fn test_palette_executes_action() {
+        let mut app = App::new(FocusTree::new(), Config::default());
+        app.dispatch(Action::OpenCommandPalette);
+        for c in "添加".chars() {
+            app.dispatch(Action::Input(c));
+        }
+        app.dispatch(Action::Submit);
+        assert_eq!(app.mode, AppMode::AddingNode);
     }
 }
@@ -4,14 +4,17 @@
 //! - Model (state.rs): App 结构体及其状态数据
 //! - View (view/): 纯函数，将 State 映射为 UI
 //! - Intent (actions.rs): 用户交互转化为明确的语义化 Action
+//!
+//! text.rs 为按显示宽度处理文本的工具函数，状态逻辑与界面渲染共用
 
 pub mod actions;
 pub mod input;
 pub mod logic;
 pub mod state;
+pub mod text;
 pub mod view;
 
 // Re-export for convenience
-pub use input::handle_key_event;
+pub use input::handle_key_events;
 pub use state::App;
 pub use view::render;
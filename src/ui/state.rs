@@ -2,7 +2,23 @@
 //!
 //! 包含应用状态结构体及相关枚举
 
-use crate::models::{FocusNode, FocusTree};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::process::Child;
+use std::time::{Duration, Instant};
+
+use ratatui::style::Color;
+use ratatui::widgets::ListState;
+
+use crate::config::Config;
+use crate::i18n::{Key, Strings};
+use crate::models::{DisplayFilter, FocusNode, FocusTree, NodeStatus, RemovedSubtree, TreeIssue};
+
+/// 最近修改列表最多保留的节点数
+pub const RECENT_CAPACITY: usize = 10;
+
+/// 操作被拒绝时提示栏闪烁的时长
+pub const FLASH_DURATION: Duration = Duration::from_millis(200);
 
 /// 应用状态
 pub struct App {
@@ -13,7 +29,35 @@ pub struct App {
     pub input_buffer: String,
     pub input_field: InputField,
     pub message: Option<String>,
-    pub temp_title: String, // Store title when moving to content input
+    pub temp_title: String,         // Store title when moving to content input
+    pub temp_content: String,       // 编辑节点时暂存未聚焦的内容
+    pub add_parent: Option<String>, // 添加对话框的父节点，连续添加时保持不变
+    pub palette_index: usize,       // 命令面板 / 最近修改列表中选中的条目
+    pub details_tab: DetailsTab,
+    pub display_filter: DisplayFilter,
+    pub expanded: HashSet<String>, // 手动展开过的节点，自动折叠已完成子树时保持展开
+    pub title_colors: HashMap<String, Color>, // 已解析的颜色标签，加载与切换颜色时更新，绘制时不再解析
+    pub config: Config,
+    pub strings: Strings,                      // 当前界面语言的文字
+    pub tree_state: ListState,                 // 节点列表的选中与滚动状态，跨帧保留
+    pub center_pending: bool,                  // 下一帧将选中行居中（按实际渲染的行高计算）
+    pub row_h_scroll: u16,                     // 选中行标题的横向滚动列数，选中其他节点时归零
+    pub audit_path: Option<PathBuf>,           // 活动日志路径，None 表示不记录
+    pub data_path: Option<PathBuf>,            // 数据文件路径，用于重新加载
+    pub select_after_refresh: Option<String>,  // 下次刷新显示列表后要选中的节点
+    pub last_deleted: Option<RemovedSubtree>,  // 最近一次删除的子树，可用 Ctrl+Z 恢复
+    pub last_rename: Option<(String, String)>, // 最近一次重命名的节点 ID 与原标题，可用 Ctrl+Z 恢复
+    pub recent: VecDeque<String>,              // 最近添加或编辑的节点，最新的在前
+    pub readonly: bool,                        // 只读模式：忽略所有修改操作
+    pub ephemeral: bool,                       // 临时会话：不读写数据文件
+    pub show_ids: bool,                        // 在标题旁显示节点 ID 前缀，便于对照活动日志
+    pub show_depth: bool,                      // 在标题前显示层级（L1 为根节点）
+    pub pending_key: Option<char>,             // 双键序列（如 `]o`）中已按下的第一个键
+    pub flash_until: Option<Instant>,          // 提示栏闪烁的截止时间，由主循环在过期后清除
+    pub bell_pending: bool,                    // 下一帧需要响铃
+    pub editor_request: Option<String>,        // 等待主循环暂停终端、用外部编辑器编辑内容的节点
+    pub hook_children: Vec<Child>,             // 仍在后台运行的完成命令
+    pub save_failed: bool,                     // 数据目录不可写或最近一次保存失败，状态栏持续提示
 }
 
 /// 应用模式
@@ -21,17 +65,87 @@ pub struct App {
 pub enum AppMode {
     Normal,
     AddingNode,
-    EditingContent(String), // String is the node ID being edited
-    EditingTitle(String),   // String is the node ID being edited
-    MovingNode(String),     // String is the node ID to move
+    EditingNode(String),   // String is the node ID being edited
+    MovingNode(String),    // String is the node ID to move
+    LinkingNode(String),   // 选择要关联的节点，String 为发起关联的节点 ID
+    CommandPalette,        // input_buffer 为筛选关键字
+    ReplaceFrom,           // 输入要查找的文字
+    ReplaceTo(String),     // String 为要查找的文字，input_buffer 为替换文字
+    GotoInput,             // 输入要跳转的节点 ID
+    FailReason(String),    // 确认失败后输入原因（可留空），String 为要标记失败的节点 ID
+    EstimateInput(String), // 输入预估工作量，String 为节点 ID
+    DueInput(String),      // 输入截止日期，String 为节点 ID
+    StaleRoots,            // 按持续天数排列的根节点列表，操作作用于选中的根节点
+    RecentList,            // 最近修改的节点列表，palette_index 为选中的条目
+    KeyHelp,               // 全部快捷键一览
     Confirm(ConfirmAction),
 }
 
+impl AppMode {
+    /// 该模式下按键是否用于输入文字
+    pub fn is_text_input(&self) -> bool {
+        matches!(
+            self,
+            AppMode::AddingNode
+                | AppMode::EditingNode(_)
+                | AppMode::CommandPalette
+                | AppMode::ReplaceFrom
+                | AppMode::ReplaceTo(_)
+                | AppMode::GotoInput
+                | AppMode::FailReason(_)
+                | AppMode::EstimateInput(_)
+                | AppMode::DueInput(_)
+        )
+    }
+}
+
+/// 详情面板标签页
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DetailsTab {
+    Content,
+    Log,
+    Meta,
+}
+
+impl DetailsTab {
+    pub const ALL: [DetailsTab; 3] = [DetailsTab::Content, DetailsTab::Log, DetailsTab::Meta];
+
+    /// 循环切换到下一个标签页
+    pub fn next(self) -> Self {
+        match self {
+            DetailsTab::Content => DetailsTab::Log,
+            DetailsTab::Log => DetailsTab::Meta,
+            DetailsTab::Meta => DetailsTab::Content,
+        }
+    }
+
+    pub fn title(self, strings: &Strings) -> &'static str {
+        strings.get(match self {
+            DetailsTab::Content => Key::TabContent,
+            DetailsTab::Log => Key::TabLog,
+            DetailsTab::Meta => Key::TabMeta,
+        })
+    }
+}
+
 /// 确认操作类型
 #[derive(Debug, Clone, PartialEq)]
 pub enum ConfirmAction {
     Delete(String),
     Fail(String),
+    Move {
+        node: String,
+        target: Option<String>, // None 表示移动为根节点
+    },
+    PurgeFailed,
+    MergeRoots, // 合并同名根节点，带有自身数据的重复根节点保留
+    Replace {
+        from: String,
+        to: String,
+    },
+    ReloadExternal,             // 放弃未保存的修改，重新加载外部修改过的数据文件
+    QuitDiscard,                // 放弃本次会话的修改并退出
+    RepairTree(Vec<TreeIssue>), // 完整性检查发现的问题，确认后修复
 }
 
 /// 输入字段类型
@@ -43,7 +157,8 @@ pub enum InputField {
 
 impl App {
     /// 创建新的应用实例
-    pub fn new(tree: FocusTree) -> Self {
+    pub fn new(mut tree: FocusTree, config: Config) -> Self {
+        tree.max_depth = config.max_depth;
         let mut app = Self {
             tree,
             selected_index: 0,
@@ -53,20 +168,82 @@ impl App {
             input_field: InputField::Title,
             message: None,
             temp_title: String::new(),
+            temp_content: String::new(),
+            add_parent: None,
+            palette_index: 0,
+            details_tab: DetailsTab::Content,
+            display_filter: DisplayFilter {
+                sort_by_status: config.sort_by_status,
+                ..DisplayFilter::default()
+            },
+            expanded: HashSet::new(),
+            title_colors: HashMap::new(),
+            strings: Strings::new(config.lang),
+            config,
+            tree_state: ListState::default(),
+            center_pending: false,
+            row_h_scroll: 0,
+            audit_path: None,
+            data_path: None,
+            select_after_refresh: None,
+            last_deleted: None,
+            last_rename: None,
+            recent: VecDeque::new(),
+            readonly: false,
+            ephemeral: false,
+            show_ids: false,
+            show_depth: false,
+            pending_key: None,
+            flash_until: None,
+            bell_pending: false,
+            editor_request: None,
+            hook_children: Vec::new(),
+            save_failed: false,
         };
+        app.refresh_title_colors();
+        // 恢复上次选中的节点，节点已不存在时选中第一个
+        app.select_after_refresh = app.tree.selected_id.clone();
         app.refresh_display_list();
         app
     }
 
     /// 刷新显示列表
+    ///
+    /// 优先选中 `select_after_refresh` 指定的节点，其次保持原先选中的节点；
+    /// 原节点被隐藏时改选最近的可见节点，都找不到时才沿用原索引
     pub fn refresh_display_list(&mut self) {
+        let previous = self.selected_node_id();
+        let mut filter = self.display_filter.clone();
+        if self.config.collapse_completed {
+            filter.collapsed.extend(
+                self.tree
+                    .nodes
+                    .keys()
+                    .filter(|id| self.is_collapsed(id))
+                    .cloned(),
+            );
+        }
         self.display_list = self
             .tree
-            .flatten_for_display()
+            .flatten_filtered(&filter)
             .iter()
             .map(|(depth, node)| (*depth, node.id.clone()))
             .collect();
 
+        let target = self.select_after_refresh.take().or(previous.clone());
+        if let Some(id) = target
+            && self.select_node(&id)
+        {
+            return;
+        }
+        if let Some(index) = previous
+            .as_deref()
+            .and_then(|id| nearest_visible(&self.tree, id, &self.display_list))
+        {
+            self.selected_index = index;
+            return;
+        }
+
         // 确保选中索引有效
         if self.display_list.is_empty() {
             self.selected_index = 0;
@@ -75,6 +252,110 @@ impl App {
         }
     }
 
+    /// 重新解析所有节点的颜色标签，在加载或重新加载树后调用；无法识别的颜色忽略
+    pub fn refresh_title_colors(&mut self) {
+        self.title_colors = self
+            .tree
+            .nodes
+            .values()
+            .filter_map(|node| Some((node.id.clone(), parse_color(node.color.as_deref()?)?)))
+            .collect();
+    }
+
+    /// 将当前选中的节点记入树中以便随数据文件保存，选中节点有变化时标记为待保存
+    pub fn remember_selection(&mut self) {
+        let id = self.selected_node_id();
+        if self.tree.selected_id != id {
+            self.tree.selected_id = id;
+            self.tree.dirty = true;
+        }
+    }
+
+    /// 节点的子树是否折叠：手动折叠，或开启 `collapse_completed` 时已完成且未手动展开
+    pub fn is_collapsed(&self, id: &str) -> bool {
+        self.display_filter.collapsed.contains(id)
+            || (self.config.collapse_completed
+                && !self.expanded.contains(id)
+                && self
+                    .tree
+                    .nodes
+                    .get(id)
+                    .is_some_and(|n| n.status == NodeStatus::Completed))
+    }
+
+    /// 手动展开节点，返回之前是否折叠（不刷新显示列表）
+    pub fn expand(&mut self, id: &str) -> bool {
+        let was_collapsed = self.is_collapsed(id);
+        self.display_filter.collapsed.remove(id);
+        self.expanded.insert(id.to_string());
+        was_collapsed
+    }
+
+    /// 手动折叠节点，返回之前是否展开（不刷新显示列表）
+    pub fn collapse(&mut self, id: &str) -> bool {
+        let was_expanded = !self.is_collapsed(id);
+        self.expanded.remove(id);
+        self.display_filter.collapsed.insert(id.to_string());
+        was_expanded
+    }
+
+    /// 确保节点出现在显示列表中（展开折叠的祖先，必要时打开归档显示），返回是否可见
+    pub fn reveal(&mut self, id: &str) -> bool {
+        if !self.tree.nodes.contains_key(id) {
+            return false;
+        }
+        let visible = |app: &Self| app.display_list.iter().any(|(_, shown)| shown == id);
+        if !visible(self) {
+            // 先展开所有祖先，仍不可见时再显示已归档节点
+            let mut path = self.tree.path_ids(id);
+            path.pop();
+            for ancestor in &path {
+                self.expand(ancestor);
+            }
+            self.refresh_display_list();
+        }
+        if !visible(self) {
+            self.display_filter.show_archived = true;
+            self.refresh_display_list();
+        }
+        visible(self)
+    }
+
+    /// 选中指定节点，节点不在显示列表中时返回 false
+    pub fn select_node(&mut self, id: &str) -> bool {
+        match self.display_list.iter().position(|(_, shown)| shown == id) {
+            Some(index) => {
+                self.selected_index = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 可见的根节点 ID，按持续天数从多到少排列
+    pub fn stale_roots(&self) -> Vec<String> {
+        self.tree
+            .roots_by_staleness()
+            .into_iter()
+            .filter(|node| self.display_list.iter().any(|(_, id)| id == &node.id))
+            .map(|node| node.id.clone())
+            .collect()
+    }
+
+    /// 最近修改且仍存在的节点 ID，最新的在前
+    pub fn recent_ids(&self) -> Vec<String> {
+        self.recent
+            .iter()
+            .filter(|id| self.tree.nodes.contains_key(*id))
+            .cloned()
+            .collect()
+    }
+
+    /// 提示栏此刻是否处于闪烁中
+    pub fn is_flashing(&self, now: Instant) -> bool {
+        flash_active(self.flash_until, now)
+    }
+
     /// 获取当前选中的节点
     pub fn selected_node(&self) -> Option<&FocusNode> {
         self.display_list
@@ -89,3 +370,158 @@ impl App {
             .map(|(_, id)| id.clone())
     }
 }
+
+/// 闪烁截止时间尚未到达
+pub fn flash_active(until: Option<Instant>, now: Instant) -> bool {
+    until.is_some_and(|until| now < until)
+}
+
+/// 节点不在显示列表中时，找到最近的可见节点：依次尝试最近的可见祖先、
+/// 后面的兄弟节点、前面的兄弟节点
+fn nearest_visible(tree: &FocusTree, id: &str, list: &[(usize, String)]) -> Option<usize> {
+    let position = |id: &str| list.iter().position(|(_, shown)| shown == id);
+    let node = tree.nodes.get(id)?;
+
+    let mut path = tree.path_ids(id);
+    path.pop();
+    if let Some(index) = path.iter().rev().find_map(|ancestor| position(ancestor)) {
+        return Some(index);
+    }
+
+    let siblings = if node.is_root() {
+        &tree.root_ids
+    } else {
+        tree.children_map.get(&node.parent_id)?
+    };
+    let at = siblings.iter().position(|sibling| sibling == id)?;
+    siblings[at + 1..]
+        .iter()
+        .chain(siblings[..at].iter().rev())
+        .find_map(|sibling| position(sibling))
+}
+
+/// 解析颜色标签（颜色名或 `#rrggbb`），无法识别时返回 None
+pub fn parse_color(name: &str) -> Option<Color> {
+    name.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TreeBuilder;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("red"), Some(Color::Red));
+        assert_eq!(parse_color(" Blue "), Some(Color::Blue));
+        assert_eq!(parse_color("#ff8800"), Some(Color::Rgb(0xff, 0x88, 0x00)));
+        assert_eq!(parse_color("not-a-color"), None);
+    }
+
+    #[test]
+    fn test_title_colors_cached() {
+        let mut tree = TreeBuilder::new().leaf("a").leaf("b").build();
+        let ids = tree.root_ids.clone();
+        tree.nodes.get_mut(&ids[0]).unwrap().color = Some("#ff8800".to_string());
+        tree.nodes.get_mut(&ids[1]).unwrap().color = Some("not-a-color".to_string());
+        let mut app = App::new(tree, Config::default());
+        assert_eq!(
+            app.title_colors.get(&ids[0]),
+            Some(&Color::Rgb(0xff, 0x88, 0x00))
+        );
+        assert_eq!(app.title_colors.get(&ids[1]), None);
+
+        // 切换颜色时同步更新
+        app.move_down();
+        app.cycle_color();
+        let color = app.tree.nodes[&ids[1]]
+            .color
+            .as_deref()
+            .and_then(parse_color);
+        assert!(color.is_some());
+        assert_eq!(app.title_colors.get(&ids[1]).copied(), color);
+    }
+
+    fn id_of(app: &App, title: &str) -> String {
+        app.tree
+            .nodes
+            .values()
+            .find(|n| n.title == title)
+            .unwrap()
+            .id
+            .clone()
+    }
+
+    #[test]
+    fn test_flash_active() {
+        let now = Instant::now();
+        assert!(!flash_active(None, now));
+        assert!(flash_active(Some(now + FLASH_DURATION), now));
+        assert!(!flash_active(Some(now), now));
+        assert!(!flash_active(Some(now), now + FLASH_DURATION));
+    }
+
+    #[test]
+    fn test_hidden_selection_falls_back_to_parent() {
+        let tree = TreeBuilder::new()
+            .node("parent", |b| b.leaf("first").leaf("second"))
+            .build();
+        let mut app = App::new(tree, Config::default());
+        let second = id_of(&app, "second");
+        app.select_node(&second);
+
+        app.tree.toggle_complete(&second);
+        app.tree.archive_completed();
+        app.refresh_display_list();
+        assert_eq!(app.selected_node_id(), Some(id_of(&app, "parent")));
+    }
+
+    #[test]
+    fn test_hidden_selection_falls_back_to_next_sibling() {
+        let tree = TreeBuilder::new()
+            .leaf("first")
+            .leaf("second")
+            .leaf("third")
+            .build();
+        let mut app = App::new(tree, Config::default());
+        let second = id_of(&app, "second");
+        app.select_node(&second);
+
+        app.tree.toggle_complete(&second);
+        app.tree.archive_completed();
+        app.refresh_display_list();
+        assert_eq!(app.selected_node_id(), Some(id_of(&app, "third")));
+
+        // 没有后面的兄弟节点时选中前一个
+        let third = id_of(&app, "third");
+        app.tree.toggle_complete(&third);
+        app.tree.archive_completed();
+        app.refresh_display_list();
+        assert_eq!(app.selected_node_id(), Some(id_of(&app, "first")));
+    }
+
+    #[test]
+    fn test_selection_round_trips_through_data() {
+        let tree = TreeBuilder::new()
+            .node("a", |b| b.leaf("x"))
+            .leaf("b")
+            .build();
+        let mut app = App::new(tree, Config::default());
+        let x = id_of(&app, "x");
+        app.select_node(&x);
+        app.remember_selection();
+
+        let data = app.tree.to_data();
+        let content = toml::to_string_pretty(&data).unwrap();
+        let loaded = FocusTree::from_data(toml::from_str(&content).unwrap());
+        let app = App::new(loaded, Config::default());
+        assert_eq!(app.selected_index, 1);
+        assert_eq!(app.selected_node_id(), Some(x));
+
+        // 记录的节点已不存在时回到第一个
+        let mut data = data;
+        data.meta.selected_id = Some("missing".to_string());
+        let app = App::new(FocusTree::from_data(data), Config::default());
+        assert_eq!(app.selected_index, 0);
+    }
+}
@@ -0,0 +1,95 @@
+//! 按终端显示宽度处理文本（中文等宽字符占两列），供状态逻辑与界面渲染共用
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// 按显示宽度截断文本，超出时以 `…` 结尾
+pub fn truncate_to_width(text: &str, max_width: usize) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+    let mut result = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if width + w + 1 > max_width {
+            break;
+        }
+        result.push(c);
+        width += w;
+    }
+    if max_width > 0 {
+        result.push('…');
+    }
+    result
+}
+
+/// 跳过文本开头 `cols` 列后的部分；宽字符跨过边界时整个跳过
+pub fn skip_width(text: &str, cols: usize) -> &str {
+    let mut width = 0;
+    for (index, c) in text.char_indices() {
+        if width >= cols {
+            return &text[index..];
+        }
+        width += c.width().unwrap_or(0);
+    }
+    ""
+}
+
+/// 按显示宽度将文本切分为多行，每行至少一个字符
+pub fn wrap_to_width(text: &str, max_width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if width + w > max_width && !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+            width = 0;
+        }
+        line.push(c);
+        width += w;
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("short", 10), "short");
+        assert_eq!(truncate_to_width("abcdefgh", 5), "abcd…");
+        // 中文字符占 2 列
+        assert_eq!(truncate_to_width("坚持每天早起", 7), "坚持每…");
+        assert_eq!(truncate_to_width("坚持每天早起", 0), "");
+    }
+
+    #[test]
+    fn test_wrap_to_width() {
+        assert_eq!(wrap_to_width("short", 10), vec!["short"]);
+        // 中文字符占 2 列，7 列每行最多 3 个字
+        assert_eq!(
+            wrap_to_width("坚持每天早起锻炼身体", 7),
+            vec!["坚持每", "天早起", "锻炼身", "体"]
+        );
+        assert_eq!(wrap_to_width("ab坚持", 3), vec!["ab", "坚", "持"]);
+        // 宽度不足一个字符时仍逐字前进
+        assert_eq!(wrap_to_width("坚持", 1), vec!["坚", "持"]);
+        assert_eq!(wrap_to_width("", 5), vec![""]);
+    }
+
+    #[test]
+    fn test_skip_width() {
+        assert_eq!(skip_width("hello world", 0), "hello world");
+        assert_eq!(skip_width("hello world", 6), "world");
+        assert_eq!(skip_width("hello", 10), "");
+        // 宽字符占两列，不会从中间切开
+        assert_eq!(skip_width("国策树", 2), "策树");
+        assert_eq!(skip_width("国策树", 3), "树");
+        assert_eq!(skip_width("a国b", 2), "b");
+    }
+}
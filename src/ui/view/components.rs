@@ -6,8 +6,12 @@ use ratatui::{
     Frame,
     layout::Rect,
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    text::Line,
+    widgets::{Block, Borders, Clear, Paragraph},
 };
+use unicode_width::UnicodeWidthStr;
+
+use crate::ui::text::wrap_to_width;
 
 /// [组件] 弹窗基础框架
 pub fn render_dialog_framework(frame: &mut Frame, area: Rect, title: &str) -> Rect {
@@ -21,7 +25,28 @@ pub fn render_dialog_framework(frame: &mut Frame, area: Rect, title: &str) -> Re
     inner
 }
 
+/// 输入框中光标（位于文本末尾）按宽度折行后的位置（行, 列）
+pub fn caret_position(value: &str, width: u16) -> (u16, u16) {
+    let width = width.max(1) as usize;
+    let lines = wrap_to_width(value, width);
+    let last = lines.last().map_or(0, |line| line.width());
+    if last >= width {
+        // 最后一行已写满，光标换到下一行行首
+        (lines.len() as u16, 0)
+    } else {
+        (lines.len() as u16 - 1, last as u16)
+    }
+}
+
+/// 让光标保持可见所需的纵向滚动行数，`area` 为含边框的输入框区域
+pub fn input_scroll(value: &str, area: Rect) -> u16 {
+    let (row, _) = caret_position(value, area.width.saturating_sub(2));
+    (row + 1).saturating_sub(area.height.saturating_sub(2))
+}
+
 /// [组件] 带有标题和样式的输入框
+///
+/// 文本按字符宽度折行后向下滚动 `scroll` 行，获得焦点时在文本末尾显示光标
 pub fn render_input_widget(
     frame: &mut Frame,
     area: Rect,
@@ -29,6 +54,7 @@ pub fn render_input_widget(
     value: &str,
     is_focused: bool,
     active_color: Color,
+    scroll: u16,
 ) {
     let style = if is_focused {
         Style::default()
@@ -38,9 +64,22 @@ pub fn render_input_widget(
         Style::default().fg(Color::Gray)
     };
 
-    let input = Paragraph::new(value)
+    let width = area.width.saturating_sub(2);
+    let lines: Vec<Line> = wrap_to_width(value, width.max(1) as usize)
+        .into_iter()
+        .map(Line::from)
+        .collect();
+    let input = Paragraph::new(lines)
         .style(style)
-        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0))
         .block(Block::default().title(title).borders(Borders::ALL));
     frame.render_widget(input, area);
+
+    if is_focused {
+        let (row, col) = caret_position(value, width);
+        let visible_rows = area.height.saturating_sub(2);
+        if let Some(row) = row.checked_sub(scroll).filter(|row| *row < visible_rows) {
+            frame.set_cursor_position((area.x + 1 + col, area.y + 1 + row));
+        }
+    }
 }
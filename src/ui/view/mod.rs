@@ -7,47 +7,143 @@ pub mod layouts;
 
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
 };
 
-use super::state::{App, AppMode, ConfirmAction, InputField};
-use crate::models::NodeStatus;
-use components::{render_dialog_framework, render_input_widget};
+use chrono::Local;
+use std::time::Instant;
+use unicode_width::UnicodeWidthStr;
+
+use super::input::key_hint;
+use super::logic::{DUE_FORMAT, center_offset, filter_actions};
+use super::state::{App, AppMode, ConfirmAction, DetailsTab, InputField};
+use super::text::{skip_width, truncate_to_width, wrap_to_width};
+use crate::config::{DeleteMode, LayoutConfig, RootGrouping, Theme};
+use crate::i18n::{Key, Strings};
+use crate::models::{FocusNode, FocusTree, NodeStatus, StatusCounts, TreeIssue};
+use components::{input_scroll, render_dialog_framework, render_input_widget};
 use layouts::centered_rect;
 
+/// 终端宽度达到该值时，树与详情左右并排
+const SIDE_BY_SIDE_MIN_WIDTH: u16 = 120;
+
+/// 主体区域的布局方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BodyLayout {
+    /// 树在上、详情在下
+    Stacked,
+    /// 树在左、详情在右（详情可占满整个高度）
+    SideBySide,
+}
+
+/// 根据终端宽度选择主体布局
+fn body_layout(width: u16) -> BodyLayout {
+    if width >= SIDE_BY_SIDE_MIN_WIDTH {
+        BodyLayout::SideBySide
+    } else {
+        BodyLayout::Stacked
+    }
+}
+
+/// 上下布局时节点列表至少保留的高度（含边框）
+const MIN_TREE_HEIGHT: u16 = 4;
+
+/// 整体的纵向布局：标题、树 + 详情、帮助 + 状态栏，隐藏的栏高度为 0
+fn frame_constraints(layout: &LayoutConfig) -> [Constraint; 3] {
+    [
+        Constraint::Length(if layout.show_title { 3 } else { 0 }),
+        Constraint::Min(10),
+        Constraint::Length(if layout.show_help { 3 } else { 1 }),
+    ]
+}
+
+/// 上下布局时树与详情的高度：详情过高时缩小，保证树至少有 `MIN_TREE_HEIGHT` 行
+fn stacked_constraints(details_height: u16, body_height: u16) -> [Constraint; 2] {
+    let details = details_height.min(body_height.saturating_sub(MIN_TREE_HEIGHT));
+    [
+        Constraint::Min(MIN_TREE_HEIGHT),
+        Constraint::Length(details),
+    ]
+}
+
 /// 渲染 UI
 pub fn render(frame: &mut Frame, app: &mut App) {
+    let layout = app.config.layout.clone();
     let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(frame_constraints(&layout))
+        .split(frame.area());
+
+    let body = match body_layout(frame.area().width) {
+        BodyLayout::Stacked => Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(stacked_constraints(layout.details_height, chunks[1].height))
+            .split(chunks[1]),
+        BodyLayout::SideBySide => Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(chunks[1]),
+    };
+
+    let bottom = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // 标题
-            Constraint::Min(10),   // 树
-            Constraint::Length(6), // 详情
-            Constraint::Length(3), // 帮助
+            Constraint::Length(if layout.show_help { 2 } else { 0 }), // 快捷键提示（上边框 + 一行）
+            Constraint::Length(1),                                    // 状态栏
         ])
-        .split(frame.area());
+        .split(chunks[2]);
 
-    render_title(frame, chunks[0]);
-    render_tree(frame, app, chunks[1]);
-    render_details(frame, app, chunks[2]);
-    render_help(frame, app, chunks[3]);
+    if layout.show_title {
+        render_title(frame, app, chunks[0]);
+    }
+    render_tree(frame, app, body[0]);
+    render_details(frame, app, body[1]);
+    if layout.show_help {
+        render_help(frame, app, bottom[0]);
+    }
+    render_status(frame, app, bottom[1]);
 
     // 渲染弹窗
     match &app.mode {
         AppMode::AddingNode => render_add_dialog(frame, app),
-        AppMode::EditingContent(_) => render_edit_content_dialog(frame, app),
-        AppMode::EditingTitle(_) => render_edit_title_dialog(frame, app),
-        AppMode::MovingNode(_) => {} // 移动模式下不需要额外弹窗，使用底部提示
-        AppMode::Confirm(action) => render_confirm_dialog(frame, action),
+        AppMode::EditingNode(_) => render_edit_dialog(frame, app),
+        AppMode::MovingNode(_) | AppMode::LinkingNode(_) => {} // 移动 / 关联模式下不需要额外弹窗，使用底部提示
+        AppMode::CommandPalette => render_command_palette(frame, app),
+        AppMode::ReplaceFrom | AppMode::ReplaceTo(_) => render_replace_dialog(frame, app),
+        AppMode::GotoInput => render_goto_dialog(frame, app),
+        AppMode::FailReason(_) => render_fail_reason_dialog(frame, app),
+        AppMode::EstimateInput(_) => render_estimate_dialog(frame, app),
+        AppMode::DueInput(_) => render_due_dialog(frame, app),
+        AppMode::StaleRoots => render_stale_roots(frame, app),
+        AppMode::RecentList => render_recent_list(frame, app),
+        AppMode::KeyHelp => render_key_help(frame, app),
+        AppMode::Confirm(action) => render_confirm_dialog(frame, app, action),
         _ => {}
     }
 }
 
-fn render_title(frame: &mut Frame, area: Rect) {
-    let title = Paragraph::new("🌳 RSIP 国策树")
+fn render_title(frame: &mut Frame, app: &App, area: Rect) {
+    let percent = app
+        .tree
+        .count_by_status()
+        .completion_percent(app.config.completion_counts_all);
+    let mut spans = vec![
+        Span::raw(app.strings.get(Key::AppTitle)),
+        Span::styled(
+            app.strings.format(Key::CompletionPercent, &[&percent]),
+            Style::default().fg(Color::Green),
+        ),
+    ];
+    if app.ephemeral {
+        spans.push(Span::styled(
+            app.strings.get(Key::EphemeralBadge),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    let title = Paragraph::new(Line::from(spans))
         .style(
             Style::default()
                 .fg(Color::Cyan)
@@ -57,114 +153,668 @@ fn render_title(frame: &mut Frame, area: Rect) {
     frame.render_widget(title, area);
 }
 
+/// 选中行前的指示符
+const HIGHLIGHT_SYMBOL: &str = "▶ ";
+
+/// 节点列表中每行可用于文字的宽度（扣除左右边框与选中指示符）
+fn tree_row_width(area_width: u16) -> usize {
+    (area_width as usize).saturating_sub(2 + HIGHLIGHT_SYMBOL.width())
+}
+
+/// 根节点图标：有子节点与没有子节点的根使用不同图标
+fn root_glyph(theme: &Theme, is_leaf: bool) -> &str {
+    if is_leaf {
+        &theme.root_leaf
+    } else {
+        &theme.root_branch
+    }
+}
+
+/// 根节点所属周期的标签，节点设置了 `period` 时优先使用
+fn root_period(node: &FocusNode, grouping: RootGrouping) -> Option<String> {
+    grouping
+        .period_label(&node.created_at)
+        .map(|label| node.period.clone().unwrap_or(label))
+}
+
+/// 每一行之前要插入的周期标题：周期与上一个根节点不同时插入，按树中原有顺序分组
+fn group_headers(
+    tree: &FocusTree,
+    rows: &[(usize, String)],
+    grouping: RootGrouping,
+) -> Vec<Option<String>> {
+    let mut last: Option<String> = None;
+    rows.iter()
+        .map(|(depth, id)| {
+            if *depth != 0 {
+                return None;
+            }
+            let period = tree.nodes.get(id).and_then(|n| root_period(n, grouping))?;
+            (last.as_ref() != Some(&period)).then(|| {
+                last = Some(period.clone());
+                period
+            })
+        })
+        .collect()
+}
+
 fn render_tree(frame: &mut Frame, app: &mut App, area: Rect) {
+    let row_width = tree_row_width(area.width);
+    let strings = app.strings;
+    let depths: Vec<usize> = app.display_list.iter().map(|(depth, _)| *depth).collect();
+    let headers = group_headers(&app.tree, &app.display_list, app.config.root_grouping);
+    let header_style = Style::default()
+        .fg(Color::DarkGray)
+        .add_modifier(Modifier::BOLD);
     let items: Vec<ListItem> = app
         .display_list
         .iter()
         .enumerate()
-        .map(|(i, (depth, id))| {
+        .flat_map(|(index, (depth, id))| {
             let node = app.tree.nodes.get(id).unwrap();
-            let indent = "  ".repeat(*depth);
-            let prefix = if *depth == 0 { "📋 " } else { "├── " };
+            let guides = indent_guides(&depths, index);
+            let is_leaf = app.tree.is_leaf(id);
+            let prefix = if *depth == 0 {
+                format!("{} ", root_glyph(&app.config.theme, is_leaf))
+            } else {
+                "├── ".to_string()
+            };
 
             let status_icon = match node.status {
                 NodeStatus::Active => "●",
                 NodeStatus::Failed => "✗",
+                NodeStatus::Completed => "✓",
+                NodeStatus::Paused => "‖",
             };
 
-            let status_color = match node.status {
-                NodeStatus::Active => Color::Green,
-                NodeStatus::Failed => Color::Red,
+            // 有子节点的节点显示展开 / 折叠标记
+            let expander = if is_leaf {
+                ""
+            } else if app.is_collapsed(id) {
+                "▸ "
+            } else {
+                "▾ "
             };
 
-            let content = format!(
-                "{}{}{} ({} 天) [{}]",
-                indent,
-                prefix,
-                node.title,
-                node.days_active(),
-                status_icon
-            );
+            // 今天新建的节点加上标记，便于批量导入后辨认
+            let badge = if node.created_today() { "🆕 " } else { "" };
+
+            let depth_tag = if app.show_depth {
+                depth_label(*depth)
+            } else {
+                String::new()
+            };
+            let lead = format!("{}{}{}{}", prefix, expander, badge, depth_tag);
+            let lead_width = 2 * depth + lead.width();
+            let id_tag = if app.show_ids {
+                format!(" {}", short_id(id))
+            } else {
+                String::new()
+            };
+            let tail = if app.display_filter.roots_only {
+                format!(
+                    "{} {}",
+                    id_tag,
+                    toc_label(&app.tree.count_subtree_by_status(id), &strings)
+                )
+            } else {
+                format!(
+                    "{}{} [{}]",
+                    id_tag,
+                    strings.format(Key::DaysSuffix, &[&node.days_active()]),
+                    status_icon
+                )
+            };
+            let title_style = title_style(node, app.title_colors.get(id).copied());
+            // 选中行可横向滚动，查看被截断的部分
+            let title = if index == app.selected_index {
+                skip_width(&node.title, app.row_h_scroll as usize)
+            } else {
+                node.title.as_str()
+            };
 
-            let style = if i == app.selected_index {
-                Style::default()
-                    .fg(Color::Yellow)
-                    .add_modifier(Modifier::BOLD | Modifier::REVERSED)
+            let title_width = row_width.saturating_sub(lead_width + tail.width());
+            let row = |lead: String, title: String, tail: Option<String>| {
+                let mut spans = guides.clone();
+                spans.push(Span::raw(lead));
+                spans.push(Span::styled(title, title_style));
+                spans.extend(tail.map(Span::raw));
+                Line::from(spans)
+            };
+            let lines: Vec<Line> = if app.config.wrap_titles && title_width > 0 {
+                // 折行时续行与标题首行对齐，尾部信息放在第一行
+                let continuation = " ".repeat(lead.width());
+                wrap_to_width(title, title_width)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, part)| match i {
+                        0 => row(lead.clone(), part, Some(tail.clone())),
+                        _ => row(continuation.clone(), part, None),
+                    })
+                    .collect()
             } else {
-                Style::default().fg(status_color)
+                vec![row(lead, truncate_to_width(title, title_width), Some(tail))]
             };
 
-            ListItem::new(Line::from(vec![Span::styled(content, style)]))
+            // 行样式设置在 ListItem 上，选中行由 highlight_style 统一覆盖
+            let header = headers[index]
+                .as_ref()
+                .map(|label| ListItem::new(format!("── {} ──", label)).style(header_style));
+            header.into_iter().chain(std::iter::once(
+                ListItem::new(lines).style(row_style(node, &app.mode)),
+            ))
         })
         .collect();
 
+    let title = strings.get(if app.display_filter.roots_only {
+        Key::TreeTitleToc
+    } else if app.display_filter.show_archived {
+        Key::TreeTitleWithArchived
+    } else {
+        Key::TreeTitle
+    });
+    let heights: Vec<usize> = items.iter().map(ListItem::height).collect();
     let tree_widget = List::new(items)
-        .block(Block::default().title("节点列表").borders(Borders::ALL))
-        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(highlight_style(&app.mode))
+        .highlight_symbol(HIGHLIGHT_SYMBOL);
 
-    let mut state = ListState::default();
-    state.select(Some(app.selected_index));
+    // 复用持久化的 ListState，保留滚动偏移；周期标题行不可选中，选中位置需跳过它们
+    let selected = (!app.display_list.is_empty()).then(|| {
+        let skipped = headers
+            .iter()
+            .take(app.selected_index + 1)
+            .filter(|h| h.is_some())
+            .count();
+        app.selected_index + skipped
+    });
+    app.tree_state.select(selected);
+    // 居中按渲染后的项下标与每项行数计算，分组标题与折行都计入
+    if std::mem::take(&mut app.center_pending)
+        && let Some(selected) = selected
+    {
+        let offset = center_offset(selected, &heights, area.height.saturating_sub(2) as usize);
+        *app.tree_state.offset_mut() = offset;
+    }
+
+    frame.render_stateful_widget(tree_widget, area, &mut app.tree_state);
 
-    frame.render_stateful_widget(tree_widget, area, &mut state);
+    if app.display_list.is_empty() {
+        render_tree_hint(frame, app, area);
+        return;
+    }
+
+    // 渲染后 offset 已调整为能看到选中行，按行数判断上下是否还有内容
+    let offset = app.tree_state.offset().min(heights.len());
+    let (up, down) = scroll_indicators(
+        heights[..offset].iter().sum(),
+        heights.iter().sum(),
+        area.height.saturating_sub(2) as usize,
+    );
+    render_scroll_indicators(frame, area, up, down);
 }
 
-fn render_details(frame: &mut Frame, app: &App, area: Rect) {
-    let content = if let Some(node) = app.selected_node() {
-        format!(
-            "标题: {}\n创建于: {}  连续: {} 天  状态: {:?}\n规则: {}",
-            node.title,
-            node.created_at.format("%Y-%m-%d %H:%M"),
-            node.days_active(),
-            node.status,
-            if node.content.is_empty() {
-                "(无)"
+/// 目录模式中根节点的汇总：子树节点数及各状态数量（为 0 的状态省略）
+fn toc_label(counts: &StatusCounts, strings: &Strings) -> String {
+    let parts: Vec<String> = [
+        ("●", counts.active),
+        ("✓", counts.completed),
+        ("✗", counts.failed),
+        ("‖", counts.paused),
+    ]
+    .iter()
+    .filter(|(_, count)| *count > 0)
+    .map(|(icon, count)| format!("{}{}", icon, count))
+    .collect();
+    format!(
+        "[{} {}]",
+        strings.format(Key::SubtreeNodes, &[&counts.total()]),
+        parts.join(" ")
+    )
+}
+
+/// 节点 ID 的前 8 个字符（UUID 的第一段），用于显示
+fn short_id(id: &str) -> &str {
+    id.char_indices().nth(8).map_or(id, |(end, _)| &id[..end])
+}
+
+/// 行首的层级标签，根节点为第 1 层（与 `max_depth` 的计法一致）
+fn depth_label(depth: usize) -> String {
+    format!("L{} ", depth + 1)
+}
+
+/// 树中整行的样式：按状态着色，已归档置灰；移动模式下被移动的节点暗淡斜体
+fn row_style(node: &FocusNode, mode: &AppMode) -> Style {
+    let color = if node.archived {
+        Color::DarkGray
+    } else {
+        status_color(&node.status)
+    };
+    let style = Style::default().fg(color);
+    match mode {
+        AppMode::MovingNode(source) | AppMode::LinkingNode(source) if *source == node.id => {
+            style.add_modifier(Modifier::DIM | Modifier::ITALIC)
+        }
+        _ => style,
+    }
+}
+
+/// 选中行的样式；移动模式下选中的是目标父节点，用品红色区分
+fn highlight_style(mode: &AppMode) -> Style {
+    let accent = match mode {
+        AppMode::MovingNode(_) => Color::Magenta,
+        AppMode::LinkingNode(_) => Color::Cyan,
+        _ => Color::Yellow,
+    };
+    // 去掉失败 / 被移动节点的暗淡效果，保证反色后仍可读
+    Style::default()
+        .fg(accent)
+        .add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        .remove_modifier(Modifier::DIM)
+}
+
+/// 树中标题的样式：颜色标签（`color` 为已解析的值）只作用于标题（已归档节点仍整行置灰），
+/// 失败节点加删除线并暗淡显示
+fn title_style(node: &FocusNode, color: Option<Color>) -> Style {
+    let style = match color {
+        Some(color) if !node.archived => Style::default().fg(color),
+        _ => Style::default(),
+    };
+    if node.status == NodeStatus::Failed {
+        style.add_modifier(Modifier::CROSSED_OUT | Modifier::DIM)
+    } else {
+        style
+    }
+}
+
+/// 第 `index` 行的缩进参考线：每层占两列，祖先在其后还有兄弟节点时画 `│`
+///
+/// 根节点不画连接线，因此第 0 层总是空白
+fn indent_guides(depths: &[usize], index: usize) -> Vec<Span<'static>> {
+    let depth = depths[index];
+    (0..depth)
+        .map(|level| {
+            let continues = level > 0
+                && depths[index + 1..]
+                    .iter()
+                    .find(|d| **d <= level)
+                    .is_some_and(|d| *d == level);
+            if continues {
+                Span::styled("│ ", Style::default().fg(Color::DarkGray))
             } else {
-                &node.content
+                Span::raw("  ")
             }
-        )
+        })
+        .collect()
+}
+
+/// 节点列表为空时在树区域中央显示引导提示
+fn render_tree_hint(frame: &mut Frame, app: &App, area: Rect) {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    if inner.height == 0 {
+        return;
+    }
+    let key = if app.tree.nodes.is_empty() {
+        Key::TreeEmptyHint
     } else {
-        "暂无节点，按 'a' 添加第一个国策".to_string()
+        Key::TreeHiddenHint
+    };
+    let hint = Paragraph::new(app.strings.get(key))
+        .style(Style::default().fg(Color::DarkGray))
+        .alignment(Alignment::Center);
+    let row = Rect::new(inner.x, inner.y + inner.height / 2, inner.width, 1);
+    frame.render_widget(hint, row);
+}
+
+/// 是否显示向上 / 向下的滚动指示（均以行为单位）
+fn scroll_indicators(offset: usize, total: usize, height: usize) -> (bool, bool) {
+    (offset > 0, offset + height < total)
+}
+
+/// 在区域右侧的上下边框上绘制 `▲` / `▼`
+fn render_scroll_indicators(frame: &mut Frame, area: Rect, up: bool, down: bool) {
+    if area.width < 4 || area.height < 2 {
+        return;
+    }
+    let x = area.right() - 3;
+    let style = Style::default().fg(Color::Yellow);
+    if up {
+        frame.render_widget(Paragraph::new("▲").style(style), Rect::new(x, area.y, 1, 1));
+    }
+    if down {
+        frame.render_widget(
+            Paragraph::new("▼").style(style),
+            Rect::new(x, area.bottom() - 1, 1, 1),
+        );
+    }
+}
+
+fn render_details(frame: &mut Frame, app: &App, area: Rect) {
+    let lines = match app.selected_node() {
+        Some(node) => {
+            let mut lines = detail_lines(
+                &app.tree,
+                node,
+                app.details_tab,
+                &app.config.date_format,
+                &app.strings,
+            );
+            if app.show_ids {
+                lines.insert(
+                    0,
+                    Line::from(vec![
+                        Span::styled(
+                            app.strings.get(Key::LabelId),
+                            Style::default().fg(Color::Gray),
+                        ),
+                        Span::raw(short_id(&node.id)),
+                    ]),
+                );
+            }
+            lines
+        }
+        None => vec![Line::from(app.strings.get(Key::EmptyTree))],
     };
 
-    let details = Paragraph::new(content)
-        .block(Block::default().title("详情").borders(Borders::ALL))
+    // 详情不滚动，内容超出时只提示下方还有内容
+    let inner_width = area.width.saturating_sub(2).max(1) as usize;
+    let rows: usize = lines
+        .iter()
+        .map(|line| line.width().div_ceil(inner_width).max(1))
+        .sum();
+
+    let details = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(details_title(app.details_tab, &app.strings))
+                .borders(Borders::ALL),
+        )
         .wrap(Wrap { trim: true });
 
     frame.render_widget(details, area);
+    let (up, down) = scroll_indicators(0, rows, area.height.saturating_sub(2) as usize);
+    render_scroll_indicators(frame, area, up, down);
 }
 
-fn render_help(frame: &mut Frame, app: &App, area: Rect) {
-    let help_text = match &app.mode {
-        AppMode::Normal => {
-            "[a] 添加  [e] 编辑  [r] 重命名  [m] 移动  [d] 删除  [f] 失败/激活  [j/k] 导航  [q] 退出"
+/// 详情面板标题，附带标签页指示（当前标签高亮）
+fn details_title(current: DetailsTab, strings: &Strings) -> Line<'static> {
+    let mut spans = vec![Span::raw(strings.get(Key::DetailsTitle))];
+    for tab in DetailsTab::ALL {
+        let style = if tab == current {
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        spans.push(Span::styled(format!("[{}]", tab.title(strings)), style));
+    }
+    Line::from(spans)
+}
+
+/// 节点状态对应的颜色
+fn status_color(status: &NodeStatus) -> Color {
+    match status {
+        NodeStatus::Active => Color::Green,
+        NodeStatus::Failed => Color::Red,
+        NodeStatus::Completed => Color::Cyan,
+        NodeStatus::Paused => Color::Gray,
+    }
+}
+
+/// 构建详情面板当前标签页的各行（字段名与字段值分开着色）
+fn detail_lines<'a>(
+    tree: &FocusTree,
+    node: &'a FocusNode,
+    tab: DetailsTab,
+    date_format: &str,
+    strings: &Strings,
+) -> Vec<Line<'a>> {
+    let label = Style::default().fg(Color::Gray);
+
+    match tab {
+        DetailsTab::Content => {
+            let content = if node.content.is_empty() {
+                strings.get(Key::NoValue)
+            } else {
+                node.content.as_str()
+            };
+            let mut lines = vec![
+                Line::from(vec![
+                    Span::styled(strings.get(Key::LabelTitle), label),
+                    Span::raw(node.title.as_str()),
+                ]),
+                Line::from(vec![
+                    Span::styled(strings.get(Key::LabelRule), label),
+                    Span::raw(content),
+                ]),
+            ];
+            let linked = tree.linked_nodes(&node.id);
+            if !linked.is_empty() {
+                let titles: Vec<&str> = linked.iter().map(|n| n.title.as_str()).collect();
+                lines.push(Line::from(vec![
+                    Span::styled(strings.get(Key::LabelLinks), label),
+                    Span::raw(titles.join(" · ")),
+                ]));
+            }
+            lines
+        }
+        DetailsTab::Log => {
+            if node.log.is_empty() {
+                return vec![Line::styled(strings.get(Key::NoLog), label)];
+            }
+            // 最新的日志在最上方
+            node.log
+                .iter()
+                .rev()
+                .map(|entry| {
+                    Line::from(vec![
+                        Span::styled(format!("{}  ", entry.at.format(date_format)), label),
+                        Span::raw(strings.log_entry(entry)),
+                    ])
+                })
+                .collect()
         }
+        DetailsTab::Meta => {
+            let mut lines = vec![
+                Line::from(vec![
+                    Span::styled(strings.get(Key::LabelCreated), label),
+                    Span::raw(node.created_at.format(date_format).to_string()),
+                    Span::styled(strings.get(Key::LabelUpdated), label),
+                    Span::raw(
+                        node.updated_at
+                            .map(|t| t.format(date_format).to_string())
+                            .unwrap_or_else(|| strings.get(Key::NoValue).to_string()),
+                    ),
+                ]),
+                Line::from(vec![
+                    Span::styled(strings.get(Key::LabelStreak), label),
+                    Span::raw(strings.format(Key::Days, &[&node.days_active()])),
+                    Span::styled(strings.get(Key::LabelStatus), label),
+                    Span::styled(
+                        strings.status(&node.status),
+                        Style::default().fg(status_color(&node.status)),
+                    ),
+                    Span::styled(strings.get(Key::LabelSubtree), label),
+                    Span::raw(strings.format(Key::SubtreeNodes, &[&tree.subtree_size(&node.id)])),
+                ]),
+            ];
+            if let Some((index, count)) = tree.sibling_position(&node.id) {
+                let key = if node.is_root() {
+                    Key::RootPosition
+                } else {
+                    Key::SiblingPosition
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(strings.get(Key::LabelPosition), label),
+                    Span::raw(strings.format(key, &[&index, &count])),
+                ]));
+            }
+            if let Some(completed_at) = node.completed_at {
+                lines.push(Line::from(vec![
+                    Span::styled(strings.get(Key::LabelCompleted), label),
+                    Span::raw(completed_at.format(date_format).to_string()),
+                ]));
+            }
+            if let Some(due) = node.due {
+                let mut spans = vec![
+                    Span::styled(strings.get(Key::LabelDue), label),
+                    Span::raw(due.format(DUE_FORMAT).to_string()),
+                ];
+                if node.is_overdue(Local::now().date_naive()) {
+                    spans.push(Span::styled(
+                        strings.get(Key::DueOverdue),
+                        Style::default().fg(Color::Red),
+                    ));
+                }
+                lines.push(Line::from(spans));
+            }
+            let rolled_up = tree.rolled_up_estimate(&node.id);
+            if rolled_up > 0.0 {
+                lines.push(Line::from(vec![
+                    Span::styled(strings.get(Key::LabelEstimate), label),
+                    Span::raw(node.estimate.to_string()),
+                    Span::styled(strings.get(Key::LabelEstimateTotal), label),
+                    Span::raw(rolled_up.to_string()),
+                ]));
+            }
+            lines
+        }
+    }
+}
+
+fn render_help(frame: &mut Frame, app: &App, area: Rect) {
+    let key = match &app.mode {
+        AppMode::Normal => Key::HelpNormal,
         AppMode::AddingNode => match app.input_field {
-            InputField::Title => "输入标题后按 [Enter] 继续  [Esc] 取消",
-            InputField::Content => "输入内容后按 [Enter] 完成  [Esc] 取消",
+            InputField::Title => Key::HelpAddTitle,
+            InputField::Content => Key::HelpAddContent,
         },
-        AppMode::EditingContent(_) => "[Enter] 保存  [Esc] 取消",
-        AppMode::EditingTitle(_) => "[Enter] 保存  [Esc] 取消",
-        AppMode::MovingNode(_) => "[j/k] 选择目标位置  [m] 确认移动  [Esc] 取消",
-        AppMode::Confirm(_) => "[y] 确认  [n] 取消",
+        AppMode::EditingNode(_) => Key::HelpEdit,
+        AppMode::MovingNode(_) => Key::HelpMove,
+        AppMode::LinkingNode(_) => Key::HelpLink,
+        AppMode::CommandPalette => Key::HelpPalette,
+        AppMode::ReplaceFrom => Key::HelpReplaceFrom,
+        AppMode::ReplaceTo(_) => Key::HelpReplaceTo,
+        AppMode::GotoInput => Key::HelpGoto,
+        AppMode::FailReason(_) => Key::HelpFailReason,
+        AppMode::EstimateInput(_) => Key::HelpEstimate,
+        AppMode::DueInput(_) => Key::HelpDue,
+        AppMode::StaleRoots => Key::HelpStaleRoots,
+        AppMode::RecentList => Key::HelpRecent,
+        AppMode::KeyHelp => Key::HelpKeys,
+        AppMode::Confirm(_) => Key::HelpConfirm,
     };
 
-    let message = app.message.as_deref().unwrap_or("");
-    let text = if message.is_empty() {
-        help_text.to_string()
+    // 放不下时省略后面的按键；普通模式下提示用 ? 查看全部
+    let more = if app.mode == AppMode::Normal {
+        app.strings.get(Key::HelpMore)
     } else {
-        format!("{}  |  {}", help_text, message)
+        "…"
     };
+    let items = help_items(app.strings.get(key));
+    let text = fit_help_items(&items, area.width as usize, more);
 
+    // 操作被拒绝后短暂反色闪烁
+    let style = if app.is_flashing(Instant::now()) {
+        Style::default()
+            .fg(Color::Red)
+            .add_modifier(Modifier::REVERSED)
+    } else {
+        Style::default().fg(Color::Gray)
+    };
     let help = Paragraph::new(text)
-        .style(Style::default().fg(Color::Gray))
-        .block(Block::default().borders(Borders::ALL));
+        .style(style)
+        .block(Block::default().borders(Borders::TOP));
 
     frame.render_widget(help, area);
 }
 
+/// 把快捷键提示拆成条目（如 `[a] 添加`），条目之间以两个空格分隔
+fn help_items(text: &str) -> Vec<&str> {
+    text.split("  ")
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// 将帮助条目拆成按键（含方括号）与说明，如 `[]o / [o] 逾期节点` 拆为 `[]o / [o]` 与 `逾期节点`
+fn help_item_parts(item: &str) -> Option<(&str, &str)> {
+    item.find("] ").map(|end| (&item[..=end], &item[end + 2..]))
+}
+
+/// 在 `width` 列内放入尽可能多的条目；放不下全部时以 `more` 结尾
+fn fit_help_items(items: &[&str], width: usize, more: &str) -> String {
+    let all = items.join("  ");
+    if all.width() <= width {
+        return all;
+    }
+    let mut fitted: Vec<&str> = Vec::new();
+    for item in items {
+        let candidate = fitted
+            .iter()
+            .chain([item, &more])
+            .copied()
+            .collect::<Vec<_>>();
+        if candidate.join("  ").width() > width {
+            break;
+        }
+        fitted.push(item);
+    }
+    fitted.push(more);
+    fitted.join("  ")
+}
+
+/// 状态栏：左侧为消息，右侧为选中位置（如 `3/12`），无法保存时在位置前持续提示
+fn render_status(frame: &mut Frame, app: &App, area: Rect) {
+    let position = selection_position(app);
+    let warning = if app.save_failed {
+        format!("{} ", app.strings.get(Key::StatusNotSaving))
+    } else {
+        String::new()
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length((warning.width() + position.width()) as u16 + 1),
+        ])
+        .split(area);
+
+    let message = app.message.as_deref().unwrap_or("");
+    frame.render_widget(
+        Paragraph::new(message).style(Style::default().fg(Color::Yellow)),
+        chunks[0],
+    );
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(warning, Style::default().fg(Color::Red)),
+            Span::styled(position, Style::default().fg(Color::Gray)),
+        ]))
+        .alignment(Alignment::Right),
+        chunks[1],
+    );
+}
+
+/// 当前选中位置与节点总数，空树时为 `0/0`
+fn selection_position(app: &App) -> String {
+    let total = app.display_list.len();
+    let current = if total == 0 {
+        0
+    } else {
+        app.selected_index + 1
+    };
+    format!("{}/{}", current, total)
+}
+
 fn render_add_dialog(frame: &mut Frame, app: &App) {
     let area = centered_rect(60, 50, frame.area());
-    let inner = render_dialog_framework(frame, area, "添加新国策");
+    let title = if app.add_parent.is_none() {
+        Key::AddRootDialogTitle
+    } else {
+        Key::AddDialogTitle
+    };
+    let inner = render_dialog_framework(frame, area, app.strings.get(title));
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -185,10 +835,15 @@ fn render_add_dialog(frame: &mut Frame, app: &App) {
     render_input_widget(
         frame,
         chunks[0],
-        "标题",
+        app.strings.get(Key::FieldTitle),
         title_val,
         is_title_active,
         Color::Yellow,
+        if is_title_active {
+            input_scroll(title_val, chunks[0])
+        } else {
+            0
+        },
     );
 
     // 内容输入
@@ -201,78 +856,1040 @@ fn render_add_dialog(frame: &mut Frame, app: &App) {
     render_input_widget(
         frame,
         chunks[1],
-        "内容 (可选)",
+        app.strings.get(Key::FieldContentOptional),
         content_val,
         is_content_active,
         Color::Yellow,
+        if is_content_active {
+            input_scroll(content_val, chunks[1])
+        } else {
+            0
+        },
     );
 
-    let hint = match app.input_field {
-        InputField::Title => "输入标题后按 Enter 继续",
-        InputField::Content => "输入内容后按 Enter 完成（可留空）",
-    };
+    let hint = app.strings.get(match app.input_field {
+        InputField::Title => Key::AddHintTitle,
+        InputField::Content => Key::AddHintContent,
+    });
     frame.render_widget(
         Paragraph::new(hint).style(Style::default().fg(Color::Gray)),
         chunks[2],
     );
 }
 
-fn render_edit_content_dialog(frame: &mut Frame, app: &App) {
-    let area = centered_rect(70, 30, frame.area());
-    let inner = render_dialog_framework(frame, area, "编辑内容");
+fn render_edit_dialog(frame: &mut Frame, app: &App) {
+    let area = centered_rect(70, 50, frame.area());
+    let inner = render_dialog_framework(frame, area, app.strings.get(Key::EditDialogTitle));
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let is_title_active = app.input_field == InputField::Title;
+    let (title_val, content_val) = if is_title_active {
+        (&app.input_buffer, &app.temp_content)
+    } else {
+        (&app.temp_title, &app.input_buffer)
+    };
+    render_input_widget(
+        frame,
+        chunks[0],
+        app.strings.get(Key::FieldTitle),
+        title_val,
+        is_title_active,
+        Color::Yellow,
+        if is_title_active {
+            input_scroll(title_val, chunks[0])
+        } else {
+            0
+        },
+    );
+    render_input_widget(
+        frame,
+        chunks[1],
+        app.strings.get(Key::FieldContent),
+        content_val,
+        !is_title_active,
+        Color::Yellow,
+        if !is_title_active {
+            input_scroll(content_val, chunks[1])
+        } else {
+            0
+        },
+    );
+
+    let hint =
+        Paragraph::new(app.strings.get(Key::EditHint)).style(Style::default().fg(Color::Gray));
+    frame.render_widget(hint, chunks[2]);
+}
+
+fn render_command_palette(frame: &mut Frame, app: &App) {
+    let area = centered_rect(60, 60, frame.area());
+    let inner = render_dialog_framework(frame, area, app.strings.get(Key::PaletteTitle));
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
         .split(inner);
 
     render_input_widget(
         frame,
         chunks[0],
-        "内容",
+        app.strings.get(Key::PaletteFilter),
         &app.input_buffer,
         true,
         Color::Yellow,
+        input_scroll(&app.input_buffer, chunks[0]),
+    );
+
+    let items: Vec<ListItem> = filter_actions(&app.input_buffer, &app.strings)
+        .iter()
+        .map(|action| {
+            ListItem::new(Line::from(vec![
+                Span::raw(action.name(&app.strings)),
+                Span::styled(
+                    format!("  [{}]", key_hint(action)),
+                    Style::default().fg(Color::Gray),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::REVERSED),
+    );
+    let mut state = ListState::default();
+    state.select(Some(app.palette_index));
+
+    frame.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+fn render_stale_roots(frame: &mut Frame, app: &App) {
+    let area = centered_rect(60, 60, frame.area());
+    let inner = render_dialog_framework(frame, area, app.strings.get(Key::StaleRootsTitle));
+
+    let roots = app.stale_roots();
+    let items: Vec<ListItem> = roots
+        .iter()
+        .filter_map(|id| app.tree.nodes.get(id))
+        .map(|node| {
+            ListItem::new(Line::from(vec![
+                Span::raw(node.title.clone()),
+                Span::styled(
+                    format!(
+                        "  {}",
+                        app.strings
+                            .format(Key::StaleRootsDays, &[&node.days_active()])
+                    ),
+                    Style::default().fg(Color::Gray),
+                ),
+                Span::styled(
+                    format!("  {}", app.strings.status(&node.status)),
+                    Style::default().fg(status_color(&node.status)),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::REVERSED),
+    );
+    let mut state = ListState::default();
+    state.select(
+        app.selected_node_id()
+            .and_then(|id| roots.iter().position(|root| *root == id)),
     );
 
-    let hint = Paragraph::new("按 Enter 保存，Esc 取消").style(Style::default().fg(Color::Gray));
-    frame.render_widget(hint, chunks[1]);
+    frame.render_stateful_widget(list, inner, &mut state);
 }
 
-fn render_edit_title_dialog(frame: &mut Frame, app: &App) {
-    let area = centered_rect(70, 30, frame.area());
-    let inner = render_dialog_framework(frame, area, "编辑标题");
+fn render_key_help(frame: &mut Frame, app: &App) {
+    let area = centered_rect(60, 80, frame.area());
+    let inner = render_dialog_framework(frame, area, app.strings.get(Key::KeysTitle));
+
+    let lines: Vec<Line> = help_items(app.strings.get(Key::HelpNormal))
+        .into_iter()
+        .map(|item| match help_item_parts(item) {
+            Some((keys, label)) => Line::from(vec![
+                Span::styled(format!("{:<12}", keys), Style::default().fg(Color::Yellow)),
+                Span::raw(label.to_string()),
+            ]),
+            None => Line::from(item.to_string()),
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn render_recent_list(frame: &mut Frame, app: &App) {
+    let area = centered_rect(60, 60, frame.area());
+    let inner = render_dialog_framework(frame, area, app.strings.get(Key::RecentTitle));
+
+    let items: Vec<ListItem> = app
+        .recent_ids()
+        .iter()
+        .filter_map(|id| app.tree.nodes.get(id))
+        .map(|node| {
+            let modified = node.updated_at.unwrap_or(node.created_at);
+            ListItem::new(Line::from(vec![
+                Span::raw(node.title.clone()),
+                Span::styled(
+                    format!("  {}", modified.format(&app.config.date_format)),
+                    Style::default().fg(Color::Gray),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::REVERSED),
+    );
+    let mut state = ListState::default();
+    state.select(Some(app.palette_index));
+
+    frame.render_stateful_widget(list, inner, &mut state);
+}
+
+fn render_replace_dialog(frame: &mut Frame, app: &App) {
+    let area = centered_rect(60, 40, frame.area());
+    let inner = render_dialog_framework(frame, area, app.strings.get(Key::ReplaceDialogTitle));
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .constraints([Constraint::Length(3), Constraint::Length(3)])
         .split(inner);
 
+    let (from, to, finding) = match &app.mode {
+        AppMode::ReplaceTo(from) => (from.as_str(), app.input_buffer.as_str(), false),
+        _ => (app.input_buffer.as_str(), "", true),
+    };
     render_input_widget(
         frame,
         chunks[0],
-        "标题",
+        app.strings.get(Key::FieldFind),
+        from,
+        finding,
+        Color::Yellow,
+        if finding {
+            input_scroll(from, chunks[0])
+        } else {
+            0
+        },
+    );
+    render_input_widget(
+        frame,
+        chunks[1],
+        app.strings.get(Key::FieldReplaceWith),
+        to,
+        !finding,
+        Color::Yellow,
+        if !finding {
+            input_scroll(to, chunks[1])
+        } else {
+            0
+        },
+    );
+}
+
+fn render_goto_dialog(frame: &mut Frame, app: &App) {
+    let area = centered_rect(60, 20, frame.area());
+    let inner = render_dialog_framework(frame, area, app.strings.get(Key::GotoDialogTitle));
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3)])
+        .split(inner);
+
+    render_input_widget(
+        frame,
+        chunks[0],
+        app.strings.get(Key::FieldNodeId),
         &app.input_buffer,
         true,
         Color::Yellow,
+        input_scroll(&app.input_buffer, chunks[0]),
     );
+}
 
-    let hint = Paragraph::new("按 Enter 保存，Esc 取消").style(Style::default().fg(Color::Gray));
-    frame.render_widget(hint, chunks[1]);
+fn render_estimate_dialog(frame: &mut Frame, app: &App) {
+    let area = centered_rect(60, 20, frame.area());
+    let inner = render_dialog_framework(frame, area, app.strings.get(Key::EstimateTitle));
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3)])
+        .split(inner);
+
+    render_input_widget(
+        frame,
+        chunks[0],
+        app.strings.get(Key::FieldEstimate),
+        &app.input_buffer,
+        true,
+        Color::Yellow,
+        input_scroll(&app.input_buffer, chunks[0]),
+    );
 }
 
-fn render_confirm_dialog(frame: &mut Frame, action: &ConfirmAction) {
+fn render_due_dialog(frame: &mut Frame, app: &App) {
+    let area = centered_rect(60, 20, frame.area());
+    let inner = render_dialog_framework(frame, area, app.strings.get(Key::DueTitle));
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3)])
+        .split(inner);
+
+    render_input_widget(
+        frame,
+        chunks[0],
+        app.strings.get(Key::FieldDue),
+        &app.input_buffer,
+        true,
+        Color::Yellow,
+        input_scroll(&app.input_buffer, chunks[0]),
+    );
+}
+
+fn render_fail_reason_dialog(frame: &mut Frame, app: &App) {
+    let area = centered_rect(60, 20, frame.area());
+    let inner = render_dialog_framework(frame, area, app.strings.get(Key::FailReasonTitle));
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3)])
+        .split(inner);
+
+    render_input_widget(
+        frame,
+        chunks[0],
+        app.strings.get(Key::FieldReason),
+        &app.input_buffer,
+        true,
+        Color::Red,
+        input_scroll(&app.input_buffer, chunks[0]),
+    );
+}
+
+/// 替换前的预览：列出受影响的节点
+fn render_replace_preview(frame: &mut Frame, app: &App, from: &str, to: &str) {
+    let area = centered_rect(60, 50, frame.area());
+    frame.render_widget(Clear, area);
+
+    let strings = &app.strings;
+    let matches = app.tree.find_text(from);
+    let mut lines = vec![Line::from(
+        strings.format(Key::ConfirmReplace, &[&from, &to, &matches.len()]),
+    )];
+    lines.extend(matches.iter().filter_map(|id| {
+        app.tree
+            .nodes
+            .get(id)
+            .map(|node| Line::from(format!("  • {}", node.title)))
+    }));
+    lines.push(Line::from(""));
+    lines.push(Line::from(strings.get(Key::HelpConfirm)));
+
+    let dialog = Paragraph::new(lines)
+        .style(Style::default().fg(Color::Yellow))
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title(strings.get(Key::ConfirmTitle))
+                .borders(Borders::ALL),
+        );
+
+    frame.render_widget(dialog, area);
+}
+
+/// 完整性检查的结果：列出每个问题及相关节点的标题
+fn render_integrity_report(frame: &mut Frame, app: &App, issues: &[TreeIssue]) {
+    let area = centered_rect(70, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let strings = &app.strings;
+    let mut lines = vec![Line::from(
+        strings.format(Key::ConfirmRepair, &[&issues.len()]),
+    )];
+    lines.extend(issues.iter().map(|issue| {
+        let text = strings.tree_issue(issue);
+        match issue.node_id().and_then(|id| app.tree.nodes.get(id)) {
+            Some(node) => Line::from(format!(
+                "  • {}",
+                strings.format(Key::IssueWithTitle, &[&text, &node.title])
+            )),
+            None => Line::from(format!("  • {}", text)),
+        }
+    }));
+    lines.push(Line::from(""));
+    lines.push(Line::from(strings.get(Key::HelpConfirm)));
+
+    let dialog = Paragraph::new(lines)
+        .style(Style::default().fg(Color::Yellow))
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title(strings.get(Key::ConfirmTitle))
+                .borders(Borders::ALL),
+        );
+
+    frame.render_widget(dialog, area);
+}
+
+fn render_confirm_dialog(frame: &mut Frame, app: &App, action: &ConfirmAction) {
+    if let ConfirmAction::RepairTree(issues) = action {
+        render_integrity_report(frame, app, issues);
+        return;
+    }
+    if let ConfirmAction::Replace { from, to } = action {
+        render_replace_preview(frame, app, from, to);
+        return;
+    }
+
     let area = centered_rect(50, 20, frame.area());
     frame.render_widget(Clear, area);
 
-    let message = match action {
-        ConfirmAction::Delete(_) => "确认删除该节点及其所有子节点？",
-        ConfirmAction::Fail(_) => "确认标记该节点为失败并删除所有子节点？",
-    };
+    let strings = &app.strings;
+    if let ConfirmAction::Move { node, target } = action {
+        let target = match target.as_ref().and_then(|id| app.tree.nodes.get(id)) {
+            Some(target) => target.title.as_str(),
+            None => strings.get(Key::RootTarget),
+        };
+        let message = strings.format(Key::ConfirmMove, &[&app.tree.subtree_size(node), &target]);
+        render_confirm_message(frame, strings, area, &message);
+        return;
+    }
+    let message = strings.get(match action {
+        ConfirmAction::Delete(id) if app.tree.is_leaf(id) => Key::ConfirmDeleteLeaf,
+        ConfirmAction::Delete(_) => match app.config.delete_mode {
+            DeleteMode::Cascade => Key::ConfirmDeleteTree,
+            DeleteMode::Reparent => Key::ConfirmDeleteReparent,
+        },
+        ConfirmAction::Fail(id) if app.tree.is_leaf(id) => Key::ConfirmFailLeaf,
+        ConfirmAction::Fail(_) => Key::ConfirmFailTree,
+        ConfirmAction::PurgeFailed => Key::ConfirmPurgeFailed,
+        ConfirmAction::MergeRoots => Key::ConfirmMergeRoots,
+        ConfirmAction::ReloadExternal => Key::ConfirmReloadExternal,
+        ConfirmAction::QuitDiscard => Key::ConfirmQuitDiscard,
+        ConfirmAction::Move { .. }
+        | ConfirmAction::Replace { .. }
+        | ConfirmAction::RepairTree(_) => unreachable!(),
+    });
+    render_confirm_message(frame, strings, area, message);
+}
 
-    let dialog = Paragraph::new(format!("{}\n\n[y] 确认  [n] 取消", message))
+fn render_confirm_message(frame: &mut Frame, strings: &Strings, area: Rect, message: &str) {
+    let dialog = Paragraph::new(format!("{}\n\n{}", message, strings.get(Key::HelpConfirm)))
         .style(Style::default().fg(Color::Red))
-        .block(Block::default().title("⚠️ 确认操作").borders(Borders::ALL));
+        .block(
+            Block::default()
+                .title(strings.get(Key::ConfirmTitle))
+                .borders(Borders::ALL),
+        );
 
     frame.render_widget(dialog, area);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, DEFAULT_DATE_FORMAT};
+    use crate::i18n::Lang;
+    use crate::models::TreeBuilder;
+    use crate::ui::actions::Action;
+    use chrono::{NaiveDate, TimeZone};
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn sample_app() -> App {
+        let tree = TreeBuilder::new().node("Root", |b| b.leaf("Child")).build();
+        App::new(tree, Config::default())
+    }
+
+    #[test]
+    fn test_toc_label() {
+        let mut tree = TreeBuilder::new()
+            .node("plan", |b| b.leaf("x").leaf("y").node("z", |b| b.leaf("w")))
+            .leaf("solo")
+            .build();
+        let plan = tree.root_ids[0].clone();
+        let x = tree.get_children(&plan)[0].id.clone();
+        let y = tree.get_children(&plan)[1].id.clone();
+        tree.toggle_complete(&x);
+        tree.fail_node(&y);
+
+        let strings = Strings::new(Lang::En);
+        assert_eq!(
+            toc_label(&tree.count_subtree_by_status(&plan), &strings),
+            "[5 node(s) ●3 ✓1 ✗1]"
+        );
+        let solo = tree.root_ids[1].clone();
+        assert_eq!(
+            toc_label(&tree.count_subtree_by_status(&solo), &strings),
+            "[1 node(s) ●1]"
+        );
+    }
+
+    #[test]
+    fn test_input_scroll_keeps_caret_visible() {
+        use components::caret_position;
+
+        // 单行输入框：内部宽 10、高 1
+        let area = Rect::new(0, 0, 12, 3);
+        assert_eq!(input_scroll("short", area), 0);
+        assert_eq!(caret_position("short", 10), (0, 5));
+
+        let long = "a".repeat(25);
+        assert_eq!(caret_position(&long, 10), (2, 5));
+        assert_eq!(input_scroll(&long, area), 2);
+
+        // 正好写满一行时光标在下一行行首
+        let full = "a".repeat(20);
+        assert_eq!(caret_position(&full, 10), (2, 0));
+        assert_eq!(input_scroll(&full, area), 2);
+
+        // 宽字符按两列计算；多行输入框只在超出高度时滚动
+        assert_eq!(caret_position("国策国策国策", 10), (1, 2));
+        let tall = Rect::new(0, 0, 12, 5);
+        assert_eq!(input_scroll(&long, tall), 0);
+        assert_eq!(input_scroll(&"a".repeat(45), tall), 2);
+    }
+
+    #[test]
+    fn test_short_id() {
+        assert_eq!(short_id("1a2b3c4d-5e6f-7a8b-9c0d-112233445566"), "1a2b3c4d");
+        assert_eq!(short_id("abc"), "abc");
+        assert_eq!(short_id(""), "");
+    }
+
+    #[test]
+    fn test_depth_label_in_rows() {
+        let mut app = sample_app();
+        let mut terminal = Terminal::new(TestBackend::new(60, 24)).unwrap();
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        assert!(!buffer_text(&terminal).contains("L2 Child"));
+
+        app.show_depth = true;
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let text = buffer_text(&terminal);
+        assert!(text.contains("L1 Root"));
+        assert!(text.contains("L2 Child"));
+        assert_eq!(depth_label(0), "L1 ");
+    }
+
+    #[test]
+    fn test_selection_position() {
+        let mut app = sample_app();
+        assert_eq!(selection_position(&app), "1/2");
+        app.move_down();
+        assert_eq!(selection_position(&app), "2/2");
+
+        let empty = App::new(FocusTree::new(), Config::default());
+        assert_eq!(selection_position(&empty), "0/0");
+    }
+
+    fn line_text(line: &Line) -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn test_detail_lines() {
+        let app = sample_app();
+        let node = app.selected_node().unwrap();
+
+        let content: Vec<String> = detail_lines(
+            &app.tree,
+            node,
+            DetailsTab::Content,
+            DEFAULT_DATE_FORMAT,
+            &app.strings,
+        )
+        .iter()
+        .map(line_text)
+        .collect();
+        assert_eq!(content, vec!["标题: Root", "规则: (无)"]);
+
+        let meta: Vec<String> = detail_lines(
+            &app.tree,
+            node,
+            DetailsTab::Meta,
+            DEFAULT_DATE_FORMAT,
+            &app.strings,
+        )
+        .iter()
+        .map(line_text)
+        .collect();
+        assert!(meta[0].contains(&node.created_at.format("%Y-%m-%d %H:%M").to_string()));
+
+        let custom: Vec<String> = detail_lines(
+            &app.tree,
+            node,
+            DetailsTab::Meta,
+            "%Y年%m月%d日",
+            &app.strings,
+        )
+        .iter()
+        .map(line_text)
+        .collect();
+        assert!(custom[0].contains(&node.created_at.format("%Y年%m月%d日").to_string()));
+        assert!(meta[0].contains("最后更新: (无)"));
+        assert!(meta[1].contains("状态: 活跃"));
+        assert!(meta[1].contains("子树: 2 个节点"));
+        assert_eq!(meta[2], "位置: 第 1 / 1 个根节点");
+        assert_eq!(meta.len(), 3);
+
+        let mut done = node.clone();
+        done.set_status(NodeStatus::Completed);
+        let meta: Vec<String> = detail_lines(
+            &app.tree,
+            &done,
+            DetailsTab::Meta,
+            DEFAULT_DATE_FORMAT,
+            &app.strings,
+        )
+        .iter()
+        .map(line_text)
+        .collect();
+        assert!(meta[3].starts_with("完成于: "));
+
+        let mut late = node.clone();
+        late.due = NaiveDate::from_ymd_opt(2000, 1, 1);
+        let meta: Vec<String> = detail_lines(
+            &app.tree,
+            &late,
+            DetailsTab::Meta,
+            DEFAULT_DATE_FORMAT,
+            &app.strings,
+        )
+        .iter()
+        .map(line_text)
+        .collect();
+        assert_eq!(meta[3], "截止: 2000-01-01（已逾期）");
+
+        let log: Vec<String> = detail_lines(
+            &app.tree,
+            node,
+            DetailsTab::Log,
+            DEFAULT_DATE_FORMAT,
+            &app.strings,
+        )
+        .iter()
+        .map(line_text)
+        .collect();
+        assert_eq!(log, vec!["(暂无日志)"]);
+
+        let en = Strings::new(Lang::En);
+        let content: Vec<String> = detail_lines(
+            &app.tree,
+            node,
+            DetailsTab::Content,
+            DEFAULT_DATE_FORMAT,
+            &en,
+        )
+        .iter()
+        .map(line_text)
+        .collect();
+        assert_eq!(content, vec!["Title: Root", "Rule: (none)"]);
+    }
+
+    #[test]
+    fn test_layout_constraints() {
+        let layout = LayoutConfig::default();
+        assert_eq!(
+            frame_constraints(&layout),
+            [
+                Constraint::Length(3),
+                Constraint::Min(10),
+                Constraint::Length(3)
+            ]
+        );
+        let hidden = LayoutConfig {
+            show_title: false,
+            show_help: false,
+            ..layout
+        };
+        assert_eq!(
+            frame_constraints(&hidden),
+            [
+                Constraint::Length(0),
+                Constraint::Min(10),
+                Constraint::Length(1)
+            ]
+        );
+
+        assert_eq!(
+            stacked_constraints(6, 20),
+            [Constraint::Min(4), Constraint::Length(6)]
+        );
+        // 详情过高时缩小，树保留 MIN_TREE_HEIGHT 行
+        assert_eq!(
+            stacked_constraints(30, 20),
+            [Constraint::Min(4), Constraint::Length(16)]
+        );
+        assert_eq!(
+            stacked_constraints(6, 3),
+            [Constraint::Min(4), Constraint::Length(0)]
+        );
+    }
+
+    #[test]
+    fn test_root_period_groups() {
+        let mut tree = TreeBuilder::new()
+            .node("q1", |b| b.leaf("x"))
+            .leaf("q1 again")
+            .leaf("q2")
+            .build();
+        let ids = tree.root_ids.clone();
+        let march = Local.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        for id in &ids {
+            tree.nodes.get_mut(id).unwrap().created_at = march;
+        }
+        tree.nodes.get_mut(&ids[2]).unwrap().period = Some("2024 Q2".to_string());
+
+        let config = Config {
+            root_grouping: RootGrouping::Quarter,
+            ..Config::default()
+        };
+        let mut app = App::new(tree, config);
+        let headers = group_headers(&app.tree, &app.display_list, RootGrouping::Quarter);
+        assert_eq!(
+            headers,
+            [
+                Some("2024 Q1".to_string()),
+                None,
+                None,
+                Some("2024 Q2".to_string())
+            ]
+        );
+        assert!(
+            group_headers(&app.tree, &app.display_list, RootGrouping::Off)
+                .iter()
+                .all(Option::is_none)
+        );
+
+        // 标题行不参与选择，选中第二个节点时列表中实际选中第三项
+        app.move_down();
+        let mut terminal = Terminal::new(TestBackend::new(60, 24)).unwrap();
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        assert_eq!(app.tree_state.selected(), Some(2));
+        assert!(buffer_text(&terminal).contains("── 2024 Q2 ──"));
+    }
+
+    #[test]
+    fn test_center_selection_with_headers_and_wrapping() {
+        let mut tree = (0..20)
+            .fold(TreeBuilder::new(), |b, i| {
+                let title = if i == 12 {
+                    "一个很长很长的标题，需要折成好几行才能显示完整".to_string()
+                } else {
+                    format!("n{}", i)
+                };
+                b.leaf(&title)
+            })
+            .build();
+        let march = Local.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        for (i, id) in tree.root_ids.clone().iter().enumerate() {
+            let node = tree.nodes.get_mut(id).unwrap();
+            node.created_at = march;
+            if i >= 8 {
+                node.period = Some("2024 Q2".to_string());
+            }
+        }
+        let config = Config {
+            root_grouping: RootGrouping::Quarter,
+            wrap_titles: true,
+            ..Config::default()
+        };
+        let mut app = App::new(tree, config);
+        for _ in 0..14 {
+            app.move_down();
+        }
+        let mut terminal = Terminal::new(TestBackend::new(40, 24)).unwrap();
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        app.dispatch(Action::CenterSelection);
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..24)
+            .map(|y| (0..40).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect();
+        let selected = (0..24)
+            .find(|&y| buffer[(1, y)].modifier.contains(Modifier::REVERSED))
+            .unwrap();
+        // 树区域为第 4~13 行：两个分组标题计入下标，折成 3 行的标题按 3 行计算，选中行上方留 5 行
+        assert_eq!(app.tree_state.offset(), 13);
+        assert_eq!(selected, 4 + 5);
+        assert!(rows[4].contains("n11"));
+        assert!(rows[selected as usize].contains("n14"));
+    }
+
+    #[test]
+    fn test_selected_row_highlight() {
+        let mut app = sample_app();
+        app.move_down();
+        let mut terminal = Terminal::new(TestBackend::new(60, 24)).unwrap();
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+
+        // 标题栏占 0~2 行，树区域边框占第 3 行，第二个节点位于第 5 行
+        let buffer = terminal.backend().buffer();
+        let selected = &buffer[(1, 5)];
+        assert!(selected.modifier.contains(Modifier::REVERSED));
+        assert_eq!(selected.fg, Color::Yellow);
+
+        let other = &buffer[(1, 4)];
+        assert!(!other.modifier.contains(Modifier::REVERSED));
+        assert_eq!(other.fg, Color::Green);
+        assert_eq!(app.tree_state.selected(), Some(1));
+    }
+
+    #[test]
+    fn test_root_glyph() {
+        let theme = Theme::default();
+        assert_eq!(root_glyph(&theme, false), "📋");
+        assert_eq!(root_glyph(&theme, true), "📌");
+
+        let custom = Theme {
+            root_branch: "+".to_string(),
+            root_leaf: "•".to_string(),
+        };
+        assert_eq!(root_glyph(&custom, true), "•");
+    }
+
+    #[test]
+    fn test_body_layout() {
+        assert_eq!(body_layout(80), BodyLayout::Stacked);
+        assert_eq!(body_layout(119), BodyLayout::Stacked);
+        assert_eq!(body_layout(120), BodyLayout::SideBySide);
+        assert_eq!(body_layout(200), BodyLayout::SideBySide);
+    }
+
+    #[test]
+    fn test_scroll_indicators() {
+        assert_eq!(scroll_indicators(0, 5, 10), (false, false));
+        assert_eq!(scroll_indicators(0, 10, 10), (false, false));
+        assert_eq!(scroll_indicators(0, 11, 10), (false, true));
+        assert_eq!(scroll_indicators(3, 13, 10), (true, false));
+        assert_eq!(scroll_indicators(3, 20, 10), (true, true));
+    }
+
+    #[test]
+    fn test_tree_scroll_indicator_rendered() {
+        let mut builder = TreeBuilder::new();
+        for i in 0..30 {
+            builder = builder.leaf(&format!("node {}", i));
+        }
+        let mut app = App::new(builder.build(), Config::default());
+        let mut terminal = Terminal::new(TestBackend::new(40, 24)).unwrap();
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+
+        // 树区域为第 3~14 行，右上边框无 ▲，右下边框有 ▼
+        let buffer = terminal.backend().buffer();
+        assert_ne!(buffer[(37, 3)].symbol(), "▲");
+        assert_eq!(buffer[(37, 14)].symbol(), "▼");
+    }
+
+    #[test]
+    fn test_tree_row_width() {
+        // 40 列：左右边框各 1 列，"▶ " 占 2 列
+        assert_eq!(tree_row_width(40), 36);
+        assert_eq!(tree_row_width(3), 0);
+    }
+
+    #[test]
+    fn test_wrapped_titles_keep_selection() {
+        let tree = TreeBuilder::new()
+            .leaf("一个非常非常非常非常非常非常长的标题")
+            .leaf("短")
+            .build();
+        let mut app = App::new(tree, Config::default());
+        app.dispatch(crate::ui::actions::Action::ToggleWrapTitles);
+        app.move_down();
+        let mut terminal = Terminal::new(TestBackend::new(30, 24)).unwrap();
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+
+        // 第一项折成多行，第二项（选中）位于其后
+        let buffer = terminal.backend().buffer();
+        assert!(!buffer[(1, 5)].modifier.contains(Modifier::REVERSED));
+        let selected_row = (5..20)
+            .find(|&y| buffer[(1, y)].modifier.contains(Modifier::REVERSED))
+            .unwrap();
+        assert!(selected_row > 5);
+        assert_eq!(app.tree_state.selected(), Some(1));
+    }
+
+    fn buffer_text(terminal: &Terminal<TestBackend>) -> String {
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn test_fit_help_items() {
+        let items = help_items("[a] Add  [d] Delete  [q] Quit");
+        assert_eq!(items, ["[a] Add", "[d] Delete", "[q] Quit"]);
+
+        // 全部放得下时不加省略提示
+        assert_eq!(
+            fit_help_items(&items, 30, "…"),
+            "[a] Add  [d] Delete  [q] Quit"
+        );
+        assert_eq!(
+            fit_help_items(&items, 29, "…"),
+            "[a] Add  [d] Delete  [q] Quit"
+        );
+        // 预留省略提示的宽度后能放几条就放几条
+        assert_eq!(fit_help_items(&items, 28, "…"), "[a] Add  [d] Delete  …");
+        assert_eq!(fit_help_items(&items, 20, "…"), "[a] Add  …");
+        assert_eq!(fit_help_items(&items, 5, "…"), "…");
+
+        // 按显示宽度计算，中文占两列
+        let zh = help_items("[a] 添加  [d] 删除  [q] 退出");
+        assert_eq!(fit_help_items(&zh, 17, "…更多(?)"), "…更多(?)");
+        assert_eq!(fit_help_items(&zh, 18, "…更多(?)"), "[a] 添加  …更多(?)");
+    }
+
+    #[test]
+    fn test_help_item_parts() {
+        assert_eq!(help_item_parts("[a] 添加"), Some(("[a]", "添加")));
+        assert_eq!(help_item_parts("无按键"), None);
+        for lang in [Lang::Zh, Lang::En] {
+            let items = help_items(Strings::new(lang).get(Key::HelpNormal));
+            // 每个条目都能拆出方括号包围的按键与非空说明
+            for item in &items {
+                let (keys, label) = help_item_parts(item).unwrap();
+                assert!(keys.starts_with('[') && keys.ends_with(']'), "{}", item);
+                assert!(!label.is_empty(), "{}", item);
+            }
+            assert!(items.iter().any(|item| item.starts_with("[]o / [o] ")));
+        }
+        assert_eq!(
+            help_item_parts("[]o / [o] 逾期节点"),
+            Some(("[]o / [o]", "逾期节点"))
+        );
+    }
+
+    #[test]
+    fn test_move_mode_row_styles() {
+        let node = FocusNode::new("moving".to_string(), String::new(), None);
+        let other = FocusNode::new("other".to_string(), String::new(), None);
+        let moving = AppMode::MovingNode(node.id.clone());
+
+        let source = row_style(&node, &moving);
+        assert!(
+            source
+                .add_modifier
+                .contains(Modifier::DIM | Modifier::ITALIC)
+        );
+        assert_eq!(source.fg, Some(Color::Green));
+        assert_eq!(
+            row_style(&other, &moving),
+            Style::default().fg(Color::Green)
+        );
+        assert_eq!(
+            row_style(&node, &AppMode::Normal),
+            Style::default().fg(Color::Green)
+        );
+
+        assert_eq!(highlight_style(&moving).fg, Some(Color::Magenta));
+        assert_eq!(highlight_style(&AppMode::Normal).fg, Some(Color::Yellow));
+        assert!(
+            highlight_style(&moving)
+                .sub_modifier
+                .contains(Modifier::DIM)
+        );
+    }
+
+    #[test]
+    fn test_title_style() {
+        let mut node = FocusNode::new("n".to_string(), String::new(), None);
+        assert_eq!(title_style(&node, None), Style::default());
+
+        node.status = NodeStatus::Failed;
+        let style = title_style(&node, Some(Color::Red));
+        assert_eq!(style.fg, Some(Color::Red));
+        assert!(style.add_modifier.contains(Modifier::CROSSED_OUT));
+        assert!(style.add_modifier.contains(Modifier::DIM));
+
+        node.status = NodeStatus::Completed;
+        assert!(
+            !title_style(&node, Some(Color::Red))
+                .add_modifier
+                .contains(Modifier::CROSSED_OUT)
+        );
+    }
+
+    #[test]
+    fn test_indent_guides() {
+        // root
+        //   ├── a
+        //   │ ├── a1
+        //   │   ├── a1x
+        //   ├── b
+        //       ├── b1
+        let depths = [0, 1, 2, 3, 1, 2];
+        let text = |index: usize| -> String {
+            indent_guides(&depths, index)
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        };
+        assert_eq!(text(0), "");
+        assert_eq!(text(1), "  ");
+        assert_eq!(text(2), "  │ ");
+        assert_eq!(text(3), "  │   ");
+        assert_eq!(text(5), "    ");
+
+        let guides = indent_guides(&depths, 3);
+        assert_eq!(guides[1].style.fg, Some(Color::DarkGray));
+        assert_eq!(guides[2].style.fg, None);
+    }
+
+    #[test]
+    fn test_empty_tree_hint() {
+        // 使用英文界面，避免宽字符占位影响文本比较
+        let config = Config {
+            lang: Lang::En,
+            ..Config::default()
+        };
+        let hint = Strings::new(Lang::En).get(Key::TreeEmptyHint);
+        let mut app = App::new(FocusTree::new(), config.clone());
+        let mut terminal = Terminal::new(TestBackend::new(100, 24)).unwrap();
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        assert!(buffer_text(&terminal).contains(hint));
+
+        // 有节点时正常渲染列表，不显示提示
+        let tree = TreeBuilder::new().node("Root", |b| b.leaf("Child")).build();
+        let mut app = App::new(tree, config);
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let text = buffer_text(&terminal);
+        assert!(!text.contains(hint));
+        assert!(text.contains("Root") && text.contains("Child"));
+    }
+
+    #[test]
+    fn test_render_short_terminal() {
+        let mut app = sample_app();
+        app.message = Some("节点已添加".to_string());
+        for (width, height) in [(160, 40), (80, 24), (40, 10), (20, 4), (1, 1)] {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal.draw(|f| render(f, &mut app)).unwrap();
+        }
+    }
+}
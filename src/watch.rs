@@ -0,0 +1,90 @@
+//! 数据文件外部修改的处理
+//!
+//! 是否重新加载的决策总是可用；实际的文件监视需启用 `watch` feature。
+
+/// 数据文件被外部修改后的处理方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReloadDecision {
+    /// 内存中没有未保存的修改，直接重新加载
+    Reload,
+    /// 内存中有未保存的修改，询问保留还是放弃
+    Ask,
+}
+
+/// 根据内存中的树是否有未保存修改决定如何处理外部修改
+pub fn reload_decision(dirty: bool) -> ReloadDecision {
+    if dirty {
+        ReloadDecision::Ask
+    } else {
+        ReloadDecision::Reload
+    }
+}
+
+#[cfg(feature = "watch")]
+pub use watcher::FileWatcher;
+
+#[cfg(feature = "watch")]
+mod watcher {
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc::{Receiver, channel};
+    use std::time::{Duration, Instant};
+
+    use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+    /// 最后一次文件事件之后静默多久才视为修改完成
+    const DEBOUNCE: Duration = Duration::from_millis(500);
+
+    /// 监视单个数据文件，对连续的文件事件去抖
+    pub struct FileWatcher {
+        _watcher: RecommendedWatcher,
+        events: Receiver<notify::Result<Event>>,
+        path: PathBuf,
+        last_event: Option<Instant>,
+    }
+
+    impl FileWatcher {
+        /// 监视文件所在目录（编辑器常以重命名方式替换文件）
+        pub fn new(path: &Path) -> notify::Result<Self> {
+            let (tx, events) = channel();
+            let mut watcher = notify::recommended_watcher(tx)?;
+            let dir = path.parent().unwrap_or(Path::new("."));
+            watcher.watch(dir, RecursiveMode::NonRecursive)?;
+            Ok(Self {
+                _watcher: watcher,
+                events,
+                path: path.to_path_buf(),
+                last_event: None,
+            })
+        }
+
+        /// 文件在去抖间隔内不再变化时返回 true（每次修改只返回一次）
+        pub fn poll_changed(&mut self) -> bool {
+            while let Ok(event) = self.events.try_recv() {
+                if let Ok(event) = event
+                    && !event.kind.is_access()
+                    && event.paths.iter().any(|p| p == &self.path)
+                {
+                    self.last_event = Some(Instant::now());
+                }
+            }
+            match self.last_event {
+                Some(at) if at.elapsed() >= DEBOUNCE => {
+                    self.last_event = None;
+                    true
+                }
+                _ => false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_decision() {
+        assert_eq!(reload_decision(false), ReloadDecision::Reload);
+        assert_eq!(reload_decision(true), ReloadDecision::Ask);
+    }
+}